@@ -4,8 +4,8 @@ import router from '../../src/api/router';
 import { statsManager } from '../../src/utils/stats';
 
 // Mock dependencies
-jest.mock('../../src/db', () => ({
-  db: {
+jest.mock('../../src/db', () => {
+  const mockDb = {
     select: jest.fn().mockReturnThis(),
     from: jest.fn().mockReturnThis(),
     orderBy: jest.fn().mockReturnThis(),
@@ -13,8 +13,9 @@ jest.mock('../../src/db', () => ({
     eq: jest.fn().mockReturnThis(),
     count: jest.fn(),
     limit: jest.fn().mockResolvedValue([]),
-  },
-}));
+  };
+  return { db: mockDb, readDb: mockDb };
+});
 
 jest.mock('../../src/utils/logger', () => ({
   logger: {
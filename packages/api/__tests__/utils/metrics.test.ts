@@ -0,0 +1,57 @@
+import { Counter, Gauge, Histogram } from '../../src/utils/metrics';
+
+describe('metrics', () => {
+  it('should accumulate counters per label set', () => {
+    const counter = new Counter('test_requests_total', 'Requests');
+    counter.inc({ route: '/a', status: '200' });
+    counter.inc({ status: '200', route: '/a' });
+    counter.inc({ route: '/b', status: '500' }, 3);
+
+    expect(counter.render()).toBe([
+      '# HELP test_requests_total Requests',
+      '# TYPE test_requests_total counter',
+      'test_requests_total{route="/a",status="200"} 2',
+      'test_requests_total{route="/b",status="500"} 3',
+    ].join('\n'));
+  });
+
+  it('should render gauges without labels', () => {
+    const gauge = new Gauge('test_connections', 'Connections');
+    gauge.set(4);
+    gauge.set(2);
+
+    expect(gauge.render()).toContain('test_connections 2');
+  });
+
+  it('should stop rendering removed gauge label sets', () => {
+    const gauge = new Gauge('test_lag', 'Lag');
+    gauge.set(3, { client: '1' });
+    gauge.set(5, { client: '2' });
+    gauge.remove({ client: '1' });
+
+    const lines = gauge.render().split('\n');
+    expect(lines).not.toContain('test_lag{client="1"} 3');
+    expect(lines).toContain('test_lag{client="2"} 5');
+  });
+
+  it('should render cumulative histogram buckets', () => {
+    const histogram = new Histogram('test_duration_seconds', 'Latency', [0.1, 1]);
+    histogram.observe({ route: '/a' }, 0.05);
+    histogram.observe({ route: '/a' }, 0.5);
+    histogram.observe({ route: '/a' }, 2);
+
+    const lines = histogram.render().split('\n');
+    expect(lines).toContain('test_duration_seconds_bucket{route="/a",le="0.1"} 1');
+    expect(lines).toContain('test_duration_seconds_bucket{route="/a",le="1"} 2');
+    expect(lines).toContain('test_duration_seconds_bucket{route="/a",le="+Inf"} 3');
+    expect(lines).toContain('test_duration_seconds_sum{route="/a"} 2.55');
+    expect(lines).toContain('test_duration_seconds_count{route="/a"} 3');
+  });
+
+  it('should escape label values', () => {
+    const counter = new Counter('test_total', 'Test');
+    counter.inc({ url: 'a"b\\c' });
+
+    expect(counter.render()).toContain('test_total{url="a\\"b\\\\c"} 1');
+  });
+});
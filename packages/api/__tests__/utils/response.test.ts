@@ -0,0 +1,43 @@
+import { fitToBudget, jsonBytes, pickFields } from '../../src/utils/response';
+
+describe('response helpers', () => {
+  describe('fitToBudget', () => {
+    const items = [{ n: 1 }, { n: 2 }, { n: 3 }];
+
+    it('should keep every item when the array fits', () => {
+      const result = fitToBudget(items, jsonBytes(items));
+      expect(result.items).toEqual(items);
+      expect(result.truncated).toBe(false);
+    });
+
+    it('should drop trailing items that exceed the budget', () => {
+      const result = fitToBudget(items, jsonBytes(items) - 1);
+      expect(result.items).toEqual(items.slice(0, 2));
+      expect(result.truncated).toBe(true);
+      expect(jsonBytes(result.items)).toBeLessThanOrEqual(jsonBytes(items) - 1);
+    });
+
+    it('should return no items when the first one does not fit', () => {
+      const result = fitToBudget(items, 4);
+      expect(result.items).toEqual([]);
+      expect(result.truncated).toBe(true);
+    });
+
+    it('should count multi-byte characters by their encoded size', () => {
+      const result = fitToBudget(['é'], jsonBytes(['e']));
+      expect(result.truncated).toBe(true);
+    });
+  });
+
+  describe('pickFields', () => {
+    const tx = { hash: '0x1', from: '0x2', value: '10' };
+
+    it('should return the object unchanged without a field list', () => {
+      expect(pickFields(tx)).toBe(tx);
+    });
+
+    it('should keep only the requested fields that exist', () => {
+      expect(pickFields(tx, ['hash', 'value', 'missing'])).toEqual({ hash: '0x1', value: '10' });
+    });
+  });
+});
@@ -145,6 +145,33 @@ describe('StatsManager', () => {
       expect(stats?.tps).toBeCloseTo(expectedTPS, 1);
     });
 
+    it('should use millisecond timestamps for sub-second blocks', () => {
+      const block1 = {
+        number: 100,
+        timestamp: 1616451600,
+        timestampMs: 1616451600000,
+        transactionCount: 100,
+        gasUsed: 4000000,
+      };
+      
+      const block2 = {
+        number: 101,
+        timestamp: 1616451600, // Same second
+        timestampMs: 1616451600250,
+        transactionCount: 100,
+        gasUsed: 4000000,
+      };
+      
+      statsManager.addBlock(block1);
+      statsManager.addBlock(block2);
+      
+      const stats = statsManager.getStats();
+      
+      // 200 transactions over 250ms
+      expect(stats?.tps).toBeCloseTo(800, 1);
+      expect(stats?.gasPerSecond).toBeCloseTo(32000000, 1);
+    });
+
     it('should handle invalid time spans by using fallback calculations', () => {
       // Use a single block to test fallback calculation
       const block1 = {
@@ -207,6 +234,38 @@ describe('StatsManager', () => {
     });
   });
 
+  describe('incomplete windows', () => {
+    const makeBlock = (number: number) => ({
+      number,
+      timestamp: 1616451600 + (number - 100) * 2,
+      transactionCount: 10,
+      gasUsed: 1000000,
+    });
+
+    it('should only use the contiguous run ending at the latest block', () => {
+      [100, 101, 105, 106, 107].forEach((number) => statsManager.addBlock(makeBlock(number)));
+
+      const stats = statsManager.getStats();
+      expect(stats?.fromBlock).toBe(105);
+      expect(stats?.toBlock).toBe(107);
+      expect(stats?.complete).toBe(false);
+      // 30 transactions over the 4 seconds between blocks 105 and 107
+      expect(stats?.tps).toBeCloseTo(30 / 4, 2);
+    });
+
+    it('should ignore backfilled blocks older than a full window', () => {
+      for (let number = 200; number < 210; number++) {
+        statsManager.addBlock(makeBlock(number));
+      }
+      statsManager.addBlock(makeBlock(150));
+
+      const stats = statsManager.getStats();
+      expect(stats?.fromBlock).toBe(200);
+      expect(stats?.toBlock).toBe(209);
+      expect(stats?.complete).toBe(true);
+    });
+  });
+
   describe('stats caching', () => {
     it('should cache stats after calculation', () => {
       const block = {
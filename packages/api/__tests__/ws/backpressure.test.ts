@@ -0,0 +1,44 @@
+import { nextMode, summarizeBlock } from '../../src/ws/backpressure';
+
+describe('WebSocket back-pressure', () => {
+  const limits = { summaryBytes: 1000, dropBytes: 10000 };
+
+  it('should keep clients with a short queue on full updates', () => {
+    expect(nextMode('full', 0, limits)).toBe('full');
+    expect(nextMode('full', 1000, limits)).toBe('full');
+  });
+
+  it('should downgrade clients whose queue exceeds the summary threshold', () => {
+    expect(nextMode('full', 1001, limits)).toBe('summary');
+    expect(nextMode('summary', 5000, limits)).toBe('summary');
+  });
+
+  it('should only restore full updates once the queue has mostly drained', () => {
+    expect(nextMode('summary', 800, limits)).toBe('summary');
+    expect(nextMode('summary', 250, limits)).toBe('full');
+  });
+
+  it('should drop clients whose queue exceeds the hard limit', () => {
+    expect(nextMode('full', 10001, limits)).toBe('drop');
+    expect(nextMode('summary', 10001, limits)).toBe('drop');
+  });
+
+  it('should summarize blocks without their transactions', () => {
+    const summary = summarizeBlock({
+      number: 1000,
+      hash: '0x123',
+      timestamp: 1616451600,
+      transactionCount: 150,
+      gasUsed: 21000,
+      transactions: [{ hash: '0xabc' }],
+    } as any);
+
+    expect(summary).toEqual({
+      number: 1000,
+      hash: '0x123',
+      timestamp: 1616451600,
+      transactionCount: 150,
+      gasUsed: 21000,
+    });
+  });
+});
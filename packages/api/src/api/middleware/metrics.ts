@@ -0,0 +1,43 @@
+import { randomUUID } from 'crypto';
+import { Request, Response, NextFunction } from 'express';
+import { logger } from '../../utils/logger';
+import { httpRequestDuration, httpRequests } from '../../utils/metrics';
+
+// Requests slower than this are logged as warnings
+const SLOW_REQUEST_MS = Number(process.env.SLOW_REQUEST_MS) || 1000;
+
+/**
+ * Middleware that gives every request an id (echoed in `x-request-id`), records its status
+ * and latency in the request metrics, and logs it once the response is sent
+ */
+export const requestMetrics = (req: Request, res: Response, next: NextFunction) => {
+  const start = process.hrtime.bigint();
+  const requestId = (req.headers['x-request-id'] as string | undefined) || randomUUID();
+  res.setHeader('x-request-id', requestId);
+
+  res.on('finish', () => {
+    const durationMs = Number(process.hrtime.bigint() - start) / 1e6;
+    // Label by route pattern rather than URL so block numbers don't explode the label set
+    const route = req.route ? `${req.baseUrl}${req.route.path}` : 'unmatched';
+
+    httpRequests.inc({ method: req.method, route, status: String(res.statusCode) });
+    httpRequestDuration.observe({ method: req.method, route }, durationMs / 1000);
+
+    const entry = {
+      requestId,
+      method: req.method,
+      url: req.originalUrl,
+      route,
+      status: res.statusCode,
+      durationMs: Math.round(durationMs),
+    };
+
+    if (durationMs >= SLOW_REQUEST_MS) {
+      logger.warn(entry, `Slow request: ${req.method} ${req.originalUrl} ${res.statusCode} - ${Math.round(durationMs)}ms`);
+    } else {
+      logger.debug(entry, `${req.method} ${req.originalUrl} ${res.statusCode} - ${Math.round(durationMs)}ms`);
+    }
+  });
+
+  next();
+};
@@ -1,18 +1,44 @@
 import express from 'express';
-import { db } from '../db';
-import { blocks, BlockStats } from '../db/schema';
-import { desc, eq, sql, count } from 'drizzle-orm';
+import { readDb } from '../db';
+import { addressActivity, addressNames, auditLog, blockFees, blocks, chainStatus, chainWatermarks, contractCode, deployedCode, gasRollups, incompleteRanges, logs, nftTransfers, receipts, throughputRollups, tokens, tokenTransfers, topContracts, transactions, BlockStats, TransactionJson } from '../db/schema';
+import { and, arrayContains, asc, desc, eq, gte, lte, or, sql, count, getTableColumns } from 'drizzle-orm';
 import { validate } from './middleware/validate';
-import { blockNumberSchema, paginationSchema } from './schemas';
+import { addressSchema, addressTransactionsQuerySchema, auditQuerySchema, blockFeesQuerySchema, blockListQuerySchema, blockNumberSchema, blockQuerySchema, logsQuerySchema, nftTransfersQuerySchema, rollupsQuerySchema, tokenAddressSchema, tokenTransfersQuerySchema, topContractsQuerySchema, transactionHashSchema } from './schemas';
 import { logger } from '../utils/logger';
 import { statsManager } from '../utils/stats';
 import { cacheMiddleware } from '../utils/cache';
+import { fitToBudget, jsonBytes, pickFields } from '../utils/response';
 
 const router = express.Router();
 
+// Columns of the blocks table, for ?fields= selection
+const blockColumns = getTableColumns(blocks);
+type BlockColumn = (typeof blockColumns)[keyof typeof blockColumns];
+
+const selectBlockColumns = (fields: string[]) =>
+  Object.fromEntries(fields.map((field) => [field, blockColumns[field as keyof typeof blockColumns]])) as Record<string, BlockColumn>;
+
+// Fields of a transaction row, as returned by the transaction routes
+const transactionFields = {
+  hash: transactions.hash,
+  blockNumber: transactions.blockNumber,
+  blockHash: transactions.blockHash,
+  transactionIndex: transactions.transactionIndex,
+  from: transactions.fromAddress,
+  to: transactions.toAddress,
+  value: transactions.value,
+  gas: transactions.gas,
+  gasPrice: transactions.gasPrice,
+  nonce: transactions.nonce,
+  input: transactions.input,
+};
+
+// Fields returned by /blocks/latest when none are requested
+const DEFAULT_BLOCK_LIST_FIELDS = ['number', 'hash', 'parentHash', 'timestamp', 'timestampMs', 'transactionCount', 'tags', 'transactions'];
+
 // Get latest blocks
 router.get('/blocks/latest', 
-  validate(paginationSchema, 'query'),
+  validate(blockListQuerySchema, 'query'),
   cacheMiddleware(),
   async (req, res) => {
     try {
@@ -20,35 +46,45 @@ router.get('/blocks/latest',
       const limit = req.query.limit as number;
       // @ts-ignore
       const offset = req.query.offset as number;
+      // @ts-ignore
+      const tag = req.query.tag as string | undefined;
+      // @ts-ignore
+      const fields = (req.query.fields as string[] | undefined) ?? DEFAULT_BLOCK_LIST_FIELDS;
+      // @ts-ignore
+      const maxBytes = req.query.maxBytes as number;
       
-      logger.info(`Fetching latest blocks with limit ${limit} and offset ${offset}`);
+      logger.info(`Fetching latest blocks with limit ${limit} and offset ${offset}${tag ? ` tagged ${tag}` : ''}`);
+      
+      // Optionally restrict to blocks carrying a tag (uses the GIN index on tags)
+      const tagFilter = tag ? arrayContains(blocks.tags, [tag]) : undefined;
       
       // Get total block count
-      const [{ value: total }] = await db
+      const [{ value: total }] = await readDb
         .select({ value: count() })
-        .from(blocks);
-      
-      // Get latest blocks
-      const latestBlocks = await db.select({
-        number: blocks.number,
-        hash: blocks.hash,
-        parentHash: blocks.parentHash,
-        timestamp: blocks.timestamp,
-        transactionCount: blocks.transactionCount,
-        transactions: blocks.transactions,
-      })
         .from(blocks)
+        .where(tagFilter);
+      
+      // Get latest blocks; unselected columns (e.g. the transactions JSONB) are never read
+      const latestBlocks = await readDb.select(selectBlockColumns(fields))
+        .from(blocks)
+        .where(tagFilter)
         .orderBy(desc(blocks.number))
         .limit(limit)
         .offset(offset);
         
       logger.info(`Found ${latestBlocks.length} blocks`);
       
+      // Drop trailing blocks that would push the response over the byte budget
+      const envelope = { status: 'success', data: { blocks: [], total, truncated: true, nextOffset: offset + limit } };
+      const budgeted = fitToBudget(latestBlocks, maxBytes - jsonBytes(envelope));
+      
       res.json({
         status: 'success',
         data: {
-          blocks: latestBlocks,
-          total
+          blocks: budgeted.items,
+          total,
+          truncated: budgeted.truncated,
+          ...(budgeted.truncated ? { nextOffset: offset + budgeted.items.length } : {})
         }
       });
     } catch (error) {
@@ -64,14 +100,21 @@ router.get('/blocks/latest',
 // Get block by number
 router.get('/blocks/:number', 
   validate(blockNumberSchema, 'params'),
+  validate(blockQuerySchema, 'query'),
   cacheMiddleware(),
   async (req, res) => {
     try {
       const blockNumber = req.params.number as unknown as number;
+      // @ts-ignore
+      const fields = req.query.fields as string[] | undefined;
+      // @ts-ignore
+      const txFields = req.query.txFields as string[] | undefined;
+      // @ts-ignore
+      const maxBytes = req.query.maxBytes as number;
       
       logger.info(`Fetching block ${blockNumber}`);
       
-      const blockData = await db.select()
+      const blockData = await readDb.select(selectBlockColumns(fields ?? Object.keys(blockColumns)))
         .from(blocks)
         .where(eq(blocks.number, blockNumber))
         .limit(1);
@@ -84,12 +127,48 @@ router.get('/blocks/:number',
         });
       }
       
+      // Blocks at or below the finalized watermark can no longer be reorged
+      const [finalizedWatermark] = await readDb.select({ blockNumber: chainWatermarks.blockNumber })
+        .from(chainWatermarks)
+        .where(eq(chainWatermarks.tag, 'finalized'))
+        .limit(1);
+      
+      // Receipts are only present when the indexer fetches them, and only read when requested
+      const withTransactions = 'transactions' in blockData[0];
+      const withReceipts = withTransactions && (!txFields || txFields.includes('receipt'));
+      const blockReceipts = !withReceipts ? [] : await readDb.select({
+        transactionHash: receipts.transactionHash,
+        status: receipts.status,
+        gasUsed: receipts.gasUsed,
+        effectiveGasPrice: receipts.effectiveGasPrice,
+        contractAddress: receipts.contractAddress,
+        logs: receipts.logs,
+      })
+        .from(receipts)
+        .where(eq(receipts.blockNumber, blockNumber));
+      
+      const receiptsByHash = new Map(blockReceipts.map(({ transactionHash, ...receipt }) => [transactionHash, receipt]));
+      const transactions = ((blockData[0].transactions ?? []) as TransactionJson[]).map((tx) => pickFields({
+        ...tx,
+        receipt: receiptsByHash.get(tx.hash) ?? null
+      }, txFields));
+      
       logger.info(`Successfully retrieved block ${blockNumber}`);
       
+      const block = {
+        ...blockData[0],
+        finalized: finalizedWatermark ? blockNumber <= finalizedWatermark.blockNumber : false
+      };
+      
+      // Transactions are the only unbounded part of a block, so they absorb the byte budget
+      const envelope = { status: 'success', data: { block: { ...block, transactions: [] }, truncated: true } };
+      const budgeted = fitToBudget(transactions, maxBytes - jsonBytes(envelope));
+      
       res.json({
         status: 'success',
         data: {
-          block: blockData[0]
+          block: withTransactions ? { ...block, transactions: budgeted.items } : block,
+          truncated: budgeted.truncated
         }
       });
     } catch (error) {
@@ -102,6 +181,712 @@ router.get('/blocks/:number',
   }
 );
 
+// Get a transaction by hash, with its receipt when indexed
+router.get('/transactions/:hash',
+  validate(transactionHashSchema, 'params'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const hash = req.params.hash;
+      
+      logger.info(`Fetching transaction ${hash}`);
+      
+      const [transaction] = await readDb.select({
+        ...transactionFields,
+        receipt: {
+          status: receipts.status,
+          gasUsed: receipts.gasUsed,
+          effectiveGasPrice: receipts.effectiveGasPrice,
+          contractAddress: receipts.contractAddress,
+        },
+      })
+        .from(transactions)
+        .leftJoin(receipts, eq(receipts.transactionHash, transactions.hash))
+        .where(eq(transactions.hash, hash))
+        .limit(1);
+      
+      if (!transaction) {
+        logger.warn(`Transaction ${hash} not found`);
+        return res.status(404).json({ 
+          status: 'error',
+          message: 'Transaction not found' 
+        });
+      }
+      
+      res.json({
+        status: 'success',
+        data: {
+          transaction
+        }
+      });
+    } catch (error) {
+      logger.error(`Error fetching transaction ${req.params.hash}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get event logs by contract address and/or event signature
+router.get('/logs',
+  validate(logsQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      // @ts-ignore
+      const offset = req.query.offset as number;
+      const address = req.query.address as string | undefined;
+      const topic0 = req.query.topic0 as string | undefined;
+      
+      // Only filtered queries can use the address/topic0 indexes
+      if (!address && !topic0) {
+        return res.status(400).json({
+          status: 'error',
+          message: 'At least one of address or topic0 is required'
+        });
+      }
+      
+      logger.info(`Fetching logs for address ${address ?? '*'} topic0 ${topic0 ?? '*'} with limit ${limit} and offset ${offset}`);
+      
+      const matchingLogs = await readDb.select()
+        .from(logs)
+        .where(and(
+          address ? eq(logs.address, address) : undefined,
+          topic0 ? eq(logs.topic0, topic0) : undefined
+        ))
+        .orderBy(desc(logs.blockNumber), desc(logs.logIndex))
+        .limit(limit)
+        .offset(offset);
+      
+      res.json({
+        status: 'success',
+        data: {
+          logs: matchingLogs
+        }
+      });
+    } catch (error) {
+      logger.error('Error fetching logs:', error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get the activity summary of an address
+router.get('/addresses/:address',
+  validate(addressSchema, 'params'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const address = req.params.address;
+      
+      logger.info(`Fetching activity of address ${address}`);
+      
+      const [activity] = await readDb.select({
+        name: addressNames.name,
+        firstSeenBlock: addressActivity.firstSeenBlock,
+        lastSeenBlock: addressActivity.lastSeenBlock,
+        txCount: addressActivity.txCount,
+      })
+        .from(addressActivity)
+        .leftJoin(addressNames, eq(addressNames.address, addressActivity.address))
+        .where(eq(addressActivity.address, address))
+        .limit(1);
+      
+      if (!activity) {
+        logger.warn(`Address ${address} not found`);
+        return res.status(404).json({ 
+          status: 'error',
+          message: 'Address not found' 
+        });
+      }
+      
+      res.json({
+        status: 'success',
+        data: {
+          address,
+          ...activity
+        }
+      });
+    } catch (error) {
+      logger.error(`Error fetching activity of address ${req.params.address}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get the transactions an address sent or received, newest first
+router.get('/addresses/:address/transactions',
+  validate(addressSchema, 'params'),
+  validate(addressTransactionsQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const address = req.params.address;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      // @ts-ignore
+      const offset = req.query.offset as number;
+      const direction = req.query.direction as 'from' | 'to' | undefined;
+      
+      logger.info(`Fetching transactions ${direction ? `${direction} ` : 'of '}${address} with limit ${limit} and offset ${offset}`);
+      
+      const sent = eq(transactions.fromAddress, address);
+      const received = eq(transactions.toAddress, address);
+      const addressTransactions = await readDb.select(transactionFields)
+        .from(transactions)
+        .where(direction === 'from' ? sent : direction === 'to' ? received : or(sent, received))
+        .orderBy(desc(transactions.blockNumber), desc(transactions.transactionIndex))
+        .limit(limit)
+        .offset(offset);
+      
+      res.json({
+        status: 'success',
+        data: {
+          address,
+          transactions: addressTransactions
+        }
+      });
+    } catch (error) {
+      logger.error(`Error fetching transactions of address ${req.params.address}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get the bytecode of a contract created in an indexed block
+router.get('/addresses/:address/code',
+  validate(addressSchema, 'params'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const address = req.params.address;
+      
+      logger.info(`Fetching code of contract ${address}`);
+      
+      // A contract can be redeployed at the same address (CREATE2 after selfdestruct), use the latest
+      const [deployment] = await readDb.select({
+        blockNumber: deployedCode.blockNumber,
+        transactionHash: deployedCode.transactionHash,
+        codeHash: deployedCode.codeHash,
+        code: contractCode.code,
+      })
+        .from(deployedCode)
+        .innerJoin(contractCode, eq(contractCode.codeHash, deployedCode.codeHash))
+        .where(eq(deployedCode.address, address))
+        .orderBy(desc(deployedCode.blockNumber))
+        .limit(1);
+      
+      if (!deployment) {
+        logger.warn(`No deployment found for contract ${address}`);
+        return res.status(404).json({ 
+          status: 'error',
+          message: 'Contract not found' 
+        });
+      }
+      
+      res.json({
+        status: 'success',
+        data: {
+          address,
+          ...deployment
+        }
+      });
+    } catch (error) {
+      logger.error(`Error fetching code of contract ${req.params.address}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get the metadata of a token seen in transfers
+router.get('/tokens/:address',
+  validate(tokenAddressSchema, 'params'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const address = req.params.address;
+      
+      logger.info(`Fetching metadata of token ${address}`);
+      
+      const [token] = await readDb.select({
+        address: tokens.address,
+        standard: tokens.standard,
+        name: tokens.name,
+        symbol: tokens.symbol,
+        decimals: tokens.decimals,
+        fetchedAt: tokens.fetchedAt,
+      })
+        .from(tokens)
+        .where(eq(tokens.address, address))
+        .limit(1);
+      
+      if (!token) {
+        logger.warn(`Token ${address} not found`);
+        return res.status(404).json({ 
+          status: 'error',
+          message: 'Token not found' 
+        });
+      }
+      
+      res.json({
+        status: 'success',
+        data: token
+      });
+    } catch (error) {
+      logger.error(`Error fetching metadata of token ${req.params.address}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get transfers of an ERC-20 token
+router.get('/tokens/:address/transfers',
+  validate(tokenAddressSchema, 'params'),
+  validate(tokenTransfersQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const token = req.params.address;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      // @ts-ignore
+      const offset = req.query.offset as number;
+      const account = req.query.account as string | undefined;
+      
+      logger.info(`Fetching transfers of token ${token}${account ? ` involving ${account}` : ''} with limit ${limit} and offset ${offset}`);
+      
+      const transfers = await readDb.select({
+        blockNumber: tokenTransfers.blockNumber,
+        logIndex: tokenTransfers.logIndex,
+        transactionHash: tokenTransfers.transactionHash,
+        from: tokenTransfers.fromAddress,
+        to: tokenTransfers.toAddress,
+        amount: tokenTransfers.amount,
+      })
+        .from(tokenTransfers)
+        .where(and(
+          eq(tokenTransfers.token, token),
+          account ? or(eq(tokenTransfers.fromAddress, account), eq(tokenTransfers.toAddress, account)) : undefined
+        ))
+        .orderBy(desc(tokenTransfers.blockNumber), desc(tokenTransfers.logIndex))
+        .limit(limit)
+        .offset(offset);
+      
+      res.json({
+        status: 'success',
+        data: {
+          token,
+          transfers
+        }
+      });
+    } catch (error) {
+      logger.error(`Error fetching transfers of token ${req.params.address}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get transfers of an ERC-721 or ERC-1155 collection
+router.get('/nfts/:address/transfers',
+  validate(tokenAddressSchema, 'params'),
+  validate(nftTransfersQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const token = req.params.address;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      // @ts-ignore
+      const offset = req.query.offset as number;
+      const tokenId = req.query.tokenId as string | undefined;
+      const account = req.query.account as string | undefined;
+      
+      logger.info(`Fetching NFT transfers of ${token}${tokenId ? ` token ${tokenId}` : ''}${account ? ` involving ${account}` : ''} with limit ${limit} and offset ${offset}`);
+      
+      const transfers = await readDb.select({
+        blockNumber: nftTransfers.blockNumber,
+        logIndex: nftTransfers.logIndex,
+        batchIndex: nftTransfers.batchIndex,
+        transactionHash: nftTransfers.transactionHash,
+        standard: nftTransfers.standard,
+        operator: nftTransfers.operator,
+        from: nftTransfers.fromAddress,
+        to: nftTransfers.toAddress,
+        tokenId: nftTransfers.tokenId,
+        amount: nftTransfers.amount,
+      })
+        .from(nftTransfers)
+        .where(and(
+          eq(nftTransfers.token, token),
+          tokenId ? eq(nftTransfers.tokenId, tokenId) : undefined,
+          account ? or(eq(nftTransfers.fromAddress, account), eq(nftTransfers.toAddress, account)) : undefined
+        ))
+        .orderBy(desc(nftTransfers.blockNumber), desc(nftTransfers.logIndex), desc(nftTransfers.batchIndex))
+        .limit(limit)
+        .offset(offset);
+      
+      res.json({
+        status: 'success',
+        data: {
+          token,
+          transfers
+        }
+      });
+    } catch (error) {
+      logger.error(`Error fetching NFT transfers of ${req.params.address}:`, error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get finality watermarks
+router.get('/finality', cacheMiddleware(), async (req, res) => {
+  try {
+    logger.info('Fetching finality watermarks');
+    
+    const watermarks = await readDb.select({
+      tag: chainWatermarks.tag,
+      blockNumber: chainWatermarks.blockNumber,
+      blockHash: chainWatermarks.blockHash,
+      updatedAt: chainWatermarks.updatedAt,
+    })
+      .from(chainWatermarks);
+    
+    const byTag = Object.fromEntries(watermarks.map(({ tag, ...rest }) => [tag, rest]));
+    
+    res.json({
+      status: 'success',
+      data: {
+        finalized: byTag.finalized ?? null,
+        safe: byTag.safe ?? null
+      }
+    });
+  } catch (error) {
+    logger.error('Error fetching finality watermarks:', error);
+    res.status(500).json({ 
+      status: 'error',
+      message: 'Internal server error' 
+    });
+  }
+});
+
+// Get chain/indexer health
+router.get('/status', async (req, res) => {
+  try {
+    logger.info('Fetching chain status');
+    
+    const [status] = await readDb.select({
+      status: chainStatus.status,
+      chainHead: chainStatus.chainHead,
+      chainHeadTimestamp: chainStatus.chainHeadTimestamp,
+      indexedBlock: chainStatus.indexedBlock,
+      indexedBlockTimestamp: chainStatus.indexedBlockTimestamp,
+      detail: chainStatus.detail,
+      earliestAvailableBlock: chainStatus.earliestAvailableBlock,
+      statusSince: chainStatus.statusSince,
+      updatedAt: chainStatus.updatedAt,
+    })
+      .from(chainStatus)
+      .limit(1);
+    
+    if (!status) {
+      return res.status(404).json({
+        status: 'error',
+        message: 'Chain status not reported yet'
+      });
+    }
+    
+    res.json({
+      status: 'success',
+      data: status
+    });
+  } catch (error) {
+    logger.error('Error fetching chain status:', error);
+    res.status(500).json({ 
+      status: 'error',
+      message: 'Internal server error' 
+    });
+  }
+});
+
+// Get operational events, newest first
+router.get('/audit',
+  validate(auditQuerySchema, 'query'),
+  async (req, res) => {
+    try {
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      // @ts-ignore
+      const offset = req.query.offset as number;
+      const source = req.query.source as string | undefined;
+      const action = req.query.action as string | undefined;
+      // @ts-ignore
+      const blockNumber = req.query.blockNumber as number | undefined;
+      
+      logger.info(`Fetching audit events with limit ${limit} and offset ${offset}`);
+      
+      const events = await readDb.select()
+        .from(auditLog)
+        .where(and(
+          source ? eq(auditLog.source, source) : undefined,
+          action ? eq(auditLog.action, action) : undefined,
+          blockNumber !== undefined ? eq(auditLog.blockNumber, blockNumber) : undefined
+        ))
+        .orderBy(desc(auditLog.id))
+        .limit(limit)
+        .offset(offset);
+      
+      res.json({
+        status: 'success',
+        data: {
+          events
+        }
+      });
+    } catch (error) {
+      logger.error('Error fetching audit events:', error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Buckets overlapping a range still being backfilled or repaired are missing blocks
+const bucketCompleteness = async () => {
+  const ranges = await readDb.select({
+    fromBlock: incompleteRanges.startBlock,
+    toBlock: incompleteRanges.endBlock,
+  })
+  .from(incompleteRanges);
+
+  return (bucket: { firstBlock: number; lastBlock: number }) =>
+    !ranges.some((range) => range.fromBlock <= bucket.lastBlock && range.toBlock >= bucket.firstBlock);
+};
+
+// Get hourly or daily gas aggregates, newest first
+router.get('/gas/rollups',
+  validate(rollupsQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const period = req.query.period as string;
+      // @ts-ignore
+      const from = req.query.from as number | undefined;
+      // @ts-ignore
+      const to = req.query.to as number | undefined;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      
+      logger.info(`Fetching ${limit} ${period} gas rollups`);
+      
+      const buckets = await readDb.select()
+        .from(gasRollups)
+        .where(and(
+          eq(gasRollups.period, period),
+          from !== undefined ? gte(gasRollups.bucketStart, from) : undefined,
+          to !== undefined ? lte(gasRollups.bucketStart, to) : undefined
+        ))
+        .orderBy(desc(gasRollups.bucketStart))
+        .limit(limit);
+      
+      const isComplete = await bucketCompleteness();
+      
+      res.json({
+        status: 'success',
+        data: {
+          period,
+          buckets: buckets.map(({ updatedAt, ...bucket }) => ({
+            ...bucket,
+            complete: isComplete(bucket),
+          }))
+        }
+      });
+    } catch (error) {
+      logger.error('Error fetching gas rollups:', error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get per-block fee statistics, newest first
+router.get('/fees/blocks',
+  validate(blockFeesQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      // @ts-ignore
+      const to = req.query.to as number | undefined;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      
+      logger.info(`Fetching fees of ${limit} blocks up to ${to ?? 'the latest'}`);
+      
+      const fees = await readDb.select({
+        blockNumber: blockFees.blockNumber,
+        blockHash: blockFees.blockHash,
+        baseFeePerGas: blocks.baseFeePerGas,
+        gasUsed: blocks.gasUsed,
+        receiptCount: blockFees.receiptCount,
+        burntFees: blockFees.burntFees,
+        priorityFees: blockFees.priorityFees,
+        minEffectiveGasPrice: blockFees.minEffectiveGasPrice,
+        medianEffectiveGasPrice: blockFees.medianEffectiveGasPrice,
+        avgEffectiveGasPrice: blockFees.avgEffectiveGasPrice,
+        maxEffectiveGasPrice: blockFees.maxEffectiveGasPrice,
+      })
+        .from(blockFees)
+        // Statistics left over from a block replaced by a reorg are skipped
+        .innerJoin(blocks, and(eq(blocks.number, blockFees.blockNumber), eq(blocks.hash, blockFees.blockHash)))
+        .where(to !== undefined ? lte(blockFees.blockNumber, to) : undefined)
+        .orderBy(desc(blockFees.blockNumber))
+        .limit(limit);
+      
+      res.json({
+        status: 'success',
+        data: fees
+      });
+    } catch (error) {
+      logger.error('Error fetching block fees:', error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get hourly or daily block and transaction throughput, newest first
+router.get('/throughput/rollups',
+  validate(rollupsQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const period = req.query.period as string;
+      // @ts-ignore
+      const from = req.query.from as number | undefined;
+      // @ts-ignore
+      const to = req.query.to as number | undefined;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      
+      logger.info(`Fetching ${limit} ${period} throughput rollups`);
+      
+      const buckets = await readDb.select()
+        .from(throughputRollups)
+        .where(and(
+          eq(throughputRollups.period, period),
+          from !== undefined ? gte(throughputRollups.bucketStart, from) : undefined,
+          to !== undefined ? lte(throughputRollups.bucketStart, to) : undefined
+        ))
+        .orderBy(desc(throughputRollups.bucketStart))
+        .limit(limit);
+      
+      const isComplete = await bucketCompleteness();
+      
+      res.json({
+        status: 'success',
+        data: {
+          period,
+          buckets: buckets.map((bucket) => {
+            // Bounds are exact to the millisecond only when the node reports millisecond timestamps;
+            // otherwise both are null until the bucket spans a second
+            const span = (bucket.lastTimestampMs - bucket.firstTimestampMs) / 1000;
+            const avgBlockTime = bucket.blockCount > 1 && span > 0 ? span / (bucket.blockCount - 1) : null;
+            return {
+              ...bucket,
+              avgBlockTime,
+              tps: avgBlockTime ? bucket.transactionCount / (bucket.blockCount * avgBlockTime) : null,
+              complete: isComplete(bucket),
+            };
+          })
+        }
+      });
+    } catch (error) {
+      logger.error('Error fetching throughput rollups:', error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
+// Get the most called contracts over the last hour, day or week
+router.get('/contracts/top',
+  validate(topContractsQuerySchema, 'query'),
+  cacheMiddleware(),
+  async (req, res) => {
+    try {
+      const period = req.query.period as string;
+      const by = req.query.by as string;
+      // @ts-ignore
+      const limit = req.query.limit as number;
+      
+      logger.info(`Fetching top ${limit} contracts by ${by} over ${period}`);
+      
+      const rank = by === 'gas' ? topContracts.gasRank : topContracts.txRank;
+      const contracts = await readDb.select({
+        rank,
+        address: topContracts.address,
+        name: addressNames.name,
+        txCount: topContracts.txCount,
+        gasUsed: topContracts.gasUsed,
+        updatedAt: topContracts.updatedAt,
+      })
+        .from(topContracts)
+        .leftJoin(addressNames, eq(addressNames.address, topContracts.address))
+        .where(and(eq(topContracts.period, period), lte(rank, limit)))
+        .orderBy(asc(rank))
+        .limit(limit);
+      
+      res.json({
+        status: 'success',
+        data: {
+          period,
+          by,
+          updatedAt: contracts[0]?.updatedAt ?? null,
+          contracts: contracts.map(({ updatedAt, ...contract }) => contract)
+        }
+      });
+    } catch (error) {
+      logger.error('Error fetching top contracts:', error);
+      res.status(500).json({ 
+        status: 'error',
+        message: 'Internal server error' 
+      });
+    }
+  }
+);
+
 // Get statistics
 router.get('/stats', cacheMiddleware(), async (req, res) => {
   try {
@@ -118,6 +903,19 @@ router.get('/stats', cacheMiddleware(), async (req, res) => {
       });
     }
     
+    // Block ranges the indexer is still backfilling or repairing
+    const ranges = await readDb.select({
+      fromBlock: incompleteRanges.startBlock,
+      toBlock: incompleteRanges.endBlock,
+      reason: incompleteRanges.reason,
+    })
+    .from(incompleteRanges)
+    .orderBy(asc(incompleteRanges.startBlock))
+    .limit(100);
+
+    const overlapsWindow = stats.fromBlock !== undefined && stats.toBlock !== undefined &&
+      ranges.some((range) => range.fromBlock <= stats.toBlock! && range.toBlock >= stats.fromBlock!);
+    
     logger.info('Successfully retrieved chain statistics');
     
     // Include fixed window size in the response
@@ -125,7 +923,9 @@ router.get('/stats', cacheMiddleware(), async (req, res) => {
       status: 'success',
       data: {
         ...stats,
-        windowSize: statsManager.getStatsWindowSize()
+        complete: stats.complete !== false && !overlapsWindow,
+        windowSize: statsManager.getStatsWindowSize(),
+        incompleteRanges: ranges,
       }
     });
   } catch (error) {
@@ -1,4 +1,34 @@
 import { z } from 'zod';
+import { getTableColumns } from 'drizzle-orm';
+import { blocks } from '../db/schema';
+import { MAX_RESPONSE_BYTES } from '../utils/response';
+
+// Block columns that can be requested with ?fields=
+export const BLOCK_FIELDS = Object.keys(getTableColumns(blocks));
+
+// Comma-separated field list, restricted to `allowed` when given
+const fieldList = (allowed?: string[]) => z.string().optional().transform((val, ctx) => {
+  if (!val) return undefined;
+  const fields = [...new Set(val.split(',').map((field) => field.trim()).filter(Boolean))];
+  const unknown = allowed
+    ? fields.filter((field) => !allowed.includes(field))
+    : fields.filter((field) => !/^[A-Za-z][A-Za-z0-9]*$/.test(field));
+  if (unknown.length > 0) {
+    ctx.addIssue({ code: z.ZodIssueCode.custom, message: `Unknown fields: ${unknown.join(', ')}` });
+    return z.NEVER;
+  }
+  return fields;
+});
+
+// Response size limit requested by the client, capped at the server limit
+const maxBytes = z.string().optional().transform((val) => {
+  if (!val) return MAX_RESPONSE_BYTES;
+  const parsed = parseInt(val);
+  if (isNaN(parsed) || parsed < 1) {
+    return MAX_RESPONSE_BYTES; // default if invalid
+  }
+  return Math.min(parsed, MAX_RESPONSE_BYTES);
+});
 
 // Schema for block number parameter
 export const blockNumberSchema = z.object({
@@ -31,13 +61,93 @@ export const paginationSchema = z.object({
   })
 });
 
+// Schema for latest blocks query parameters
+export const blockListQuerySchema = paginationSchema.extend({
+  tag: z.string().optional(),
+  fields: fieldList(BLOCK_FIELDS),
+  maxBytes
+});
+
+// Schema for block detail query parameters; txFields selects keys of each transaction (including `receipt`)
+export const blockQuerySchema = z.object({
+  fields: fieldList(BLOCK_FIELDS),
+  txFields: fieldList(),
+  maxBytes
+});
+
+// Schema for log query parameters; addresses and topics are stored lowercase
+export const logsQuerySchema = paginationSchema.extend({
+  address: z.string().regex(/^0x[0-9a-fA-F]{40}$/, 'Invalid address').transform((val) => val.toLowerCase()).optional(),
+  topic0: z.string().regex(/^0x[0-9a-fA-F]{64}$/, 'Invalid topic').transform((val) => val.toLowerCase()).optional()
+});
+
+// Schema for account address parameter
+export const addressSchema = z.object({
+  address: z.string().regex(/^0x[0-9a-fA-F]{40}$/, 'Invalid address').transform((val) => val.toLowerCase())
+});
+
+// Schema for transaction hash parameter
+export const transactionHashSchema = z.object({
+  hash: z.string().regex(/^0x[0-9a-fA-F]{64}$/, 'Invalid transaction hash').transform((val) => val.toLowerCase())
+});
+
+// Schema for address transaction query parameters
+export const addressTransactionsQuerySchema = paginationSchema.extend({
+  direction: z.enum(['from', 'to']).optional()
+});
+
+// Schema for token address parameter
+export const tokenAddressSchema = z.object({
+  address: z.string().regex(/^0x[0-9a-fA-F]{40}$/, 'Invalid address').transform((val) => val.toLowerCase())
+});
+
+// Schema for token transfer query parameters
+export const tokenTransfersQuerySchema = paginationSchema.extend({
+  account: z.string().regex(/^0x[0-9a-fA-F]{40}$/, 'Invalid address').transform((val) => val.toLowerCase()).optional()
+});
+
+// Schema for NFT transfer query parameters
+export const nftTransfersQuerySchema = tokenTransfersQuerySchema.extend({
+  tokenId: z.string().regex(/^[0-9]{1,78}$/, 'Invalid token id').optional()
+});
+
+// Schema for audit log query parameters
+export const auditQuerySchema = paginationSchema.extend({
+  source: z.string().regex(/^[a-z_]{1,64}$/, 'Invalid source').optional(),
+  action: z.string().regex(/^[a-z_]{1,64}$/, 'Invalid action').optional(),
+  blockNumber: z.coerce.number().int().nonnegative().optional()
+});
+
+// Schema for rollup query parameters; from/to are unix timestamps bounding bucket starts
+export const rollupsQuerySchema = z.object({
+  period: z.enum(['hour', 'day']).default('hour'),
+  from: z.coerce.number().int().nonnegative().optional(),
+  to: z.coerce.number().int().nonnegative().optional(),
+  limit: z.coerce.number().int().positive().max(1000).default(24)
+});
+
+// Schema for block fee query parameters; `to` is the highest block to return
+export const blockFeesQuerySchema = z.object({
+  to: z.coerce.number().int().nonnegative().optional(),
+  limit: z.coerce.number().int().positive().max(1000).default(100)
+});
+
+// Schema for top contracts query parameters
+export const topContractsQuerySchema = z.object({
+  period: z.enum(['1h', '24h', '7d']).default('24h'),
+  by: z.enum(['transactions', 'gas']).default('transactions'),
+  limit: z.coerce.number().int().positive().max(100).default(20)
+});
+
 // Schema for block response
 export const blockResponseSchema = z.object({
   number: z.number(),
   hash: z.string(),
   parentHash: z.string(),
   timestamp: z.number(),
+  timestampMs: z.number().nullable().optional(),
   transactionCount: z.number(),
+  tags: z.array(z.string()).optional(),
   transactions: z.array(
     z.object({
       hash: z.string(),
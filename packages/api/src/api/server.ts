@@ -4,6 +4,8 @@ import dotenv from 'dotenv';
 import router from './router';
 import { logger } from '../utils/logger';
 import { verifyApiKey } from './middleware/validate';
+import { requestMetrics } from './middleware/metrics';
+import { renderMetrics } from '../utils/metrics';
 
 dotenv.config();
 
@@ -14,20 +16,13 @@ const apiPort = process.env.PORT || 3001;
 app.use(cors());
 app.use(express.json());
 
-// Request logging middleware
-// app.use((req, res, next) => {
-//   const start = Date.now();
-//   res.on('finish', () => {
-//     const duration = Date.now() - start;
-//     logger.debug({
-//       method: req.method,
-//       url: req.originalUrl,
-//       status: res.statusCode,
-//       duration: `${duration}ms`,
-//     }, `${req.method} ${req.originalUrl} ${res.statusCode} - ${duration}ms`);
-//   });
-//   next();
-// });
+// Request ids, logging and latency metrics
+app.use(requestMetrics);
+
+// Prometheus metrics for the REST and WebSocket servers, behind the API key
+app.get('/metrics', verifyApiKey, (req, res) => {
+  res.type('text/plain; version=0.0.4').send(renderMetrics());
+});
 
 // Routes with API key verification
 // The /health endpoint remains unprotected for infrastructure health checks
@@ -35,8 +35,23 @@ if ('connectionString' in connectionConfig) {
 
 const pool = new Pool(connectionConfig);
 
+// Optional read replica for the REST API's queries, so heavy explorer queries can't starve
+// the primary the indexer writes to. Falls back to the primary when not set.
+const readPool = process.env.DATABASE_READ_URL
+  ? new Pool({ connectionString: process.env.DATABASE_READ_URL })
+  : pool;
+
+if (process.env.DATABASE_READ_URL) {
+  const maskedReadUrl = process.env.DATABASE_READ_URL.replace(/:([^:@]+)@/, ':***@');
+  console.log(`- Using read replica for queries: ${maskedReadUrl}`);
+}
+
 // Initialize Drizzle with the pool and schema
 export const db = drizzle(pool, { schema });
 
-// Export the pool for pg-listen
-export { pool };
\ No newline at end of file
+// Queries that may lag the primary slightly; reads reacting to a block notification use `db`,
+// as the block may not have reached the replica yet
+export const readDb = drizzle(readPool, { schema });
+
+// Export the pools for pg-listen, health checks and shutdown
+export { pool, readPool };
\ No newline at end of file
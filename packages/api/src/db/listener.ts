@@ -36,6 +36,7 @@ export async function getBlockForStats(blockNumber: number) {
   const blockData = await db.select({
     number: blocks.number,
     timestamp: blocks.timestamp,
+    timestampMs: blocks.timestampMs,
     transactionCount: blocks.transactionCount,
     gasUsed: blocks.gasUsed,
   })
@@ -0,0 +1,33 @@
+import { logger } from '../utils/logger';
+
+// Latest indexer migration (packages/indexer/migrations) whose tables and columns schema.ts
+// reflects; bump it with schema.ts when the API starts using a newer migration
+export const EXPECTED_SCHEMA_VERSION = 33;
+
+// Version of the schema recorded in schema_meta by the indexer's migrations; 0 for databases
+// migrated before schema_meta existed, or without the indexer's schema at all
+export async function getSchemaVersion(pool: any): Promise<number> {
+  const tableCheck = await pool.query(`SELECT to_regclass('public.schema_meta') IS NOT NULL AS present`);
+  if (!tableCheck.rows[0].present) {
+    return 0;
+  }
+
+  const result = await pool.query('SELECT version FROM schema_meta');
+  return result.rows.length > 0 ? Number(result.rows[0].version) : 0;
+}
+
+// Refuse to serve against a schema older than the API's queries expect, which would otherwise
+// surface later as errors about missing tables or columns
+export async function verifySchemaVersion(pool: any): Promise<boolean> {
+  const version = await getSchemaVersion(pool);
+  if (version < EXPECTED_SCHEMA_VERSION) {
+    logger.error(
+      `Database schema is at version ${version} but this API requires version ${EXPECTED_SCHEMA_VERSION}. ` +
+      'Start the indexer, or apply the migrations in packages/indexer/migrations, before starting the API.'
+    );
+    return false;
+  }
+
+  logger.info(`Database schema is at version ${version} (required: ${EXPECTED_SCHEMA_VERSION})`);
+  return true;
+}
@@ -1,4 +1,4 @@
-import { pgTable, serial, bigint, timestamp, integer, text, unique, doublePrecision, jsonb } from 'drizzle-orm/pg-core';
+import { pgTable, serial, bigint, boolean, timestamp, integer, text, unique, doublePrecision, jsonb, numeric } from 'drizzle-orm/pg-core';
 
 // Blocks table based on new indexer schema
 export const blocks = pgTable('blocks', {
@@ -14,13 +14,234 @@ export const blocks = pgTable('blocks', {
   baseFeePerGas: bigint('base_fee_per_gas', { mode: 'number' }),
   extraData: text('extra_data').notNull(),
   miner: text('miner').notNull(),
-  difficulty: text('difficulty').notNull(),
-  totalDifficulty: text('total_difficulty').notNull(),
+  difficulty: text('difficulty'),
+  totalDifficulty: text('total_difficulty'),
   size: bigint('size', { mode: 'number' }).notNull(),
   transactionCount: bigint('transaction_count', { mode: 'number' }).notNull(),
   transactions: jsonb('transactions'),
+  tags: text('tags').array().notNull().default([]),
   createdAt: timestamp('created_at', { withTimezone: true }).defaultNow().notNull(),
   updatedAt: timestamp('updated_at', { withTimezone: true }).defaultNow().notNull(),
+  // Only set when the node reports millisecond timestamps
+  timestampMs: bigint('timestamp_ms', { mode: 'number' }),
+});
+
+// Transactions of the stored blocks, one row per transaction; without FETCH_FULL_TRANSACTIONS
+// only the hash, block and index are set
+export const transactions = pgTable('transactions', {
+  hash: text('hash').primaryKey(),
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  transactionIndex: bigint('transaction_index', { mode: 'number' }).notNull(),
+  fromAddress: text('from_address'),
+  toAddress: text('to_address'),
+  value: numeric('value', { precision: 78, scale: 0 }),
+  gas: bigint('gas', { mode: 'number' }),
+  gasPrice: bigint('gas_price', { mode: 'number' }),
+  nonce: bigint('nonce', { mode: 'number' }),
+  input: text('input'),
+});
+
+// Finality watermarks (finalized/safe block tags) maintained by the indexer
+export const chainWatermarks = pgTable('chain_watermarks', {
+  tag: text('tag').primaryKey(),
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  updatedAt: timestamp('updated_at', { withTimezone: true }).defaultNow().notNull(),
+});
+
+// Chain/indexer health maintained by the indexer's status monitor (single row)
+export const chainStatus = pgTable('chain_status', {
+  id: boolean('id').primaryKey().default(true),
+  status: text('status').notNull(),
+  chainHead: bigint('chain_head', { mode: 'number' }),
+  chainHeadTimestamp: bigint('chain_head_timestamp', { mode: 'number' }),
+  indexedBlock: bigint('indexed_block', { mode: 'number' }),
+  indexedBlockTimestamp: bigint('indexed_block_timestamp', { mode: 'number' }),
+  detail: text('detail'),
+  earliestAvailableBlock: bigint('earliest_available_block', { mode: 'number' }),
+  statusSince: timestamp('status_since', { withTimezone: true }).defaultNow().notNull(),
+  updatedAt: timestamp('updated_at', { withTimezone: true }).defaultNow(),
+});
+
+// Transaction receipts, stored when the indexer runs with FETCH_RECEIPTS
+export const receipts = pgTable('receipts', {
+  transactionHash: text('transaction_hash').primaryKey(),
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  transactionIndex: bigint('transaction_index', { mode: 'number' }).notNull(),
+  fromAddress: text('from_address').notNull(),
+  toAddress: text('to_address'),
+  status: integer('status'),
+  gasUsed: bigint('gas_used', { mode: 'number' }),
+  cumulativeGasUsed: bigint('cumulative_gas_used', { mode: 'number' }).notNull(),
+  effectiveGasPrice: bigint('effective_gas_price', { mode: 'number' }),
+  contractAddress: text('contract_address'),
+  logs: jsonb('logs').notNull(),
+  createdAt: timestamp('created_at', { withTimezone: true }).defaultNow(),
+});
+
+// Event logs extracted from receipts, one row per log
+export const logs = pgTable('logs', {
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  logIndex: bigint('log_index', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  transactionHash: text('transaction_hash').notNull(),
+  transactionIndex: bigint('transaction_index', { mode: 'number' }).notNull(),
+  address: text('address').notNull(),
+  topic0: text('topic0'),
+  topic1: text('topic1'),
+  topic2: text('topic2'),
+  topic3: text('topic3'),
+  data: text('data').notNull(),
+});
+
+// ERC-20 transfers decoded from logs by the indexer
+export const tokenTransfers = pgTable('token_transfers', {
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  logIndex: bigint('log_index', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  transactionHash: text('transaction_hash').notNull(),
+  token: text('token').notNull(),
+  fromAddress: text('from_address').notNull(),
+  toAddress: text('to_address').notNull(),
+  amount: numeric('amount', { precision: 78, scale: 0 }).notNull(),
+});
+
+// Define the nft_transfers table (ERC-721 and ERC-1155 transfers decoded by the indexer)
+export const nftTransfers = pgTable('nft_transfers', {
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  logIndex: bigint('log_index', { mode: 'number' }).notNull(),
+  batchIndex: bigint('batch_index', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  transactionHash: text('transaction_hash').notNull(),
+  token: text('token').notNull(),
+  standard: text('standard').notNull(),
+  operator: text('operator'),
+  fromAddress: text('from_address').notNull(),
+  toAddress: text('to_address').notNull(),
+  tokenId: numeric('token_id', { precision: 78, scale: 0 }).notNull(),
+  amount: numeric('amount', { precision: 78, scale: 0 }).notNull(),
+});
+
+// Define the address_activity table (per-address summary maintained by the indexer)
+export const addressActivity = pgTable('address_activity', {
+  address: text('address').primaryKey(),
+  firstSeenBlock: bigint('first_seen_block', { mode: 'number' }).notNull(),
+  lastSeenBlock: bigint('last_seen_block', { mode: 'number' }).notNull(),
+  txCount: bigint('tx_count', { mode: 'number' }).notNull(),
+});
+
+// Define the contract_code table (distinct contract bytecode, keyed by its keccak hash)
+export const contractCode = pgTable('contract_code', {
+  codeHash: text('code_hash').primaryKey(),
+  code: text('code').notNull(),
+  createdAt: timestamp('created_at', { withTimezone: true }).defaultNow(),
+});
+
+// Define the deployed_code table (contracts created in indexed blocks and their code)
+export const deployedCode = pgTable('deployed_code', {
+  address: text('address').notNull(),
+  blockNumber: bigint('block_number', { mode: 'number' }).notNull(),
+  blockHash: text('block_hash').notNull(),
+  transactionHash: text('transaction_hash').notNull(),
+  codeHash: text('code_hash').notNull(),
+});
+
+// Define the audit_log table (operational events recorded by the indexer and its CLI)
+export const auditLog = pgTable('audit_log', {
+  id: bigint('id', { mode: 'number' }).primaryKey(),
+  occurredAt: timestamp('occurred_at', { withTimezone: true }).notNull(),
+  source: text('source').notNull(),
+  action: text('action').notNull(),
+  actor: text('actor'),
+  blockNumber: bigint('block_number', { mode: 'number' }),
+  details: jsonb('details').notNull(),
+});
+
+// Define the tokens table (token contracts seen in transfers, with metadata read by the indexer)
+export const tokens = pgTable('tokens', {
+  address: text('address').primaryKey(),
+  standard: text('standard').notNull(),
+  name: text('name'),
+  symbol: text('symbol'),
+  decimals: integer('decimals'),
+  fetchedAt: timestamp('fetched_at', { withTimezone: true }),
+  createdAt: timestamp('created_at', { withTimezone: true }).defaultNow(),
+});
+
+// Define the address_names table (names reverse-resolved by the indexer's name resolver)
+export const addressNames = pgTable('address_names', {
+  address: text('address').primaryKey(),
+  name: text('name'),
+  resolvedAt: timestamp('resolved_at', { withTimezone: true }).notNull(),
+});
+
+// Define the incomplete_ranges table (block ranges still being ingested by the indexer)
+export const incompleteRanges = pgTable('incomplete_ranges', {
+  startBlock: bigint('start_block', { mode: 'number' }).notNull(),
+  endBlock: bigint('end_block', { mode: 'number' }).notNull(),
+  reason: text('reason').notNull(),
+  createdAt: timestamp('created_at', { withTimezone: true }).defaultNow(),
+});
+
+// Define the gas_rollups table (hourly and daily gas aggregates maintained by the indexer)
+export const gasRollups = pgTable('gas_rollups', {
+  period: text('period').notNull(),
+  bucketStart: bigint('bucket_start', { mode: 'number' }).notNull(),
+  blockCount: bigint('block_count', { mode: 'number' }).notNull(),
+  gasUsed: bigint('gas_used', { mode: 'number' }).notNull(),
+  gasLimit: bigint('gas_limit', { mode: 'number' }).notNull(),
+  avgUtilization: doublePrecision('avg_utilization'),
+  avgBaseFee: doublePrecision('avg_base_fee'),
+  minBaseFee: bigint('min_base_fee', { mode: 'number' }),
+  maxBaseFee: bigint('max_base_fee', { mode: 'number' }),
+  firstBlock: bigint('first_block', { mode: 'number' }).notNull(),
+  lastBlock: bigint('last_block', { mode: 'number' }).notNull(),
+  feeBlockCount: bigint('fee_block_count', { mode: 'number' }),
+  burntFees: numeric('burnt_fees', { precision: 78, scale: 0 }),
+  priorityFees: numeric('priority_fees', { precision: 78, scale: 0 }),
+  avgEffectiveGasPrice: doublePrecision('avg_effective_gas_price'),
+  updatedAt: timestamp('updated_at', { withTimezone: true }).notNull(),
+});
+
+// Define the block_fees table (per-block fee statistics computed from receipts, in wei)
+export const blockFees = pgTable('block_fees', {
+  blockNumber: bigint('block_number', { mode: 'number' }).primaryKey(),
+  blockHash: text('block_hash').notNull(),
+  receiptCount: bigint('receipt_count', { mode: 'number' }).notNull(),
+  burntFees: numeric('burnt_fees', { precision: 78, scale: 0 }).notNull(),
+  priorityFees: numeric('priority_fees', { precision: 78, scale: 0 }).notNull(),
+  minEffectiveGasPrice: bigint('min_effective_gas_price', { mode: 'number' }),
+  medianEffectiveGasPrice: bigint('median_effective_gas_price', { mode: 'number' }),
+  avgEffectiveGasPrice: doublePrecision('avg_effective_gas_price'),
+  maxEffectiveGasPrice: bigint('max_effective_gas_price', { mode: 'number' }),
+  updatedAt: timestamp('updated_at', { withTimezone: true }).notNull(),
+});
+
+// Define the throughput_rollups table (hourly and daily block and transaction counts kept by the indexer)
+export const throughputRollups = pgTable('throughput_rollups', {
+  period: text('period').notNull(),
+  bucketStart: bigint('bucket_start', { mode: 'number' }).notNull(),
+  blockCount: bigint('block_count', { mode: 'number' }).notNull(),
+  transactionCount: bigint('transaction_count', { mode: 'number' }).notNull(),
+  firstBlock: bigint('first_block', { mode: 'number' }).notNull(),
+  lastBlock: bigint('last_block', { mode: 'number' }).notNull(),
+  firstTimestamp: bigint('first_timestamp', { mode: 'number' }).notNull(),
+  lastTimestamp: bigint('last_timestamp', { mode: 'number' }).notNull(),
+  firstTimestampMs: bigint('first_timestamp_ms', { mode: 'number' }).notNull(),
+  lastTimestampMs: bigint('last_timestamp_ms', { mode: 'number' }).notNull(),
+});
+
+// Define the top_contracts table (most called contracts per window, ranked by the indexer)
+export const topContracts = pgTable('top_contracts', {
+  period: text('period').notNull(),
+  address: text('address').notNull(),
+  txCount: bigint('tx_count', { mode: 'number' }).notNull(),
+  gasUsed: bigint('gas_used', { mode: 'number' }).notNull(),
+  txRank: bigint('tx_rank', { mode: 'number' }).notNull(),
+  gasRank: bigint('gas_rank', { mode: 'number' }).notNull(),
+  updatedAt: timestamp('updated_at', { withTimezone: true }).notNull(),
 });
 
 // Types for transactions in the jsonb field
@@ -39,4 +260,7 @@ export interface BlockStats {
   tps: number; // average transactions per second
   shredInterval?: number; // average time (in ms) per shred
   gasPerSecond: number; // average gas used per second
+  fromBlock?: number; // first block the stats were calculated over
+  toBlock?: number; // last block the stats were calculated over
+  complete?: boolean; // false if blocks in the window were missing and left out
 }
\ No newline at end of file
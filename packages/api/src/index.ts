@@ -1,8 +1,11 @@
+// Settings from CONFIG_FILE go into the environment before the other modules read it
+import { unknownSettings } from './utils/config-file';
 import dotenv from 'dotenv';
 import { startApiServer } from './api/server';
 import { createWebSocketServer } from './ws/server';
 import { setupDatabaseListener, verifyDatabaseTrigger } from './db/listener';
-import { pool } from './db';
+import { pool, readPool } from './db';
+import { verifySchemaVersion } from './db/schema-version';
 import { logger } from './utils/logger';
 
 // Check for version flag
@@ -17,6 +20,9 @@ dotenv.config();
 async function main() {
   try {
     logger.info('Starting Shred Explorer API Server...');
+    for (const key of unknownSettings) {
+      logger.warn(`Ignoring unknown setting ${key} in config file ${process.env.CONFIG_FILE}`);
+    }
     
     // Test database connection
     try {
@@ -27,6 +33,11 @@ async function main() {
       logger.error('Database connection failed:', dbError);
       throw dbError;
     }
+
+    // Check the schema before serving anything from it
+    if (!(await verifySchemaVersion(pool))) {
+      process.exit(1);
+    }
     
     // Verify database triggers for notifications exist
     try {
@@ -58,6 +69,9 @@ async function main() {
     const shutdown = async () => {
       logger.info('Shutting down...');
       await pool.end();
+      if (readPool !== pool) {
+        await readPool.end();
+      }
       process.exit(0);
     };
     
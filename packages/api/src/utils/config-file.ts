@@ -0,0 +1,135 @@
+import dotenv from 'dotenv';
+import fs from 'fs';
+import path from 'path';
+import yaml from 'js-yaml';
+
+// Settings read from the environment, which a config file may set
+const SETTINGS = [
+  'API_SECRET_KEY',
+  'DATABASE_HOST',
+  'DATABASE_NAME',
+  'DATABASE_PASSWORD',
+  'DATABASE_PORT',
+  'DATABASE_READ_URL',
+  'DATABASE_URL',
+  'DATABASE_USER',
+  'LOG_LEVEL',
+  'MAX_RESPONSE_BYTES',
+  'NODE_ENV',
+  'PORT',
+  'SKIP_DB_CHECK_HEALTH',
+  'SLOW_REQUEST_MS',
+  'WS_MAX_BUFFERED_BYTES',
+  'WS_PORT',
+  'WS_SUMMARY_BUFFERED_BYTES',
+];
+
+type Settings = Record<string, unknown>;
+
+/**
+ * Parse the flat TOML the settings use: `key = value` lines with strings, numbers, booleans
+ * and arrays of them. Tables are rejected, as settings are never nested.
+ */
+function parseToml(source: string): Settings {
+  const settings: Settings = {};
+
+  source.split(/\r?\n/).forEach((raw, index) => {
+    const line = raw.trim();
+    if (!line || line.startsWith('#')) return;
+    if (line.startsWith('[')) {
+      throw new Error(`line ${index + 1}: tables are not supported, settings must be top-level keys`);
+    }
+
+    const match = line.match(/^([A-Za-z0-9_-]+)\s*=\s*(.+)$/);
+    if (!match) {
+      throw new Error(`line ${index + 1}: expected \`key = value\``);
+    }
+    settings[match[1]] = parseTomlValue(match[2], index + 1);
+  });
+
+  return settings;
+}
+
+/**
+ * Parse a TOML value, dropping a trailing comment. Basic strings, numbers, booleans and
+ * arrays are valid JSON; literal strings ('...') are taken as is.
+ */
+function parseTomlValue(value: string, line: number): unknown {
+  const literal = value.match(/^'([^']*)'\s*(#.*)?$/);
+  if (literal) return literal[1];
+
+  // Try the whole value first, then without everything from each `#` on, right to left
+  const candidates = [value];
+  for (let at = value.lastIndexOf('#'); at > 0; at = value.lastIndexOf('#', at - 1)) {
+    candidates.push(value.slice(0, at));
+  }
+  for (const candidate of candidates) {
+    try {
+      return JSON.parse(candidate.trim());
+    } catch {
+      // Not this one; the `#` may be part of a string
+    }
+  }
+
+  throw new Error(`line ${line}: invalid value ${value}`);
+}
+
+/** Read a TOML, YAML or JSON file (by extension) into its top-level settings */
+function readSettings(file: string): Settings {
+  const source = fs.readFileSync(file, 'utf8');
+  const extension = path.extname(file).toLowerCase();
+
+  const settings = extension === '.toml'
+    ? parseToml(source)
+    : ['.yaml', '.yml', '.json'].includes(extension)
+      ? yaml.safeLoad(source)
+      : undefined;
+
+  if (settings === undefined) {
+    throw new Error('expected a .toml, .yaml, .yml or .json file');
+  }
+  if (settings === null || typeof settings !== 'object' || Array.isArray(settings)) {
+    throw new Error('expected a table of settings');
+  }
+  return settings as Settings;
+}
+
+/**
+ * Load the settings in the file named by `CONFIG_FILE` into the environment, without
+ * overriding variables that are already set, including the ones from `.env`. Keys are the
+ * environment variable names, in any case; lists are joined with commas. Returns the keys
+ * that aren't settings, which are skipped.
+ */
+export function loadConfigFile(): string[] {
+  dotenv.config();
+  const file = process.env.CONFIG_FILE;
+  if (!file) return [];
+
+  let settings: Settings;
+  try {
+    settings = readSettings(file);
+  } catch (error) {
+    throw new Error(`Failed to load config file ${file}: ${(error as Error).message}`);
+  }
+
+  const unknown: string[] = [];
+  for (const [key, value] of Object.entries(settings)) {
+    const name = key.toUpperCase();
+    if (!SETTINGS.includes(name)) {
+      unknown.push(key);
+      continue;
+    }
+    if (process.env[name] !== undefined) continue;
+
+    if (value !== null && typeof value === 'object' && !Array.isArray(value)) {
+      throw new Error(`${key} in config file ${file} must be a value or a list, not a table`);
+    }
+    process.env[name] = Array.isArray(value) ? value.join(',') : String(value);
+  }
+
+  return unknown;
+}
+
+// Loaded on import, so that modules reading the environment when they are imported see the
+// file's settings; import this module before any other
+export const unknownSettings = loadConfigFile();
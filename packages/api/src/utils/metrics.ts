@@ -0,0 +1,115 @@
+// Minimal Prometheus metrics registry, rendered in the text exposition format by GET /metrics
+
+type Labels = Record<string, string>;
+
+// Default latency buckets in seconds
+const DEFAULT_BUCKETS = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10];
+
+const labelKey = (labels: Labels) => JSON.stringify(Object.entries(labels).sort(([a], [b]) => a.localeCompare(b)));
+
+const formatLabels = (labels: Labels) => {
+  const entries = Object.entries(labels);
+  if (entries.length === 0) return '';
+  return `{${entries.map(([name, value]) => `${name}="${value.replace(/\\/g, '\\\\').replace(/"/g, '\\"').replace(/\n/g, '\\n')}"`).join(',')}}`;
+};
+
+interface Metric {
+  render(): string;
+}
+
+export class Counter implements Metric {
+  private values = new Map<string, { labels: Labels; value: number }>();
+
+  constructor(readonly name: string, readonly help: string) {}
+
+  inc(labels: Labels = {}, value = 1) {
+    const key = labelKey(labels);
+    const entry = this.values.get(key) ?? { labels, value: 0 };
+    entry.value += value;
+    this.values.set(key, entry);
+  }
+
+  render(): string {
+    const lines = [`# HELP ${this.name} ${this.help}`, `# TYPE ${this.name} counter`];
+    for (const { labels, value } of this.values.values()) {
+      lines.push(`${this.name}${formatLabels(labels)} ${value}`);
+    }
+    return lines.join('\n');
+  }
+}
+
+export class Gauge implements Metric {
+  private values = new Map<string, { labels: Labels; value: number }>();
+
+  constructor(readonly name: string, readonly help: string) {}
+
+  set(value: number, labels: Labels = {}) {
+    this.values.set(labelKey(labels), { labels, value });
+  }
+
+  // Stop reporting a label set, e.g. for a client that disconnected
+  remove(labels: Labels) {
+    this.values.delete(labelKey(labels));
+  }
+
+  render(): string {
+    const lines = [`# HELP ${this.name} ${this.help}`, `# TYPE ${this.name} gauge`];
+    for (const { labels, value } of this.values.values()) {
+      lines.push(`${this.name}${formatLabels(labels)} ${value}`);
+    }
+    return lines.join('\n');
+  }
+}
+
+export class Histogram implements Metric {
+  private values = new Map<string, { labels: Labels; counts: number[]; sum: number; count: number }>();
+
+  constructor(readonly name: string, readonly help: string, readonly buckets: number[] = DEFAULT_BUCKETS) {}
+
+  observe(labels: Labels, value: number) {
+    const key = labelKey(labels);
+    const entry = this.values.get(key) ?? { labels, counts: this.buckets.map(() => 0), sum: 0, count: 0 };
+    this.buckets.forEach((bound, i) => {
+      if (value <= bound) entry.counts[i]++;
+    });
+    entry.sum += value;
+    entry.count++;
+    this.values.set(key, entry);
+  }
+
+  render(): string {
+    const lines = [`# HELP ${this.name} ${this.help}`, `# TYPE ${this.name} histogram`];
+    for (const { labels, counts, sum, count } of this.values.values()) {
+      this.buckets.forEach((bound, i) => {
+        lines.push(`${this.name}_bucket${formatLabels({ ...labels, le: String(bound) })} ${counts[i]}`);
+      });
+      lines.push(`${this.name}_bucket${formatLabels({ ...labels, le: '+Inf' })} ${count}`);
+      lines.push(`${this.name}_sum${formatLabels(labels)} ${sum}`);
+      lines.push(`${this.name}_count${formatLabels(labels)} ${count}`);
+    }
+    return lines.join('\n');
+  }
+}
+
+const registry: Metric[] = [];
+
+const register = <T extends Metric>(metric: T): T => {
+  registry.push(metric);
+  return metric;
+};
+
+// REST API
+export const httpRequests = register(new Counter('api_http_requests_total', 'HTTP requests by method, route and status code'));
+export const httpRequestDuration = register(new Histogram('api_http_request_duration_seconds', 'HTTP request latency by method and route'));
+
+// WebSocket server
+export const wsConnections = register(new Gauge('api_ws_connections', 'Connected WebSocket clients'));
+export const wsMessages = register(new Counter('api_ws_messages_total', 'WebSocket client messages by type and outcome'));
+export const wsMessageDuration = register(new Histogram('api_ws_message_duration_seconds', 'Time to handle a WebSocket client message by type'));
+export const wsBroadcastDuration = register(new Histogram('api_ws_broadcast_duration_seconds', 'Time to fetch and broadcast a block update'));
+export const wsClientBufferedBytes = register(new Gauge('api_ws_client_buffered_bytes', 'Bytes queued for each WebSocket client, as of the last broadcast'));
+export const wsClientLagBlocks = register(new Gauge('api_ws_client_lag_blocks', 'Block updates queued but not yet written to each WebSocket client'));
+export const wsSlowConsumers = register(new Counter('api_ws_slow_consumers_total', 'WebSocket clients switched to summary mode, back to full mode, or dropped for falling behind'));
+
+// Render every registered metric in the Prometheus text format
+export const renderMetrics = () => registry.map((metric) => metric.render()).join('\n') + '\n';
@@ -0,0 +1,31 @@
+// Upper bound on the size of a JSON response body; clients may ask for less with ?maxBytes=
+export const MAX_RESPONSE_BYTES = Number(process.env.MAX_RESPONSE_BYTES) || 1_000_000;
+
+// Serialized size of a value in bytes
+export const jsonBytes = (value: unknown): number => Buffer.byteLength(JSON.stringify(value) ?? '');
+
+/**
+ * Keep as many leading items as fit in `budget` bytes once serialized as a JSON array.
+ * `truncated` tells the client that items were dropped and it should page or narrow its request.
+ */
+export function fitToBudget<T>(items: T[], budget: number): { items: T[]; truncated: boolean } {
+  let used = 2; // []
+
+  for (let i = 0; i < items.length; i++) {
+    used += jsonBytes(items[i]) + (i > 0 ? 1 : 0);
+    if (used > budget) {
+      return { items: items.slice(0, i), truncated: true };
+    }
+  }
+
+  return { items, truncated: false };
+}
+
+// Project an object onto the requested fields, or return it unchanged when no fields were requested
+export function pickFields<T extends object>(value: T, fields?: string[]): Partial<T> {
+  if (!fields) return value;
+
+  return Object.fromEntries(
+    fields.filter((field) => field in value).map((field) => [field, value[field as keyof T]])
+  ) as Partial<T>;
+}
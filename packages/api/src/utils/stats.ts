@@ -7,10 +7,14 @@ import { logger } from './logger';
 interface BlockStatsData {
   number: number;
   timestamp: number;
+  timestampMs?: number | null; // set when the node reports millisecond timestamps
   transactionCount: number;
   gasUsed: number;
 }
 
+// Block time in milliseconds, falling back to the start of its second
+const blockTimeMs = (block: BlockStatsData) => block.timestampMs ?? block.timestamp * 1000;
+
 // Stats cache configuration - how many recent blocks to track for stats
 const STATS_WINDOW_SIZE = 10;
 
@@ -32,12 +36,25 @@ class StatsManager {
 
   // Add a new block to the cache, trim if needed, and recalculate stats
   addBlock(block: BlockStatsData) {
-    // Add new block to the end
+    // Blocks older than the whole window come from a backfill, not the chain head,
+    // and would otherwise stretch the window over a gap
+    if (
+      this.recentBlocks.length >= this.statsWindowSize &&
+      this.recentBlocks.every((recent) => recent.number > block.number)
+    ) {
+      logger.debug(`Ignoring backfilled block ${block.number} for stats`);
+      return;
+    }
+
+    // Replace the block if it was re-indexed (e.g. after a reorg), otherwise add it
+    this.recentBlocks = this.recentBlocks.filter((recent) => recent.number !== block.number);
     this.recentBlocks.push(block);
     
-    // Keep only the most recent blocks based on fixed window size
+    // Keep only the highest blocks based on fixed window size
     if (this.recentBlocks.length > this.statsWindowSize) {
-      this.recentBlocks = this.recentBlocks.slice(-this.statsWindowSize);
+      this.recentBlocks = this.recentBlocks
+        .sort((a, b) => a.number - b.number)
+        .slice(-this.statsWindowSize);
     }
     
     logger.debug(`Added block ${block.number} to stats cache, now tracking ${this.recentBlocks.length} blocks`);
@@ -70,9 +87,22 @@ class StatsManager {
     
     // Get latest block
     const latestBlock = sortedBlocks[sortedBlocks.length - 1];
+
+    // Only use the contiguous run ending at the latest block, so blocks missing from
+    // the window (still being backfilled) don't read as a throughput collapse
+    let runStart = sortedBlocks.length - 1;
+    while (runStart > 0 && sortedBlocks[runStart - 1].number === sortedBlocks[runStart].number - 1) {
+      runStart--;
+    }
+    const windowBlocks = sortedBlocks.slice(runStart);
+    const coverage = {
+      fromBlock: windowBlocks[0].number,
+      toBlock: latestBlock.number,
+      complete: windowBlocks.length === sortedBlocks.length,
+    };
     
     // For calculation, we need at least 2 blocks
-    if (sortedBlocks.length < 2) {
+    if (windowBlocks.length < 2) {
       logger.debug('Only one block in cache, using simplified stats calculation');
       // If we only have one block, use a simplified calculation
       const blockTimeSeconds = 12; // Assuming average block time
@@ -80,18 +110,19 @@ class StatsManager {
         tps: Number((latestBlock.transactionCount / blockTimeSeconds).toFixed(2)),
         gasPerSecond: Number((latestBlock.gasUsed / blockTimeSeconds).toFixed(4)),
         shredInterval: Number((1 / (latestBlock.transactionCount || 1)).toFixed(2)), // Avoid division by zero
+        ...coverage,
       };
       this.statsNeedRecalculation = false;
       return;
     }
 
     // Calculate time difference between first and last block
-    const firstBlock = sortedBlocks[0];
-    const timeSpanSeconds = (latestBlock.timestamp - firstBlock.timestamp);
+    const firstBlock = windowBlocks[0];
+    const timeSpanSeconds = (blockTimeMs(latestBlock) - blockTimeMs(firstBlock)) / 1000;
 
     // Calculate total transactions and gas used in this window
-    const totalTransactions = sortedBlocks.reduce((sum, block) => sum + block.transactionCount, 0);
-    const totalGasUsed = sortedBlocks.reduce((sum, block) => sum + block.gasUsed, 0);
+    const totalTransactions = windowBlocks.reduce((sum, block) => sum + block.transactionCount, 0);
+    const totalGasUsed = windowBlocks.reduce((sum, block) => sum + block.gasUsed, 0);
 
     // Calculate TPS and gas/s
     const tps = Number((totalTransactions / timeSpanSeconds).toFixed(2));
@@ -99,12 +130,13 @@ class StatsManager {
     const shredInterval = Number((timeSpanSeconds / (totalTransactions || 1)).toFixed(4));
 
     // Calculate shred interval (average time per transaction)
-    logger.debug(`Calculated stats over ${sortedBlocks.length} blocks spanning ${timeSpanSeconds} seconds`);
+    logger.debug(`Calculated stats over ${windowBlocks.length} blocks spanning ${timeSpanSeconds} seconds`);
     
     this.cachedStats = {
       tps,
       gasPerSecond,
       shredInterval,
+      ...coverage,
     };
     
     this.statsNeedRecalculation = false;
@@ -118,6 +150,7 @@ class StatsManager {
       const latestBlocks = await db.select({
         number: blocks.number,
         timestamp: blocks.timestamp,
+        timestampMs: blocks.timestampMs,
         transactionCount: blocks.transactionCount,
         gasUsed: blocks.gasUsed,
       })
@@ -0,0 +1,36 @@
+// Slow consumer handling for the WebSocket server, so one client that can't keep up
+// doesn't grow an unbounded send queue while the broadcast loop keeps going
+
+// full: every block and stats update; summary: a small summary per block and no stats
+export type ClientMode = 'full' | 'summary';
+
+export interface BackpressureLimits {
+  // Bytes queued for a client above which it only receives block summaries
+  summaryBytes: number;
+  // Bytes queued for a client above which it is disconnected
+  dropBytes: number;
+}
+
+export const backpressureLimits = (): BackpressureLimits => ({
+  summaryBytes: Number(process.env.WS_SUMMARY_BUFFERED_BYTES) || 256 * 1024,
+  dropBytes: Number(process.env.WS_MAX_BUFFERED_BYTES) || 4 * 1024 * 1024,
+});
+
+// Decide how to treat a client given the bytes still queued for it. A client in summary
+// mode only gets full updates again once its queue has drained below a quarter of the
+// threshold, so it doesn't flap between modes.
+export const nextMode = (mode: ClientMode, bufferedBytes: number, limits: BackpressureLimits): ClientMode | 'drop' => {
+  if (bufferedBytes > limits.dropBytes) return 'drop';
+  if (bufferedBytes > limits.summaryBytes) return 'summary';
+  if (mode === 'summary' && bufferedBytes > limits.summaryBytes / 4) return 'summary';
+  return 'full';
+};
+
+// Fields of a block sent to clients in summary mode
+export const summarizeBlock = (block: { number: number; hash: string; timestamp: number; transactionCount: number; gasUsed: number }) => ({
+  number: block.number,
+  hash: block.hash,
+  timestamp: block.timestamp,
+  transactionCount: block.transactionCount,
+  gasUsed: block.gasUsed,
+});
@@ -6,6 +6,8 @@ import { wsMessageSchema } from '../api/schemas';
 import { ZodError } from 'zod';
 import { logger } from '../utils/logger';
 import { statsManager } from '../utils/stats';
+import { wsBroadcastDuration, wsClientBufferedBytes, wsClientLagBlocks, wsConnections, wsMessageDuration, wsMessages, wsSlowConsumers } from '../utils/metrics';
+import { backpressureLimits, ClientMode, nextMode, summarizeBlock } from './backpressure';
 
 dotenv.config();
 
@@ -18,6 +20,14 @@ interface ServerMessage {
   message?: string;
 }
 
+// Send queue tracking of a connected client
+interface ClientState {
+  id: number;
+  mode: ClientMode;
+  // Block updates handed to the socket but not yet written out
+  pendingBlocks: number;
+}
+
 // Create WebSocket server
 export function createWebSocketServer(port: number = Number(process.env.WS_PORT) || 3002) {
   const server = http.createServer();
@@ -28,7 +38,9 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
   });
   
   // Connected clients
-  const clients = new Set<WebSocket>();
+  const clients = new Map<WebSocket, ClientState>();
+  let nextClientId = 1;
+  const limits = backpressureLimits();
 
   // Send a message to a client
   const sendMessage = (ws: WebSocket, message: ServerMessage) => {
@@ -50,8 +62,10 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
 
   // Handle new WebSocket connections
   wss.on('connection', (ws: WebSocket) => {
-    logger.info('WebSocket client connected');
-    clients.add(ws);
+    const state: ClientState = { id: nextClientId++, mode: 'full', pendingBlocks: 0 };
+    logger.info(`WebSocket client ${state.id} connected`);
+    clients.set(ws, state);
+    wsConnections.set(clients.size);
 
     // Send initial data - the latest 10 blocks
     getLatestBlocks(10).then(blocks => {
@@ -83,6 +97,9 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
 
     // Handle client messages
     ws.on('message', async (message: string) => {
+      const start = process.hrtime.bigint();
+      let messageType = 'invalid';
+      let outcome = 'ok';
       try {
         // Parse and validate the incoming message
         const parsedData = JSON.parse(message);
@@ -91,6 +108,7 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
         try {
           // Validate using Zod schema
           const validatedMessage = wsMessageSchema.parse(parsedData);
+          messageType = validatedMessage.type;
           
           // Process validated message
           switch (validatedMessage.type) {
@@ -204,6 +222,7 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
         } catch (validationError) {
           // Send validation errors back to client
           if (validationError instanceof ZodError) {
+            outcome = 'invalid';
             logger.warn('WebSocket message validation failed:', validationError.errors);
             sendError(ws, 'Validation failed', {
               errors: validationError.errors.map(err => ({
@@ -216,20 +235,29 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
           }
         }
       } catch (err) {
+        outcome = 'error';
         logger.error('Error processing WebSocket message:', err);
         sendError(ws, 'Error processing message');
+      } finally {
+        const seconds = Number(process.hrtime.bigint() - start) / 1e9;
+        wsMessages.inc({ type: messageType, outcome });
+        wsMessageDuration.observe({ type: messageType }, seconds);
       }
     });
 
     // Handle disconnections
     ws.on('close', () => {
-      logger.info('WebSocket client disconnected');
+      logger.info(`WebSocket client ${state.id} disconnected`);
       clients.delete(ws);
+      wsConnections.set(clients.size);
+      wsClientBufferedBytes.remove({ client: String(state.id) });
+      wsClientLagBlocks.remove({ client: String(state.id) });
     });
   });
 
   // Function to broadcast block updates to all connected clients
   const broadcastBlockUpdate = async (blockNumber: number) => {
+    const start = process.hrtime.bigint();
     const block = await getBlockDetails(blockNumber);
     if (!block) {
       logger.warn(`Failed to broadcast block update: Block ${blockNumber} not found`);
@@ -258,22 +286,64 @@ export function createWebSocketServer(port: number = Number(process.env.WS_PORT)
       timestamp: Date.now()
     };
     
+    // Clients that are falling behind only get this instead of the full block and stats
+    const summaryMessage: ServerMessage = {
+      type: 'blockSummary',
+      status: 'success',
+      data: summarizeBlock(block),
+      timestamp: Date.now()
+    };
+    
     const blockMessageStr = JSON.stringify(blockMessage);
     const statsMessageStr = JSON.stringify(statsMessage);
+    const summaryMessageStr = JSON.stringify(summaryMessage);
     let sentCount = 0;
     
-    for (const client of clients) {
-      if (client.readyState === WebSocket.OPEN) {
+    for (const [client, state] of clients) {
+      if (client.readyState !== WebSocket.OPEN) {
+        continue;
+      }
+
+      // Never wait on a slow client: downgrade it, or drop it if its queue keeps growing
+      const mode = nextMode(state.mode, client.bufferedAmount, limits);
+      if (mode === 'drop') {
+        logger.warn(`Dropping WebSocket client ${state.id}: ${client.bufferedAmount} bytes queued`);
+        wsSlowConsumers.inc({ action: 'dropped' });
+        client.terminate();
+        continue;
+      }
+      if (mode !== state.mode) {
+        logger.info(`WebSocket client ${state.id} switched to ${mode} mode (${client.bufferedAmount} bytes queued)`);
+        wsSlowConsumers.inc({ action: mode === 'summary' ? 'downgraded' : 'restored' });
+        state.mode = mode;
+        sendMessage(client, {
+          type: 'modeChanged',
+          status: 'success',
+          data: { mode },
+          message: mode === 'summary' ? 'Falling behind, sending block summaries only' : 'Caught up, sending full updates',
+          timestamp: Date.now()
+        });
+      }
+
+      state.pendingBlocks++;
+      const onWritten = () => { state.pendingBlocks--; };
+      if (mode === 'full') {
         // Send both block and stats updates
-        client.send(blockMessageStr);
+        client.send(blockMessageStr, onWritten);
         if (stats) {
           client.send(statsMessageStr);
         }
-        sentCount++;
+      } else {
+        client.send(summaryMessageStr, onWritten);
       }
+      sentCount++;
+
+      wsClientBufferedBytes.set(client.bufferedAmount, { client: String(state.id) });
+      wsClientLagBlocks.set(state.pendingBlocks, { client: String(state.id) });
     }
     
     logger.debug(`Block and stats updates sent to ${sentCount} clients`);
+    wsBroadcastDuration.observe({}, Number(process.hrtime.bigint() - start) / 1e9);
   };
 
   // Start the server
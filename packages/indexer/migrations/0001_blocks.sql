@@ -0,0 +1,31 @@
+-- Blocks with their transactions as JSON
+CREATE TABLE IF NOT EXISTS blocks (
+    number BIGINT PRIMARY KEY,
+    hash TEXT NOT NULL UNIQUE,
+    parent_hash TEXT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    transactions_root TEXT NOT NULL,
+    state_root TEXT NOT NULL,
+    receipts_root TEXT NOT NULL,
+    gas_used BIGINT NOT NULL,
+    gas_limit BIGINT NOT NULL,
+    base_fee_per_gas BIGINT,
+    extra_data TEXT NOT NULL,
+    miner TEXT NOT NULL,
+    difficulty TEXT NOT NULL,
+    total_difficulty TEXT,
+    size BIGINT NOT NULL,
+    transaction_count BIGINT NOT NULL DEFAULT 0,
+    transactions JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Index on parent_hash for fast lookups
+CREATE INDEX IF NOT EXISTS idx_blocks_parent_hash ON blocks (parent_hash);
+
+-- Index on timestamp for time-based queries
+CREATE INDEX IF NOT EXISTS idx_blocks_timestamp ON blocks (timestamp);
+
+-- Index on block number for sorted queries (DESC for latest blocks first)
+CREATE INDEX IF NOT EXISTS idx_blocks_number_desc ON blocks (number DESC);
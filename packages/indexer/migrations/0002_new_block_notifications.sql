@@ -0,0 +1,22 @@
+-- Notify listeners on the `new_block` channel of every inserted block
+CREATE OR REPLACE FUNCTION notify_new_block()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('new_block', json_build_object(
+        'number', NEW.number,
+        'hash', NEW.hash,
+        'timestamp', NEW.timestamp,
+        'transaction_count', NEW.transaction_count,
+        'gas_used', NEW.gas_used,
+        'base_fee_per_gas', NEW.base_fee_per_gas
+    )::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS block_insert_trigger ON blocks;
+
+CREATE TRIGGER block_insert_trigger
+AFTER INSERT ON blocks
+FOR EACH ROW
+EXECUTE FUNCTION notify_new_block();
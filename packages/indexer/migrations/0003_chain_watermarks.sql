@@ -0,0 +1,7 @@
+-- Finality watermarks (finalized/safe block tags)
+CREATE TABLE IF NOT EXISTS chain_watermarks (
+    tag TEXT PRIMARY KEY,
+    block_number BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
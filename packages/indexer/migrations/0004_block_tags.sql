@@ -0,0 +1,4 @@
+-- Tags populated by the block tagging rules
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_blocks_tags ON blocks USING GIN (tags);
@@ -0,0 +1,12 @@
+-- Single-row table with the chain/indexer health reported by the status monitor
+CREATE TABLE IF NOT EXISTS chain_status (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    status TEXT NOT NULL,
+    chain_head BIGINT,
+    chain_head_timestamp BIGINT,
+    indexed_block BIGINT,
+    indexed_block_timestamp BIGINT,
+    detail TEXT,
+    status_since TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,10 @@
+-- Block ranges imported from another explorer database and validated
+CREATE TABLE IF NOT EXISTS trusted_ranges (
+    id BIGSERIAL PRIMARY KEY,
+    start_block BIGINT NOT NULL,
+    end_block BIGINT NOT NULL,
+    source TEXT NOT NULL,
+    block_count BIGINT NOT NULL,
+    transaction_count BIGINT NOT NULL,
+    imported_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
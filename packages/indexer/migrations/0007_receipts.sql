@@ -0,0 +1,19 @@
+-- Receipts, filled when receipt fetching is enabled
+CREATE TABLE IF NOT EXISTS receipts (
+    transaction_hash TEXT PRIMARY KEY,
+    block_number BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    transaction_index BIGINT NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT,
+    status SMALLINT,
+    gas_used BIGINT,
+    cumulative_gas_used BIGINT NOT NULL,
+    effective_gas_price BIGINT,
+    contract_address TEXT,
+    logs JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Index on block number to load all receipts of a block
+CREATE INDEX IF NOT EXISTS idx_receipts_block_number ON receipts (block_number);
@@ -0,0 +1,20 @@
+-- One row per event log, extracted from receipts
+CREATE TABLE IF NOT EXISTS logs (
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    transaction_index BIGINT NOT NULL,
+    address TEXT NOT NULL,
+    topic0 TEXT,
+    topic1 TEXT,
+    topic2 TEXT,
+    topic3 TEXT,
+    data TEXT NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+
+-- Indexes for "events emitted by contract X" and "events of type Y" queries, newest first
+CREATE INDEX IF NOT EXISTS idx_logs_address ON logs (address, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_logs_topic0 ON logs (topic0, block_number DESC);
@@ -0,0 +1,19 @@
+-- ERC-20 transfers decoded from receipt logs
+CREATE TABLE IF NOT EXISTS token_transfers (
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    token TEXT NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL,
+    PRIMARY KEY (block_number, log_index)
+);
+
+-- Indexes for token and account activity, newest first
+CREATE INDEX IF NOT EXISTS idx_token_transfers_token ON token_transfers (token, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_token_transfers_from ON token_transfers (from_address, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_token_transfers_to ON token_transfers (to_address, block_number DESC);
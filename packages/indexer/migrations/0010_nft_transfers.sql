@@ -0,0 +1,24 @@
+-- ERC-721 and ERC-1155 transfers decoded from receipt logs;
+-- an ERC-1155 batch transfer is stored as one row per token id
+CREATE TABLE IF NOT EXISTS nft_transfers (
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    batch_index BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    token TEXT NOT NULL,
+    standard TEXT NOT NULL,
+    operator TEXT,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    token_id NUMERIC(78, 0) NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL,
+    PRIMARY KEY (block_number, log_index, batch_index)
+);
+
+-- Indexes for collection, token and account activity, newest first
+CREATE INDEX IF NOT EXISTS idx_nft_transfers_token ON nft_transfers (token, token_id, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_nft_transfers_from ON nft_transfers (from_address, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_nft_transfers_to ON nft_transfers (to_address, block_number DESC);
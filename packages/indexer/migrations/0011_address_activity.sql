@@ -0,0 +1,7 @@
+-- Per-address transaction summary maintained alongside blocks
+CREATE TABLE IF NOT EXISTS address_activity (
+    address TEXT PRIMARY KEY,
+    first_seen_block BIGINT NOT NULL,
+    last_seen_block BIGINT NOT NULL,
+    tx_count BIGINT NOT NULL DEFAULT 0
+);
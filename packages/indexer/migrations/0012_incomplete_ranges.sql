@@ -0,0 +1,9 @@
+-- Block ranges still being ingested (historic sync, gap repair),
+-- so aggregates covering them can be flagged as incomplete
+CREATE TABLE IF NOT EXISTS incomplete_ranges (
+    start_block BIGINT NOT NULL,
+    end_block BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (reason, start_block)
+);
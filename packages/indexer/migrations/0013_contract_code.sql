@@ -0,0 +1,19 @@
+-- Contract bytecode: each distinct code once, keyed by its keccak hash,
+-- and the contracts deployed with it
+CREATE TABLE IF NOT EXISTS contract_code (
+    code_hash TEXT PRIMARY KEY,
+    code TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS deployed_code (
+    address TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    code_hash TEXT NOT NULL REFERENCES contract_code (code_hash),
+    PRIMARY KEY (address, block_number)
+);
+
+-- Index for finding every contract deployed with the same code
+CREATE INDEX IF NOT EXISTS idx_deployed_code_code_hash ON deployed_code (code_hash);
@@ -0,0 +1,14 @@
+-- Audit log of operational events (reorg replacements, status changes, gap repairs,
+-- manual maintenance commands), so incidents can be reconstructed afterwards
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    source TEXT NOT NULL,
+    action TEXT NOT NULL,
+    actor TEXT,
+    block_number BIGINT,
+    details JSONB NOT NULL DEFAULT '{}'
+);
+
+-- Index for listing events of one kind, newest first
+CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log (action, id DESC);
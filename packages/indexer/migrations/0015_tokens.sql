@@ -0,0 +1,14 @@
+-- Token contracts seen in transfers, with display metadata filled in
+-- by the token metadata worker (fetched_at stays NULL until it has read the contract)
+CREATE TABLE IF NOT EXISTS tokens (
+    address TEXT PRIMARY KEY,
+    standard TEXT NOT NULL,
+    name TEXT,
+    symbol TEXT,
+    decimals SMALLINT,
+    fetched_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Index for the worker's queue of tokens still to be read
+CREATE INDEX IF NOT EXISTS idx_tokens_pending ON tokens (created_at) WHERE fetched_at IS NULL;
@@ -0,0 +1,7 @@
+-- Names reverse-resolved for addresses by the name resolver
+-- (name is NULL for addresses without a verified name)
+CREATE TABLE IF NOT EXISTS address_names (
+    address TEXT PRIMARY KEY,
+    name TEXT,
+    resolved_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
@@ -0,0 +1,20 @@
+-- Index used to find blocks saved since the gas rollups were last refreshed
+CREATE INDEX IF NOT EXISTS idx_blocks_updated_at ON blocks (updated_at);
+
+-- Hourly and daily gas aggregates maintained by the gas rollup worker
+-- (bucket_start is the unix timestamp the bucket starts at)
+CREATE TABLE IF NOT EXISTS gas_rollups (
+    period TEXT NOT NULL,
+    bucket_start BIGINT NOT NULL,
+    block_count BIGINT NOT NULL,
+    gas_used BIGINT NOT NULL,
+    gas_limit BIGINT NOT NULL,
+    avg_utilization DOUBLE PRECISION,
+    avg_base_fee DOUBLE PRECISION,
+    min_base_fee BIGINT,
+    max_base_fee BIGINT,
+    first_block BIGINT NOT NULL,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (period, bucket_start)
+);
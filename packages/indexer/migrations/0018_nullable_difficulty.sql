@@ -0,0 +1,2 @@
+-- Difficulty is meaningless on chains that are not proof-of-work (see CHAIN_PROFILE)
+ALTER TABLE blocks ALTER COLUMN difficulty DROP NOT NULL;
@@ -0,0 +1,13 @@
+-- Hourly and daily block and transaction counts, updated as each block
+-- is saved (bucket_start is the unix timestamp the bucket starts at)
+CREATE TABLE IF NOT EXISTS throughput_rollups (
+    period TEXT NOT NULL,
+    bucket_start BIGINT NOT NULL,
+    block_count BIGINT NOT NULL,
+    transaction_count BIGINT NOT NULL,
+    first_block BIGINT NOT NULL,
+    last_block BIGINT NOT NULL,
+    first_timestamp BIGINT NOT NULL,
+    last_timestamp BIGINT NOT NULL,
+    PRIMARY KEY (period, bucket_start)
+);
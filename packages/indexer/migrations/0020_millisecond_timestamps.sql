@@ -0,0 +1,17 @@
+-- Millisecond timestamps, for chains whose nodes report them (see CHAIN_TIMESTAMP_UNIT);
+-- NULL when only the whole-second `timestamp` is known
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS timestamp_ms BIGINT;
+
+-- Bucket bounds in milliseconds, so block times under a second can be derived
+ALTER TABLE throughput_rollups
+    ADD COLUMN IF NOT EXISTS first_timestamp_ms BIGINT,
+    ADD COLUMN IF NOT EXISTS last_timestamp_ms BIGINT;
+
+-- Buckets built before the columns existed only know whole seconds
+UPDATE throughput_rollups
+SET first_timestamp_ms = first_timestamp * 1000, last_timestamp_ms = last_timestamp * 1000
+WHERE first_timestamp_ms IS NULL OR last_timestamp_ms IS NULL;
+
+ALTER TABLE throughput_rollups
+    ALTER COLUMN first_timestamp_ms SET NOT NULL,
+    ALTER COLUMN last_timestamp_ms SET NOT NULL;
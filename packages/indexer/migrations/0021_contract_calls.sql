@@ -0,0 +1,12 @@
+-- Contract calls (transactions with a recipient and calldata) per contract
+-- and 5 minute bucket, updated as each block is saved; gas_used needs receipts
+CREATE TABLE IF NOT EXISTS contract_calls (
+    address TEXT NOT NULL,
+    bucket_start BIGINT NOT NULL,
+    tx_count BIGINT NOT NULL,
+    gas_used BIGINT NOT NULL,
+    PRIMARY KEY (address, bucket_start)
+);
+
+-- Index on bucket start for leaderboard windows and pruning
+CREATE INDEX IF NOT EXISTS idx_contract_calls_bucket_start ON contract_calls (bucket_start);
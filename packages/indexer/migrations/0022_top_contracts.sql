@@ -0,0 +1,12 @@
+-- Most called contracts per window (1h, 24h, 7d), ranked by
+-- transaction count and by gas used, rebuilt by the top contracts worker
+CREATE TABLE IF NOT EXISTS top_contracts (
+    period TEXT NOT NULL,
+    address TEXT NOT NULL,
+    tx_count BIGINT NOT NULL,
+    gas_used BIGINT NOT NULL,
+    tx_rank BIGINT NOT NULL,
+    gas_rank BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    PRIMARY KEY (period, address)
+);
@@ -0,0 +1,14 @@
+-- Per-block fee statistics, computed from the block's receipts as they
+-- are saved (amounts in wei; burnt fees are the base fee times the block's gas used)
+CREATE TABLE IF NOT EXISTS block_fees (
+    block_number BIGINT PRIMARY KEY,
+    block_hash TEXT NOT NULL,
+    receipt_count BIGINT NOT NULL,
+    burnt_fees NUMERIC(78, 0) NOT NULL,
+    priority_fees NUMERIC(78, 0) NOT NULL,
+    min_effective_gas_price BIGINT,
+    median_effective_gas_price BIGINT,
+    avg_effective_gas_price DOUBLE PRECISION,
+    max_effective_gas_price BIGINT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
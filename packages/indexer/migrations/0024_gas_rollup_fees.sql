@@ -0,0 +1,6 @@
+-- Fee totals per gas rollup bucket, NULL for buckets without fee statistics
+ALTER TABLE gas_rollups
+    ADD COLUMN IF NOT EXISTS fee_block_count BIGINT,
+    ADD COLUMN IF NOT EXISTS burnt_fees NUMERIC(78, 0),
+    ADD COLUMN IF NOT EXISTS priority_fees NUMERIC(78, 0),
+    ADD COLUMN IF NOT EXISTS avg_effective_gas_price DOUBLE PRECISION;
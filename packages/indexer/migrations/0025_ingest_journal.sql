@@ -0,0 +1,13 @@
+-- Journal of notable ingestion events (connections, subscriptions, persisted blocks,
+-- persistence failures), kept as a ring buffer of the most recent entries
+CREATE TABLE IF NOT EXISTS ingest_journal (
+    id BIGSERIAL PRIMARY KEY,
+    recorded_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    component TEXT NOT NULL,
+    event TEXT NOT NULL,
+    block_number BIGINT,
+    details JSONB NOT NULL DEFAULT '{}'
+);
+
+-- Index for finding what happened around a block
+CREATE INDEX IF NOT EXISTS idx_ingest_journal_block_number ON ingest_journal (block_number);
@@ -0,0 +1,12 @@
+-- Blocks claimed by indexer instances sharing the database, so only one
+-- of them writes each block
+CREATE TABLE IF NOT EXISTS block_claims (
+    block_number BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    instance_id TEXT NOT NULL,
+    claimed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (block_number, block_hash)
+);
+
+-- Index for pruning expired claims
+CREATE INDEX IF NOT EXISTS idx_block_claims_claimed_at ON block_claims (claimed_at);
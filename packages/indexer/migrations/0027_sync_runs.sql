@@ -0,0 +1,18 @@
+-- Historic sync runs with their completion report (range, blocks stored,
+-- gaps, failed fetches, rate and database growth)
+CREATE TABLE IF NOT EXISTS sync_runs (
+    id BIGSERIAL PRIMARY KEY,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    from_block BIGINT NOT NULL,
+    to_block BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    blocks_persisted BIGINT NOT NULL,
+    gaps_detected BIGINT NOT NULL,
+    gaps_repaired BIGINT NOT NULL,
+    failed_ranges JSONB NOT NULL DEFAULT '[]',
+    duration_ms BIGINT NOT NULL,
+    blocks_per_second DOUBLE PRECISION NOT NULL,
+    db_size_before BIGINT,
+    db_size_after BIGINT
+);
@@ -0,0 +1,2 @@
+-- Earliest block the RPC node can serve, set when historic sync finds older blocks pruned
+ALTER TABLE chain_status ADD COLUMN IF NOT EXISTS earliest_available_block BIGINT;
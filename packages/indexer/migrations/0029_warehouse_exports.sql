@@ -0,0 +1,8 @@
+-- High-water marks of the blocks shipped to each analytics warehouse sink
+CREATE TABLE IF NOT EXISTS warehouse_exports (
+    sink TEXT PRIMARY KEY,
+    last_block BIGINT NOT NULL,
+    blocks_exported BIGINT NOT NULL DEFAULT 0,
+    transactions_exported BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,10 @@
+-- Sync checkpoint: how far the stored blocks are contiguous and which ranges
+-- above that are still missing, so a restart resumes below gaps left by out-of-order writes
+CREATE TABLE IF NOT EXISTS sync_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    contiguous_block BIGINT,
+    highest_block BIGINT,
+    pending_ranges JSONB NOT NULL DEFAULT '[]',
+    advanced_at TIMESTAMP WITH TIME ZONE,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
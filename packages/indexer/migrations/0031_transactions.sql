@@ -0,0 +1,44 @@
+-- One row per transaction of the stored blocks, so they can be looked up
+-- by hash, sender or recipient without unpacking the blocks' JSONB
+CREATE TABLE IF NOT EXISTS transactions (
+    hash TEXT PRIMARY KEY,
+    block_number BIGINT NOT NULL REFERENCES blocks (number) ON DELETE CASCADE,
+    block_hash TEXT NOT NULL,
+    transaction_index BIGINT NOT NULL,
+    from_address TEXT,
+    to_address TEXT,
+    value NUMERIC(78, 0),
+    gas BIGINT,
+    gas_price BIGINT,
+    nonce BIGINT,
+    input TEXT
+);
+
+-- Indexes for a block's transactions and an address's transactions, newest first
+CREATE INDEX IF NOT EXISTS idx_transactions_block ON transactions (block_number, transaction_index);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_from ON transactions (from_address, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_to ON transactions (to_address, block_number DESC);
+
+-- Fill the table from blocks stored before it existed; a no-op once it has rows
+INSERT INTO transactions (
+    hash, block_number, block_hash, transaction_index, from_address, to_address,
+    value, gas, gas_price, nonce, input
+)
+SELECT
+    tx->>'hash',
+    b.number,
+    b.hash,
+    (tx->>'transaction_index')::BIGINT,
+    lower(tx->>'from'),
+    CASE WHEN tx->>'from' IS NOT NULL THEN lower(tx->>'to') END,
+    CASE WHEN tx->>'from' IS NOT NULL THEN (tx->>'value')::NUMERIC END,
+    CASE WHEN tx->>'from' IS NOT NULL THEN LEAST((tx->>'gas')::NUMERIC, 9223372036854775807)::BIGINT END,
+    CASE WHEN tx->>'from' IS NOT NULL THEN LEAST((tx->>'gas_price')::NUMERIC, 9223372036854775807)::BIGINT END,
+    CASE WHEN tx->>'from' IS NOT NULL THEN LEAST((tx->>'nonce')::NUMERIC, 9223372036854775807)::BIGINT END,
+    CASE WHEN tx->>'from' IS NOT NULL THEN tx->>'input' END
+FROM blocks b
+CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+WHERE NOT EXISTS (SELECT 1 FROM transactions)
+ON CONFLICT (hash) DO NOTHING;
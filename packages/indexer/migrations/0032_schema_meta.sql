@@ -0,0 +1,31 @@
+-- Version of the schema (single row): the latest migration applied, so binaries that skip
+-- migrations can refuse to run against a schema older than they expect
+CREATE TABLE IF NOT EXISTS schema_meta (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Keep the version current however migrations are applied (the indexer, `db`, sqlx-cli);
+-- an older release never lowers it
+CREATE OR REPLACE FUNCTION record_schema_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO schema_meta (version) VALUES (NEW.version)
+    ON CONFLICT (id) DO UPDATE SET
+        version = GREATEST(schema_meta.version, EXCLUDED.version),
+        updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS schema_version_trigger ON _sqlx_migrations;
+
+CREATE TRIGGER schema_version_trigger
+AFTER INSERT ON _sqlx_migrations
+FOR EACH ROW
+EXECUTE FUNCTION record_schema_version();
+
+INSERT INTO schema_meta (version)
+SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations
+ON CONFLICT (id) DO NOTHING;
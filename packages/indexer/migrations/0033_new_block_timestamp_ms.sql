@@ -0,0 +1,17 @@
+-- Include the millisecond timestamp in `new_block` notifications, so listeners can derive
+-- block times under a second (NULL when only the whole-second `timestamp` is known)
+CREATE OR REPLACE FUNCTION notify_new_block()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('new_block', json_build_object(
+        'number', NEW.number,
+        'hash', NEW.hash,
+        'timestamp', NEW.timestamp,
+        'timestamp_ms', NEW.timestamp_ms,
+        'transaction_count', NEW.transaction_count,
+        'gas_used', NEW.gas_used,
+        'base_fee_per_gas', NEW.base_fee_per_gas
+    )::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,191 @@
+//! Live terminal dashboard of the notification stream: the latest blocks, rolling TPS,
+//! a block time sparkline and how far the stored blocks lag behind the wall clock
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Row, Sparkline, Table};
+use ratatui::DefaultTerminal;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::format::format_integer;
+use crate::BlockNotification;
+
+/// Blocks kept for the rolling statistics and the sparkline
+const WINDOW_BLOCKS: usize = 120;
+
+/// A block as received, with the time since the block before it
+struct ReceivedBlock {
+    block: BlockNotification,
+    block_time_ms: Option<u64>,
+    received_at: Instant,
+}
+
+pub struct Dashboard {
+    terminal: DefaultTerminal,
+    /// Latest blocks, newest first
+    blocks: VecDeque<ReceivedBlock>,
+    started_at: Instant,
+}
+
+impl Dashboard {
+    /// Take over the terminal until the dashboard is dropped
+    pub fn start() -> Result<Self> {
+        Ok(Self {
+            terminal: ratatui::init(),
+            blocks: VecDeque::with_capacity(WINDOW_BLOCKS),
+            started_at: Instant::now(),
+        })
+    }
+
+    pub fn push(&mut self, block: BlockNotification, block_time_ms: Option<u64>) {
+        self.blocks.push_front(ReceivedBlock { block, block_time_ms, received_at: Instant::now() });
+        self.blocks.truncate(WINDOW_BLOCKS);
+    }
+
+    /// Whether q, Esc or Ctrl-C was pressed since the last check
+    pub fn quit_requested(&self) -> Result<bool> {
+        while event::poll(Duration::ZERO)? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)) {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Transactions per second over the blocks in the window, from their timestamps
+    fn rolling_tps(&self) -> Option<f64> {
+        let newest = self.blocks.front()?;
+        let oldest = self.blocks.back()?;
+        let span_ms = newest.block.timestamp_ms().checked_sub(oldest.block.timestamp_ms())?;
+        if span_ms == 0 {
+            return None;
+        }
+        // The oldest block's transactions were produced before the span starts
+        let transactions: u64 = self.blocks.iter().rev().skip(1).map(|received| received.block.transaction_count).sum();
+        Some(transactions as f64 * 1000.0 / span_ms as f64)
+    }
+
+    /// Mean time between consecutive blocks in the window, in milliseconds
+    fn average_block_time_ms(&self) -> Option<f64> {
+        let block_times: Vec<u64> = self.blocks.iter().filter_map(|received| received.block_time_ms).collect();
+        if block_times.is_empty() {
+            return None;
+        }
+        Some(block_times.iter().sum::<u64>() as f64 / block_times.len() as f64)
+    }
+
+    pub fn draw(&mut self) -> Result<()> {
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let label = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+        let value = Style::default().fg(Color::Cyan);
+        let unknown = || "-".to_string();
+
+        let latest = self.blocks.front();
+        let summary = vec![
+            Line::from(vec![
+                Span::styled("Latest block: ", label),
+                Span::styled(latest.map(|received| format!("#{}", received.block.number)).unwrap_or_else(unknown), value),
+                Span::styled("   Lag: ", label),
+                Span::styled(
+                    latest
+                        .map(|received| format!("{:.1}s", now_ms.saturating_sub(received.block.timestamp_ms()) as f64 / 1000.0))
+                        .unwrap_or_else(unknown),
+                    value,
+                ),
+                Span::styled("   Received: ", label),
+                Span::styled(
+                    latest
+                        .map(|received| format!("{}s ago", received.received_at.elapsed().as_secs()))
+                        .unwrap_or_else(|| format!("nothing in {}s", self.started_at.elapsed().as_secs())),
+                    value,
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Rolling TPS: ", label),
+                Span::styled(self.rolling_tps().map(|tps| format!("{:.2}", tps)).unwrap_or_else(unknown), value),
+                Span::styled("   Avg block time: ", label),
+                Span::styled(
+                    self.average_block_time_ms().map(|ms| format!("{:.3}s", ms / 1000.0)).unwrap_or_else(unknown),
+                    value,
+                ),
+                Span::styled(format!("   (last {} blocks)", self.blocks.len()), Style::default().fg(Color::DarkGray)),
+            ]),
+        ];
+
+        // Oldest first, so the sparkline scrolls left like a chart
+        let block_times: Vec<u64> = self.blocks.iter().rev().map(|received| received.block_time_ms.unwrap_or(0)).collect();
+
+        let rows: Vec<Row> = self.blocks.iter().map(|received| {
+            let block = &received.block;
+            Row::new(vec![
+                block.number.to_string(),
+                format!("{}…", block.hash.chars().take(18).collect::<String>()),
+                format_integer(block.transaction_count),
+                block.gas_used.map(format_integer).unwrap_or_else(unknown),
+                received.block_time_ms.map(|ms| format!("{:.3}s", ms as f64 / 1000.0)).unwrap_or_else(unknown),
+                format!("{:.1}s", now_ms.saturating_sub(block.timestamp_ms()) as f64 / 1000.0),
+            ])
+        }).collect();
+
+        self.terminal.draw(|frame| {
+            let [summary_area, sparkline_area, table_area, help_area] = Layout::vertical([
+                Constraint::Length(4),
+                Constraint::Length(7),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ])
+            .areas(frame.area());
+
+            frame.render_widget(
+                Paragraph::new(summary).block(Block::bordered().title(" Block watcher ")),
+                summary_area,
+            );
+
+            // Keep the newest block times that fit, right-aligned
+            let width = sparkline_area.width.saturating_sub(2) as usize;
+            let visible = &block_times[block_times.len().saturating_sub(width)..];
+            frame.render_widget(
+                Sparkline::default()
+                    .block(Block::bordered().title(" Block time "))
+                    .style(Style::default().fg(Color::Green))
+                    .data(visible),
+                sparkline_area,
+            );
+
+            let header = Row::new(vec!["Block", "Hash", "Txs", "Gas used", "Block time", "Age"])
+                .style(label);
+            let widths = [
+                Constraint::Length(12),
+                Constraint::Length(20),
+                Constraint::Length(10),
+                Constraint::Length(16),
+                Constraint::Length(11),
+                Constraint::Length(10),
+            ];
+            frame.render_widget(
+                Table::new(rows, widths).header(header).block(Block::bordered().title(" Latest blocks ")),
+                table_area,
+            );
+
+            frame.render_widget(
+                Paragraph::new("q to quit").style(Style::default().fg(Color::DarkGray)),
+                help_area,
+            );
+        })?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
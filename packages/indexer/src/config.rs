@@ -1,14 +1,25 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
+use tracing::warn;
+
+use crate::models::ChainProfile;
+use crate::sync::PAGERDUTY_EVENTS_URL;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub database_url: String,
     pub http_provider_url: String,
     pub ws_provider_url: String,
+    pub ws_provider_urls: Vec<String>,
+    pub rpc_authorization: Option<String>,
+    pub rpc_ca_cert: Option<String>,
+    pub rpc_tls_insecure: bool,
+    pub rpc_proxy: Option<String>,
     pub start_block: u64,
     pub blocks_from_tip: Option<u64>,  // NEW: Number of blocks to sync from the latest
+    pub end_block: Option<u64>,
     pub batch_size: usize,
     pub max_concurrent_requests: usize,
     pub retry_delay: u64,
@@ -17,6 +28,70 @@ pub struct Config {
     pub block_queue_size: usize,
     pub db_workers: usize,
     pub max_concurrent_batches: usize,
+    pub rpc_rate_limit: u32,
+    pub slow_query_threshold_ms: u64,
+    pub metrics_addr: Option<String>,
+    pub skip_migrations: bool,
+    pub dry_run: bool,
+    pub finality_poll_interval: u64,
+    pub strict_schema_check: bool,
+    pub gap_repair_interval: u64,
+    pub sync_checkpoint_interval: u64,
+    pub tag_rules_file: Option<String>,
+    pub fetch_full_transactions: bool,
+    pub chain_stall_threshold: u64,
+    pub fetch_receipts: bool,
+    pub chain_profile: ChainProfile,
+    pub ws_head_timeout_blocks: u64,
+    pub token_metadata_interval: u64,
+    pub gas_rollup_interval: u64,
+    pub top_contracts_interval: u64,
+    pub top_contracts_size: u32,
+    pub retention_days: u64,
+    pub retention_interval: u64,
+    pub maintenance_interval: u64,
+    pub maintenance_analyze_threshold: u64,
+    pub maintenance_materialized_views: Vec<String>,
+    pub idle_after: u64,
+    pub idle_interval_multiplier: u32,
+    pub ingest_journal_size: u64,
+    pub block_dedup_cache_size: usize,
+    pub block_claim_ttl: u64,
+    pub instance_id: String,
+    pub name_registry_address: Option<String>,
+    pub name_refresh_interval: u64,
+    pub slo_evaluation_interval: u64,
+    pub slo_window: u64,
+    pub slo_burn_rate_threshold: f64,
+    pub slo_persist_latency_ms: u64,
+    pub slo_persist_target: f64,
+    pub slo_max_lag_blocks: u64,
+    pub slo_lag_target: f64,
+    pub alert_stall_seconds: u64,
+    pub alert_max_lag_blocks: u64,
+    pub alert_check_interval: u64,
+    pub alert_webhook_url: Option<String>,
+    pub alert_pagerduty_routing_key: Option<String>,
+    pub alert_pagerduty_url: String,
+    pub write_throttle_latency_ms: u64,
+    pub write_throttle_max_replication_lag: Option<u64>,
+    pub write_throttle_max_delay_ms: u64,
+    pub ordered_commits: bool,
+    pub ordered_commit_max_wait: u64,
+    pub warehouse_url: Option<String>,
+    #[cfg_attr(not(feature = "warehouse"), allow(dead_code))]
+    pub warehouse_database: String,
+    #[cfg_attr(not(feature = "warehouse"), allow(dead_code))]
+    pub warehouse_user: Option<String>,
+    #[cfg_attr(not(feature = "warehouse"), allow(dead_code))]
+    pub warehouse_password: Option<String>,
+    #[cfg_attr(not(feature = "warehouse"), allow(dead_code))]
+    pub warehouse_interval: u64,
+    #[cfg_attr(not(feature = "warehouse"), allow(dead_code))]
+    pub warehouse_batch_size: u64,
+    pub remote_explorer_url: Option<String>,
+    #[cfg_attr(not(feature = "remote-sync"), allow(dead_code))]
+    pub remote_explorer_api_key: Option<String>,
 }
 
 impl Config {
@@ -24,14 +99,48 @@ impl Config {
         // Load .env file if it exists
         let _ = dotenv::dotenv();
 
+        // Settings from a TOML or YAML file fill in what the environment and .env leave unset
+        if let Some(path) = env::var("CONFIG_FILE").ok().filter(|path| !path.is_empty()) {
+            load_file(&path)?;
+        }
+
         let database_url = env::var("DATABASE_URL")
             .context("DATABASE_URL must be set")?;
 
-        let http_provider_url = env::var("HTTP_PROVIDER_URL")
-            .context("HTTP_PROVIDER_URL must be set")?;
+        // Mirror another explorer instance's API instead of syncing from the chain
+        let remote_explorer_url = env::var("REMOTE_EXPLORER_URL").ok().filter(|url| !url.is_empty());
+        let remote_explorer_api_key = env::var("REMOTE_EXPLORER_API_KEY").ok().filter(|key| !key.is_empty());
 
-        let ws_provider_url = env::var("WS_PROVIDER_URL")
-            .context("WS_PROVIDER_URL must be set")?;
+        // Provider URLs are not used when mirroring a remote explorer
+        let http_provider_url = match env::var("HTTP_PROVIDER_URL") {
+            Ok(url) => url,
+            Err(_) if remote_explorer_url.is_some() => String::new(),
+            Err(e) => return Err(e).context("HTTP_PROVIDER_URL must be set"),
+        };
+
+        // Comma-separated; live sync fails over between them, block fetches are spread across them,
+        // other components use the first
+        let ws_provider_urls: Vec<String> = match env::var("WS_PROVIDER_URL") {
+            Ok(urls) => urls.split(',').map(|url| url.trim().to_string()).filter(|url| !url.is_empty()).collect(),
+            Err(_) if remote_explorer_url.is_some() => Vec::new(),
+            Err(e) => return Err(e).context("WS_PROVIDER_URL must be set"),
+        };
+        let ws_provider_url = ws_provider_urls.first().cloned().unwrap_or_default();
+
+        // Authorization header value for authenticated RPC endpoints, e.g. `Bearer <token>`
+        let rpc_authorization = env::var("RPC_AUTHORIZATION").ok().filter(|value| !value.is_empty());
+
+        // Extra root certificates for HTTP endpoints behind a private CA, as a PEM file
+        let rpc_ca_cert = env::var("RPC_CA_CERT").ok().filter(|path| !path.is_empty());
+
+        // Skip certificate verification for HTTP endpoints, an explicit opt-in for test setups
+        let rpc_tls_insecure = env::var("RPC_TLS_INSECURE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("RPC_TLS_INSECURE must be true or false")?;
+
+        // HTTP proxy for HTTP endpoints, e.g. `http://proxy.internal:3128`
+        let rpc_proxy = env::var("RPC_PROXY").ok().filter(|url| !url.is_empty());
 
         let start_block = env::var("START_BLOCK")
             .unwrap_or_else(|_| "0".to_string())
@@ -52,6 +161,18 @@ impl Config {
             Err(_) => None,
         };
 
+        // Sync up to this block and exit instead of following the chain
+        let end_block = match env::var("END_BLOCK") {
+            Ok(val) if !val.is_empty() => {
+                let end_block: u64 = val.parse().context("END_BLOCK must be a valid number")?;
+                if end_block < start_block {
+                    anyhow::bail!("END_BLOCK ({}) must not be below START_BLOCK ({})", end_block, start_block);
+                }
+                Some(end_block)
+            }
+            _ => None,
+        };
+
         let batch_size = env::var("BATCH_SIZE")
             .unwrap_or_else(|_| "100".to_string())
             .parse()
@@ -92,12 +213,321 @@ impl Config {
             .parse()
             .context("MAX_CONCURRENT_BATCHES must be a valid number")?;
 
+        // Requests per second shared by all block fetcher workers (0 disables the limit)
+        let rpc_rate_limit = env::var("RPC_RATE_LIMIT")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("RPC_RATE_LIMIT must be a valid number")?;
+
+        let slow_query_threshold_ms = env::var("SLOW_QUERY_THRESHOLD_MS")
+            .unwrap_or_else(|_| "500".to_string()) // Default to reporting queries slower than 500ms
+            .parse()
+            .context("SLOW_QUERY_THRESHOLD_MS must be a valid number")?;
+
+        // Metrics exporter is only started when a listen address is configured
+        let metrics_addr = env::var("METRICS_ADDR").ok().filter(|addr| !addr.is_empty());
+
+        // Allow schema changes to be applied separately (e.g. by a DBA)
+        let skip_migrations = env::var("SKIP_MIGRATIONS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("SKIP_MIGRATIONS must be true or false")?;
+
+        // Fetch and convert blocks without writing anything, to validate provider or schema changes
+        let dry_run = env::var("DRY_RUN")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("DRY_RUN must be true or false")?;
+
+        let finality_poll_interval = env::var("FINALITY_POLL_INTERVAL")
+            .unwrap_or_else(|_| "12".to_string()) // Default to 12 seconds, 0 disables tracking
+            .parse()
+            .context("FINALITY_POLL_INTERVAL must be a valid number")?;
+
+        // Refuse to start when the live schema differs from what this binary expects
+        let strict_schema_check = env::var("STRICT_SCHEMA_CHECK")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("STRICT_SCHEMA_CHECK must be true or false")?;
+
+        let gap_repair_interval = env::var("GAP_REPAIR_INTERVAL")
+            .unwrap_or_else(|_| "300".to_string()) // Default to 5 minutes, 0 disables repairs
+            .parse()
+            .context("GAP_REPAIR_INTERVAL must be a valid number")?;
+
+        let sync_checkpoint_interval = env::var("SYNC_CHECKPOINT_INTERVAL")
+            .unwrap_or_else(|_| "5".to_string()) // Default to 5 seconds
+            .parse()
+            .context("SYNC_CHECKPOINT_INTERVAL must be a valid number")?;
+
+        // Blocks are only tagged when a rules file is configured
+        let tag_rules_file = env::var("TAG_RULES_FILE").ok().filter(|path| !path.is_empty());
+
+        // Store real from/to/value/gas instead of transaction hashes only
+        let fetch_full_transactions = env::var("FETCH_FULL_TRANSACTIONS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("FETCH_FULL_TRANSACTIONS must be true or false")?;
+
+        let chain_stall_threshold = env::var("CHAIN_STALL_THRESHOLD")
+            .unwrap_or_else(|_| "60".to_string()) // Default to 1 minute, 0 disables status monitoring
+            .parse()
+            .context("CHAIN_STALL_THRESHOLD must be a valid number")?;
+
+        // Store transaction receipts (status, gas used, logs) in the receipts table
+        let fetch_receipts = env::var("FETCH_RECEIPTS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("FETCH_RECEIPTS must be true or false")?;
+
+        // What to expect from the indexed chain's nodes, starting from a preset
+        let mut chain_profile: ChainProfile = env::var("CHAIN_PROFILE")
+            .unwrap_or_else(|_| "ethereum".to_string())
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!("CHAIN_PROFILE: {}", e))?;
+
+        // Overrides for individual aspects of the chain profile
+        if let Ok(head_source) = env::var("CHAIN_HEAD_SOURCE") {
+            chain_profile = chain_profile.with_head_source(
+                head_source.parse().map_err(|e: String| anyhow::anyhow!("CHAIN_HEAD_SOURCE: {}", e))?,
+            );
+        }
+        if let Ok(finality) = env::var("CHAIN_FINALITY") {
+            chain_profile = chain_profile.with_finality(
+                finality.parse().map_err(|e: String| anyhow::anyhow!("CHAIN_FINALITY: {}", e))?,
+            );
+        }
+        if let Ok(timestamp_unit) = env::var("CHAIN_TIMESTAMP_UNIT") {
+            chain_profile = chain_profile.with_timestamp_unit(
+                timestamp_unit.parse().map_err(|e: String| anyhow::anyhow!("CHAIN_TIMESTAMP_UNIT: {}", e))?,
+            );
+        }
+        if let Ok(block_time_ms) = env::var("CHAIN_BLOCK_TIME_MS") {
+            chain_profile = chain_profile.with_block_time_ms(
+                block_time_ms.parse().context("CHAIN_BLOCK_TIME_MS must be a valid number")?,
+            );
+        }
+
+        // Blocks of expected block time without a head on the WebSocket subscription before
+        // it is checked for staleness (0 disables)
+        let ws_head_timeout_blocks = env::var("WS_HEAD_TIMEOUT_BLOCKS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .context("WS_HEAD_TIMEOUT_BLOCKS must be a valid number")?;
+
+        // Read name, symbol and decimals of tokens seen in transfers (0 disables)
+        let token_metadata_interval = env::var("TOKEN_METADATA_INTERVAL")
+            .unwrap_or_else(|_| "30".to_string()) // Default to 30 seconds
+            .parse()
+            .context("TOKEN_METADATA_INTERVAL must be a valid number")?;
+
+        // Maintain hourly and daily gas rollups (0 disables)
+        let gas_rollup_interval = env::var("GAS_ROLLUP_INTERVAL")
+            .unwrap_or_else(|_| "60".to_string()) // Default to 1 minute
+            .parse()
+            .context("GAS_ROLLUP_INTERVAL must be a valid number")?;
+
+        // Refresh the top contracts leaderboard (0 disables)
+        let top_contracts_interval = env::var("TOP_CONTRACTS_INTERVAL")
+            .unwrap_or_else(|_| "60".to_string()) // Default to 1 minute
+            .parse()
+            .context("TOP_CONTRACTS_INTERVAL must be a valid number")?;
+
+        // Contracts kept in the leaderboard per window and ranking
+        let top_contracts_size = env::var("TOP_CONTRACTS_SIZE")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse()
+            .context("TOP_CONTRACTS_SIZE must be a valid number")?;
+
+        // Days to keep the transactions, receipts and logs of a block (0 keeps them forever)
+        let retention_days = env::var("RETENTION_DAYS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("RETENTION_DAYS must be a valid number")?;
+
+        let retention_interval = env::var("RETENTION_INTERVAL")
+            .unwrap_or_else(|_| "3600".to_string()) // Default to 1 hour
+            .parse()
+            .context("RETENTION_INTERVAL must be a valid number")?;
+
+        // Seconds between database maintenance passes (0 disables)
+        let maintenance_interval = env::var("MAINTENANCE_INTERVAL")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("MAINTENANCE_INTERVAL must be a valid number")?;
+
+        // Rows changed since the last ANALYZE for a table to be analyzed again (0 disables)
+        let maintenance_analyze_threshold = env::var("MAINTENANCE_ANALYZE_THRESHOLD")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .context("MAINTENANCE_ANALYZE_THRESHOLD must be a valid number")?;
+
+        // Comma-separated materialized views refreshed on each maintenance pass
+        let maintenance_materialized_views = env::var("MAINTENANCE_MATERIALIZED_VIEWS")
+            .map(|views| views.split(',').map(|view| view.trim().to_string()).filter(|view| !view.is_empty()).collect())
+            .unwrap_or_default();
+
+        // Go idle after this many seconds without indexed transactions (0 disables)
+        let idle_after = env::var("IDLE_AFTER")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("IDLE_AFTER must be a valid number")?;
+
+        // Factor background polling intervals are stretched by while idle
+        let idle_interval_multiplier = env::var("IDLE_INTERVAL_MULTIPLIER")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .context("IDLE_INTERVAL_MULTIPLIER must be a valid number")?;
+
+        // Ingestion events kept in the ingest_journal table (0 disables the journal)
+        let ingest_journal_size = env::var("INGEST_JOURNAL_SIZE")
+            .unwrap_or_else(|_| "100000".to_string())
+            .parse()
+            .context("INGEST_JOURNAL_SIZE must be a valid number")?;
+
+        // Recently persisted blocks remembered to skip duplicate writes (0 disables)
+        let block_dedup_cache_size = env::var("BLOCK_DEDUP_CACHE_SIZE")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .context("BLOCK_DEDUP_CACHE_SIZE must be a valid number")?;
+
+        // Claim blocks in block_claims before writing them, for instances sharing a database;
+        // claims of a dead instance are taken over after this many seconds (0 disables claims)
+        let block_claim_ttl = env::var("BLOCK_CLAIM_TTL")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("BLOCK_CLAIM_TTL must be a valid number")?;
+
+        // Name of this instance in block_claims, unique among instances sharing the database
+        let instance_id = env::var("INSTANCE_ID")
+            .ok()
+            .filter(|val| !val.is_empty())
+            .unwrap_or_else(|| format!(
+                "{}-{}",
+                env::var("HOSTNAME").unwrap_or_else(|_| "indexer".to_string()),
+                std::process::id()
+            ));
+
+        // Reverse-resolve addresses to names through this ENS-style registry (unset disables)
+        let name_registry_address = env::var("NAME_REGISTRY_ADDRESS").ok().filter(|val| !val.is_empty());
+
+        let name_refresh_interval = env::var("NAME_REFRESH_INTERVAL")
+            .unwrap_or_else(|_| "86400".to_string()) // Default to 1 day
+            .parse()
+            .context("NAME_REFRESH_INTERVAL must be a valid number")?;
+
+        // Evaluate latency SLOs and alert on fast error budget burn (0 disables)
+        let slo_evaluation_interval = env::var("SLO_EVALUATION_INTERVAL")
+            .unwrap_or_else(|_| "10".to_string()) // Default to 10 seconds
+            .parse()
+            .context("SLO_EVALUATION_INTERVAL must be a valid number")?;
+
+        let slo_window = env::var("SLO_WINDOW")
+            .unwrap_or_else(|_| "3600".to_string()) // Default to 1 hour
+            .parse()
+            .context("SLO_WINDOW must be a valid number")?;
+
+        let slo_burn_rate_threshold = env::var("SLO_BURN_RATE_THRESHOLD")
+            .unwrap_or_else(|_| "14.4".to_string())
+            .parse()
+            .context("SLO_BURN_RATE_THRESHOLD must be a valid number")?;
+
+        // Blocks persisted within this long of their timestamp
+        let slo_persist_latency_ms = env::var("SLO_PERSIST_LATENCY_MS")
+            .unwrap_or_else(|_| "2000".to_string())
+            .parse()
+            .context("SLO_PERSIST_LATENCY_MS must be a valid number")?;
+
+        let slo_persist_target = parse_slo_target("SLO_PERSIST_TARGET")?;
+
+        // Latest indexed block within this many blocks of the chain head
+        let slo_max_lag_blocks = env::var("SLO_MAX_LAG_BLOCKS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .context("SLO_MAX_LAG_BLOCKS must be a valid number")?;
+
+        let slo_lag_target = parse_slo_target("SLO_LAG_TARGET")?;
+
+        // Alert when no block is persisted for this many seconds (0 disables)
+        let alert_stall_seconds = env::var("ALERT_STALL_SECONDS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("ALERT_STALL_SECONDS must be a valid number")?;
+
+        // Alert when the indexer is more than this many blocks behind the chain head (0 disables)
+        let alert_max_lag_blocks = env::var("ALERT_MAX_LAG_BLOCKS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("ALERT_MAX_LAG_BLOCKS must be a valid number")?;
+
+        let alert_check_interval = env::var("ALERT_CHECK_INTERVAL")
+            .unwrap_or_else(|_| "10".to_string()) // Default to 10 seconds
+            .parse()
+            .context("ALERT_CHECK_INTERVAL must be a valid number")?;
+
+        // Where alerts are sent besides the logs and the audit log
+        let alert_webhook_url = env::var("ALERT_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+        let alert_pagerduty_routing_key = env::var("ALERT_PAGERDUTY_ROUTING_KEY").ok().filter(|key| !key.is_empty());
+        let alert_pagerduty_url = env::var("ALERT_PAGERDUTY_URL")
+            .unwrap_or_else(|_| PAGERDUTY_EVENTS_URL.to_string());
+
+        // Back off block writes when the average write takes longer than this (0 disables)
+        let write_throttle_latency_ms = env::var("WRITE_THROTTLE_LATENCY_MS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .context("WRITE_THROTTLE_LATENCY_MS must be a valid number")?;
+
+        // Optionally also back off while replicas lag further behind than this (seconds)
+        let write_throttle_max_replication_lag = match env::var("WRITE_THROTTLE_MAX_REPLICATION_LAG") {
+            Ok(val) if !val.is_empty() => Some(val.parse().context("WRITE_THROTTLE_MAX_REPLICATION_LAG must be a valid number")?),
+            _ => None,
+        };
+
+        let write_throttle_max_delay_ms = env::var("WRITE_THROTTLE_MAX_DELAY_MS")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse()
+            .context("WRITE_THROTTLE_MAX_DELAY_MS must be a valid number")?;
+
+        // Persist blocks strictly in ascending order, holding back blocks fetched ahead of a missing one
+        let ordered_commits = env::var("ORDERED_COMMITS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .context("ORDERED_COMMITS must be true or false")?;
+
+        // Seconds to wait for a missing block before persisting the blocks after it anyway
+        let ordered_commit_max_wait = env::var("ORDERED_COMMIT_MAX_WAIT")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .context("ORDERED_COMMIT_MAX_WAIT must be a valid number")?;
+
+        // Ship finalized blocks to a ClickHouse warehouse over its HTTP interface (unset disables)
+        let warehouse_url = env::var("WAREHOUSE_URL").ok().filter(|url| !url.is_empty());
+        let warehouse_database = env::var("WAREHOUSE_DATABASE").unwrap_or_else(|_| "default".to_string());
+        let warehouse_user = env::var("WAREHOUSE_USER").ok().filter(|user| !user.is_empty());
+        let warehouse_password = env::var("WAREHOUSE_PASSWORD").ok().filter(|password| !password.is_empty());
+
+        let warehouse_interval = env::var("WAREHOUSE_INTERVAL")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .context("WAREHOUSE_INTERVAL must be a valid number")?;
+
+        let warehouse_batch_size = env::var("WAREHOUSE_BATCH_SIZE")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse()
+            .context("WAREHOUSE_BATCH_SIZE must be a valid number")?;
+
         Ok(Config {
             database_url,
             http_provider_url,
             ws_provider_url,
+            ws_provider_urls,
+            rpc_authorization,
+            rpc_ca_cert,
+            rpc_tls_insecure,
+            rpc_proxy,
             start_block,
             blocks_from_tip,
+            end_block,
             batch_size,
             max_concurrent_requests,
             retry_delay,
@@ -106,6 +536,142 @@ impl Config {
             block_queue_size,
             db_workers,
             max_concurrent_batches,
+            rpc_rate_limit,
+            slow_query_threshold_ms,
+            metrics_addr,
+            skip_migrations,
+            dry_run,
+            finality_poll_interval,
+            strict_schema_check,
+            gap_repair_interval,
+            sync_checkpoint_interval,
+            tag_rules_file,
+            fetch_full_transactions,
+            chain_stall_threshold,
+            fetch_receipts,
+            chain_profile,
+            ws_head_timeout_blocks,
+            token_metadata_interval,
+            gas_rollup_interval,
+            top_contracts_interval,
+            top_contracts_size,
+            retention_days,
+            retention_interval,
+            maintenance_interval,
+            maintenance_analyze_threshold,
+            maintenance_materialized_views,
+            idle_after,
+            idle_interval_multiplier,
+            ingest_journal_size,
+            block_dedup_cache_size,
+            block_claim_ttl,
+            instance_id,
+            name_registry_address,
+            name_refresh_interval,
+            slo_evaluation_interval,
+            slo_window,
+            slo_burn_rate_threshold,
+            slo_persist_latency_ms,
+            slo_persist_target,
+            slo_max_lag_blocks,
+            slo_lag_target,
+            alert_stall_seconds,
+            alert_max_lag_blocks,
+            alert_check_interval,
+            alert_webhook_url,
+            alert_pagerduty_routing_key,
+            alert_pagerduty_url,
+            write_throttle_latency_ms,
+            write_throttle_max_replication_lag,
+            write_throttle_max_delay_ms,
+            ordered_commits,
+            ordered_commit_max_wait,
+            warehouse_url,
+            warehouse_database,
+            warehouse_user,
+            warehouse_password,
+            warehouse_interval,
+            warehouse_batch_size,
+            remote_explorer_url,
+            remote_explorer_api_key,
         })
     }
 }
+
+/// Parse an SLO target, the fraction of samples that must be good (default 0.99)
+fn parse_slo_target(name: &str) -> Result<f64> {
+    let target: f64 = env::var(name)
+        .unwrap_or_else(|_| "0.99".to_string())
+        .parse()
+        .with_context(|| format!("{} must be a valid number", name))?;
+
+    if !(target > 0.0 && target < 1.0) {
+        anyhow::bail!("{} must be between 0 and 1 (exclusive)", name);
+    }
+    Ok(target)
+}
+
+/// Settings read from the environment, which a config file may set. Keys outside this list
+/// are likely typos and are ignored with a warning.
+const SETTINGS: &[&str] = &[
+    "ALERT_CHECK_INTERVAL", "ALERT_MAX_LAG_BLOCKS", "ALERT_PAGERDUTY_ROUTING_KEY",
+    "ALERT_PAGERDUTY_URL", "ALERT_STALL_SECONDS", "ALERT_WEBHOOK_URL", "BATCH_SIZE",
+    "BLOCKS_FROM_TIP", "BLOCK_CLAIM_TTL", "BLOCK_DEDUP_CACHE_SIZE", "BLOCK_QUEUE_SIZE",
+    "CHAIN_BLOCK_TIME_MS", "CHAIN_FINALITY", "CHAIN_HEAD_SOURCE", "CHAIN_PROFILE",
+    "CHAIN_STALL_THRESHOLD", "CHAIN_TIMESTAMP_UNIT", "DATABASE_URL", "DB_WORKERS", "DRY_RUN",
+    "END_BLOCK", "FETCH_FULL_TRANSACTIONS", "FETCH_RECEIPTS", "FINALITY_POLL_INTERVAL",
+    "GAP_REPAIR_INTERVAL", "GAS_ROLLUP_INTERVAL", "HOSTNAME", "HTTP_PROVIDER_URL", "IDLE_AFTER",
+    "IDLE_INTERVAL_MULTIPLIER", "INGEST_JOURNAL_SIZE", "INSTANCE_ID",
+    "MAINTENANCE_ANALYZE_THRESHOLD", "MAINTENANCE_INTERVAL", "MAINTENANCE_MATERIALIZED_VIEWS",
+    "MAX_CONCURRENT_BATCHES", "MAX_CONCURRENT_REQUESTS", "MAX_RETRIES", "METRICS_ADDR",
+    "NAME_REFRESH_INTERVAL", "NAME_REGISTRY_ADDRESS", "ORDERED_COMMITS",
+    "ORDERED_COMMIT_MAX_WAIT", "REMOTE_EXPLORER_API_KEY", "REMOTE_EXPLORER_URL",
+    "RETENTION_DAYS", "RETENTION_INTERVAL", "RETRY_DELAY", "RPC_AUTHORIZATION",
+    "RPC_BATCH_SIZE", "RPC_CA_CERT", "RPC_PROXY", "RPC_RATE_LIMIT", "RPC_TLS_INSECURE",
+    "SKIP_MIGRATIONS", "SLOW_QUERY_THRESHOLD_MS", "SLO_BURN_RATE_THRESHOLD",
+    "SLO_EVALUATION_INTERVAL", "SLO_LAG_TARGET", "SLO_MAX_LAG_BLOCKS", "SLO_PERSIST_LATENCY_MS",
+    "SLO_PERSIST_TARGET", "SLO_WINDOW", "START_BLOCK", "STRICT_SCHEMA_CHECK",
+    "SYNC_CHECKPOINT_INTERVAL", "TAG_RULES_FILE", "TOKEN_METADATA_INTERVAL",
+    "TOP_CONTRACTS_INTERVAL", "TOP_CONTRACTS_SIZE", "WAREHOUSE_BATCH_SIZE",
+    "WAREHOUSE_DATABASE", "WAREHOUSE_INTERVAL", "WAREHOUSE_PASSWORD", "WAREHOUSE_URL",
+    "WAREHOUSE_USER", "WRITE_THROTTLE_LATENCY_MS", "WRITE_THROTTLE_MAX_DELAY_MS",
+    "WRITE_THROTTLE_MAX_REPLICATION_LAG", "WS_HEAD_TIMEOUT_BLOCKS", "WS_PROVIDER_URL",
+];
+
+/// Load the settings in a TOML or YAML file (by extension) into the environment, without
+/// overriding variables that are already set. Keys are the environment variable names, in
+/// any case; lists are joined with commas. Unknown keys are skipped with a warning.
+fn load_file(path: &str) -> Result<()> {
+    let settings = ::config::Config::builder()
+        .add_source(::config::File::with_name(path))
+        .build()
+        .and_then(|settings| settings.try_deserialize::<HashMap<String, ::config::Value>>())
+        .with_context(|| format!("Failed to load config file {}", path))?;
+
+    for (key, value) in settings {
+        let name = key.to_uppercase();
+        if !SETTINGS.contains(&name.as_str()) {
+            warn!("Ignoring unknown setting {} in config file {}", key, path);
+            continue;
+        }
+        if env::var_os(&name).is_some() {
+            continue;
+        }
+
+        let value = match value.kind {
+            ::config::ValueKind::Array(values) => values.into_iter()
+                .map(|value| value.into_string())
+                .collect::<Result<Vec<_>, _>>()
+                .map(|values| values.join(",")),
+            ::config::ValueKind::Table(_) => {
+                anyhow::bail!("{} in config file {} must be a value or a list, not a table", key, path)
+            }
+            _ => value.into_string(),
+        }
+        .with_context(|| format!("Invalid {} in config file {}", key, path))?;
+
+        env::set_var(name, value);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use sqlx::PgConnection;
+use tracing::debug;
+
+/// Credit each address involved in the stored block's transactions, as sender or
+/// recipient, with one transaction. Reads the block's `transactions` JSONB, so it
+/// runs after the block row is written.
+pub async fn add_block_activity(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Recording address activity for block {}", block_number);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO address_activity (address, first_seen_block, last_seen_block, tx_count)
+        SELECT address, $1, $1, COUNT(*)
+        FROM (
+            SELECT DISTINCT tx->>'hash' AS hash, lower(a.address) AS address
+            FROM blocks b
+            CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+            CROSS JOIN LATERAL (VALUES (tx->>'from'), (tx->>'to')) AS a(address)
+            WHERE b.number = $1 AND a.address IS NOT NULL
+        ) t
+        GROUP BY address
+        ON CONFLICT (address) DO UPDATE SET
+            first_seen_block = LEAST(address_activity.first_seen_block, EXCLUDED.first_seen_block),
+            last_seen_block = GREATEST(address_activity.last_seen_block, EXCLUDED.last_seen_block),
+            tx_count = address_activity.tx_count + EXCLUDED.tx_count
+        "#,
+        block_number as i64,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Undo `add_block_activity` for a stored block that is about to be replaced by a reorg.
+/// First/last seen blocks are left as they are; the replacement block has the same number.
+pub async fn remove_block_activity(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Removing address activity of replaced block {}", block_number);
+
+    sqlx::query!(
+        r#"
+        UPDATE address_activity AS activity
+        SET tx_count = GREATEST(activity.tx_count - d.tx_count, 0)
+        FROM (
+            SELECT address, COUNT(*) AS tx_count
+            FROM (
+                SELECT DISTINCT tx->>'hash' AS hash, lower(a.address) AS address
+                FROM blocks b
+                CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+                CROSS JOIN LATERAL (VALUES (tx->>'from'), (tx->>'to')) AS a(address)
+                WHERE b.number = $1 AND a.address IS NOT NULL
+            ) t
+            GROUP BY address
+        ) d
+        WHERE activity.address = d.address
+        "#,
+        block_number as i64,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
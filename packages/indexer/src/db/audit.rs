@@ -0,0 +1,79 @@
+use anyhow::Result;
+use sqlx::{PgExecutor, PgPool};
+use sqlx::types::Json;
+use tracing::{debug, error, instrument};
+
+/// A significant operational event, kept in `audit_log` so incidents can be reconstructed
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    /// Component that recorded the event, e.g. `live_sync` or `cli`
+    pub source: &'static str,
+    /// What happened, e.g. `block_replaced`
+    pub action: &'static str,
+    /// Who triggered a manual action; None for automatic ones
+    pub actor: Option<String>,
+    /// Block the event is about, if any
+    pub block_number: Option<u64>,
+    pub details: serde_json::Value,
+}
+
+impl AuditEvent {
+    pub fn new(source: &'static str, action: &'static str) -> Self {
+        Self {
+            source,
+            action,
+            actor: None,
+            block_number: None,
+            details: serde_json::Value::Object(Default::default()),
+        }
+    }
+
+    /// Attribute the event to the user running a manual command (used by the `db` binary)
+    #[allow(dead_code)]
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block_number = Some(block_number);
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Insert an audit event, e.g. within the transaction performing the audited change
+pub async fn insert_audit_event<'e>(executor: impl PgExecutor<'e>, event: &AuditEvent) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_log (source, action, actor, block_number, details)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        event.source,
+        event.action,
+        event.actor,
+        event.block_number.map(|n| n as i64),
+        Json(&event.details) as _,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip(pool, event), fields(source = event.source, action = event.action))]
+pub async fn save_audit_event(pool: &PgPool, event: &AuditEvent) -> Result<()> {
+    debug!("Recording {} event from {}", event.action, event.source);
+
+    match insert_audit_event(pool, event).await {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to record {} event: {}", event.action, e);
+            Err(e.into())
+        }
+    }
+}
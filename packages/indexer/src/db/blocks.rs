@@ -1,20 +1,23 @@
-use anyhow::{Result, Context};
-use sqlx::{PgPool, Row};
+use anyhow::Result;
+use sqlx::PgPool;
 use tracing::{debug, error, instrument};
 use sqlx::postgres::PgQueryResult;
 use sqlx::types::Json;
 
-use crate::models::Block;
+use crate::db::{activity, contract_calls, receipts, throughput, transactions};
+use crate::db::audit::{self, AuditEvent};
+use crate::models::{parse_difficulty, Block, ChainProfile, Transaction};
 
-#[instrument(skip(pool, block), fields(block_number = block.number, block_hash = %block.hash))]
-pub async fn save_block(pool: &PgPool, block: &Block) -> Result<()> {
+#[instrument(skip(pool, block, tags), fields(block_number = block.number, block_hash = %block.hash))]
+pub async fn save_block(pool: &PgPool, block: &Block, tags: &[String], chain_profile: ChainProfile) -> Result<()> {
     debug!("Saving block {} to database", block.number);
     
-    // Convert U256 fields to strings for storage
-    let difficulty = block.difficulty.to_string();
-    let total_difficulty = block.total_difficulty
-        .map(|td| td.to_string())
-        .unwrap_or_default();
+    // Convert U256 fields to strings for storage, leaving them NULL where the chain has no difficulty
+    let (difficulty, total_difficulty) = if chain_profile.has_difficulty() {
+        (block.difficulty.map(|d| d.to_string()), block.total_difficulty.map(|td| td.to_string()))
+    } else {
+        (None, None)
+    };
     
     // Serialize transactions to JSON with additional error handling
     let transactions_json = match serde_json::to_value(&block.transactions) {
@@ -26,53 +29,116 @@ pub async fn save_block(pool: &PgPool, block: &Block) -> Result<()> {
         }
     };
     
-    // Upsert query to handle potential re-orgs
-    let query = r#"
-    INSERT INTO blocks (
-        number, hash, parent_hash, timestamp, transactions_root,
-        state_root, receipts_root, gas_used, gas_limit, base_fee_per_gas,
-        extra_data, miner, difficulty, total_difficulty, size, transaction_count, transactions
-    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
-    ON CONFLICT (number) DO UPDATE SET
-        hash = EXCLUDED.hash,
-        parent_hash = EXCLUDED.parent_hash,
-        timestamp = EXCLUDED.timestamp,
-        transactions_root = EXCLUDED.transactions_root,
-        state_root = EXCLUDED.state_root,
-        receipts_root = EXCLUDED.receipts_root,
-        gas_used = EXCLUDED.gas_used,
-        gas_limit = EXCLUDED.gas_limit,
-        base_fee_per_gas = EXCLUDED.base_fee_per_gas,
-        extra_data = EXCLUDED.extra_data,
-        miner = EXCLUDED.miner,
-        difficulty = EXCLUDED.difficulty,
-        total_difficulty = EXCLUDED.total_difficulty,
-        size = EXCLUDED.size,
-        transaction_count = EXCLUDED.transaction_count, 
-        transactions = EXCLUDED.transactions,
-        updated_at = CURRENT_TIMESTAMP
-    "#;
+    // Gas used per transaction, for the contract call counts
+    let (receipt_hashes, receipt_gas_used): (Vec<String>, Vec<Option<i64>>) = block.receipts.iter()
+        .map(|receipt| (receipt.transaction_hash.clone(), receipt.gas_used.map(|gas| gas as i64)))
+        .unzip();
     
-    let result: Result<PgQueryResult, sqlx::Error> = sqlx::query(query)
-        .bind(block.number as i64)
-        .bind(&block.hash)
-        .bind(&block.parent_hash)
-        .bind(block.timestamp as i64)
-        .bind(&block.transactions_root)
-        .bind(&block.state_root)
-        .bind(&block.receipts_root)
-        .bind(block.gas_used as i64)
-        .bind(block.gas_limit as i64)
-        .bind(block.base_fee_per_gas.map(|fee| fee as i64))
-        .bind(&block.extra_data)
-        .bind(&block.miner)
-        .bind(&difficulty)
-        .bind(&total_difficulty)
-        .bind(block.size as i64)
-        .bind(block.transaction_count as i64)
-        .bind(transactions_json)
-        .execute(pool)
-        .await;
+    let result: Result<PgQueryResult, sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+
+        // Serialize writers of the same block number: the row lock below takes nothing while the
+        // block isn't stored yet, so concurrent first writes (historic sync, live sync and gap
+        // repair) would all take the new block path and count it several times
+        sqlx::query!("SELECT pg_advisory_xact_lock($1)", block.number as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        // Address activity, throughput and contract calls are counted once per block hash, so replays
+        // of the same block are no-ops and a reorged block's counts are undone before its replacement
+        // is counted
+        let previous_hash = sqlx::query_scalar!(
+            "SELECT hash FROM blocks WHERE number = $1 FOR UPDATE",
+            block.number as i64,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        
+        let is_new = previous_hash.as_deref() != Some(block.hash.as_str());
+        if let Some(previous_hash) = previous_hash.as_deref().filter(|_| is_new) {
+            activity::remove_block_activity(&mut tx, block.number).await?;
+            contract_calls::remove_block_contract_calls(&mut tx, block.number).await?;
+            throughput::remove_block_throughput(&mut tx, block.number).await?;
+            transactions::remove_block_transactions(&mut tx, block.number).await?;
+            
+            let event = AuditEvent::new("indexer", "block_replaced")
+                .with_block_number(block.number)
+                .with_details(serde_json::json!({ "oldHash": previous_hash, "newHash": block.hash }));
+            audit::insert_audit_event(&mut *tx, &event).await?;
+        }
+        
+        // Upsert query to handle potential re-orgs
+        let res = sqlx::query!(
+            r#"
+            INSERT INTO blocks (
+                number, hash, parent_hash, timestamp, transactions_root,
+                state_root, receipts_root, gas_used, gas_limit, base_fee_per_gas,
+                extra_data, miner, difficulty, total_difficulty, size, transaction_count, transactions, tags,
+                timestamp_ms
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
+            ON CONFLICT (number) DO UPDATE SET
+                hash = EXCLUDED.hash,
+                parent_hash = EXCLUDED.parent_hash,
+                timestamp = EXCLUDED.timestamp,
+                timestamp_ms = EXCLUDED.timestamp_ms,
+                transactions_root = EXCLUDED.transactions_root,
+                state_root = EXCLUDED.state_root,
+                receipts_root = EXCLUDED.receipts_root,
+                gas_used = EXCLUDED.gas_used,
+                gas_limit = EXCLUDED.gas_limit,
+                base_fee_per_gas = EXCLUDED.base_fee_per_gas,
+                extra_data = EXCLUDED.extra_data,
+                miner = EXCLUDED.miner,
+                difficulty = EXCLUDED.difficulty,
+                total_difficulty = EXCLUDED.total_difficulty,
+                size = EXCLUDED.size,
+                transaction_count = EXCLUDED.transaction_count,
+                transactions = EXCLUDED.transactions,
+                tags = EXCLUDED.tags,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+            block.number as i64,
+            &block.hash,
+            &block.parent_hash,
+            chain_profile.timestamp_secs(block.timestamp) as i64,
+            &block.transactions_root,
+            &block.state_root,
+            &block.receipts_root,
+            block.gas_used as i64,
+            block.gas_limit as i64,
+            block.base_fee_per_gas.map(|fee| fee as i64),
+            &block.extra_data,
+            &block.miner,
+            difficulty,
+            total_difficulty,
+            block.size as i64,
+            block.transaction_count as i64,
+            transactions_json,
+            tags,
+            chain_profile.timestamp_ms(block.timestamp).map(|ms| ms as i64),
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        // Written again for the same block too, as a refetch may bring full transactions
+        transactions::add_block_transactions(&mut tx, block.number).await?;
+        
+        if is_new {
+            activity::add_block_activity(&mut tx, block.number).await?;
+            throughput::add_block_throughput(&mut tx, block.number).await?;
+            contract_calls::add_block_contract_calls(&mut tx, block.number, &receipt_hashes, &receipt_gas_used).await?;
+        }
+
+        // In the same transaction, so a failed receipts write doesn't leave a block that looks
+        // complete to the gap repairer
+        if !block.receipts.is_empty() {
+            receipts::save_receipts(&mut tx, block).await?;
+        }
+        
+        tx.commit().await?;
+        Ok(res)
+    }
+    .await;
     
     match result {
         Ok(res) => {
@@ -91,21 +157,13 @@ pub async fn get_latest_block_number(pool: &PgPool) -> Result<Option<u64>> {
     debug!("Fetching latest block number from database");
     
     // Use the optimized index for faster MAX lookup
-    let query = "SELECT MAX(number) as latest FROM blocks";
-    
-    let result = sqlx::query(query)
-        .fetch_optional(pool)
+    let result = sqlx::query_scalar!("SELECT MAX(number) as latest FROM blocks")
+        .fetch_one(pool)
         .await;
     
     match result {
-        Ok(row) => {
-            let latest = match row {
-                Some(row) => {
-                    let number: Option<i64> = row.try_get("latest").ok();
-                    number.map(|n| n as u64)
-                },
-                None => None,
-            };
+        Ok(latest) => {
+            let latest = latest.map(|n| n as u64);
             debug!("Latest block number from database: {:?}", latest);
             Ok(latest)
         },
@@ -117,15 +175,24 @@ pub async fn get_latest_block_number(pool: &PgPool) -> Result<Option<u64>> {
 }
 
 #[instrument(skip(pool))]
+#[allow(dead_code)]
 pub async fn get_head_block(pool: &PgPool) -> Result<Option<crate::models::Block>> {
     debug!("Fetching head block from database");
     
     // Use the optimized index for this query - ORDER BY number DESC LIMIT 1 is efficient with our index
-    let query = "SELECT * FROM blocks ORDER BY number DESC LIMIT 1";
-    
-    let result = sqlx::query_as::<_, BlockRow>(query)
-        .fetch_optional(pool)
-        .await;
+    let result = sqlx::query_as!(
+        BlockRow,
+        r#"
+        SELECT
+            number, hash, parent_hash, timestamp, transactions_root, state_root, receipts_root,
+            gas_used, gas_limit, base_fee_per_gas, extra_data, miner, difficulty, total_difficulty,
+            size, transaction_count, transactions as "transactions: Json<Vec<Transaction>>"
+        FROM blocks
+        ORDER BY number DESC LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await;
     
     match result {
         Ok(row) => {
@@ -145,6 +212,7 @@ pub async fn get_head_block(pool: &PgPool) -> Result<Option<crate::models::Block
 }
 
 #[instrument(skip(pool))]
+#[allow(dead_code)]
 pub async fn get_blocks_paginated(
     pool: &PgPool, 
     offset: u64, 
@@ -154,17 +222,39 @@ pub async fn get_blocks_paginated(
     debug!("Fetching paginated blocks with offset {} and limit {}", offset, limit);
     
     // Use the optimized index for efficient pagination
-    let query = if descending {
-        "SELECT * FROM blocks ORDER BY number DESC LIMIT $1 OFFSET $2"
+    let result = if descending {
+        sqlx::query_as!(
+            BlockRow,
+            r#"
+            SELECT
+                number, hash, parent_hash, timestamp, transactions_root, state_root, receipts_root,
+                gas_used, gas_limit, base_fee_per_gas, extra_data, miner, difficulty, total_difficulty,
+                size, transaction_count, transactions as "transactions: Json<Vec<Transaction>>"
+            FROM blocks
+            ORDER BY number DESC LIMIT $1 OFFSET $2
+            "#,
+            limit as i64,
+            offset as i64,
+        )
+        .fetch_all(pool)
+        .await
     } else {
-        "SELECT * FROM blocks ORDER BY number ASC LIMIT $1 OFFSET $2"
-    };
-    
-    let result = sqlx::query_as::<_, BlockRow>(query)
-        .bind(limit as i64)
-        .bind(offset as i64)
+        sqlx::query_as!(
+            BlockRow,
+            r#"
+            SELECT
+                number, hash, parent_hash, timestamp, transactions_root, state_root, receipts_root,
+                gas_used, gas_limit, base_fee_per_gas, extra_data, miner, difficulty, total_difficulty,
+                size, transaction_count, transactions as "transactions: Json<Vec<Transaction>>"
+            FROM blocks
+            ORDER BY number ASC LIMIT $1 OFFSET $2
+            "#,
+            limit as i64,
+            offset as i64,
+        )
         .fetch_all(pool)
-        .await;
+        .await
+    };
     
     match result {
         Ok(rows) => {
@@ -188,12 +278,20 @@ pub async fn get_blocks_paginated(
 pub async fn get_block_by_number(pool: &PgPool, block_number: u64) -> Result<Option<Block>> {
     debug!("Fetching block {} from database", block_number);
     
-    let query = "SELECT * FROM blocks WHERE number = $1";
-    
-    let result = sqlx::query_as::<_, BlockRow>(query)
-        .bind(block_number as i64)
-        .fetch_optional(pool)
-        .await;
+    let result = sqlx::query_as!(
+        BlockRow,
+        r#"
+        SELECT
+            number, hash, parent_hash, timestamp, transactions_root, state_root, receipts_root,
+            gas_used, gas_limit, base_fee_per_gas, extra_data, miner, difficulty, total_difficulty,
+            size, transaction_count, transactions as "transactions: Json<Vec<Transaction>>"
+        FROM blocks
+        WHERE number = $1
+        "#,
+        block_number as i64,
+    )
+    .fetch_optional(pool)
+    .await;
     
     match result {
         Ok(row) => {
@@ -212,16 +310,56 @@ pub async fn get_block_by_number(pool: &PgPool, block_number: u64) -> Result<Opt
     }
 }
 
+/// Stored blocks numbered `from` to `to` inclusive, in ascending order
+#[cfg(feature = "warehouse")]
+#[instrument(skip(pool))]
+pub async fn get_blocks_range(pool: &PgPool, from: u64, to: u64) -> Result<Vec<Block>> {
+    debug!("Fetching blocks {} to {} from database", from, to);
+    
+    let result = sqlx::query_as!(
+        BlockRow,
+        r#"
+        SELECT
+            number, hash, parent_hash, timestamp, transactions_root, state_root, receipts_root,
+            gas_used, gas_limit, base_fee_per_gas, extra_data, miner, difficulty, total_difficulty,
+            size, transaction_count, transactions as "transactions: Json<Vec<Transaction>>"
+        FROM blocks
+        WHERE number BETWEEN $1 AND $2
+        ORDER BY number ASC
+        "#,
+        from as i64,
+        to as i64,
+    )
+    .fetch_all(pool)
+    .await;
+    
+    match result {
+        Ok(rows) => rows.into_iter().map(|row| row.into_block()).collect(),
+        Err(e) => {
+            error!("Failed to get blocks {} to {}: {}", from, to, e);
+            Err(e.into())
+        }
+    }
+}
+
 #[instrument(skip(pool), fields(block_hash = %block_hash))]
 pub async fn get_block_by_hash(pool: &PgPool, block_hash: &str) -> Result<Option<Block>> {
     debug!("Fetching block with hash {} from database", block_hash);
     
-    let query = "SELECT * FROM blocks WHERE hash = $1";
-    
-    let result = sqlx::query_as::<_, BlockRow>(query)
-        .bind(block_hash)
-        .fetch_optional(pool)
-        .await;
+    let result = sqlx::query_as!(
+        BlockRow,
+        r#"
+        SELECT
+            number, hash, parent_hash, timestamp, transactions_root, state_root, receipts_root,
+            gas_used, gas_limit, base_fee_per_gas, extra_data, miner, difficulty, total_difficulty,
+            size, transaction_count, transactions as "transactions: Json<Vec<Transaction>>"
+        FROM blocks
+        WHERE hash = $1
+        "#,
+        block_hash,
+    )
+    .fetch_optional(pool)
+    .await;
     
     match result {
         Ok(row) => {
@@ -240,8 +378,117 @@ pub async fn get_block_by_hash(pool: &PgPool, block_hash: &str) -> Result<Option
     }
 }
 
-// Helper struct for database queries
-#[derive(sqlx::FromRow)]
+/// Find ranges of block numbers missing between `from` and `to` (inclusive)
+#[instrument(skip(pool))]
+pub async fn find_missing_block_ranges(pool: &PgPool, from: u64, to: u64) -> Result<Vec<(u64, u64)>> {
+    debug!("Scanning for missing blocks between {} and {}", from, to);
+    
+    // Compare each block with the next stored one; any jump larger than 1 is a gap.
+    // The bounds are included as sentinels so gaps at either end of the range are found too.
+    let result = sqlx::query!(
+        r#"
+        SELECT number + 1 AS "gap_start!", next_number - 1 AS "gap_end!"
+        FROM (
+            SELECT number, LEAD(number) OVER (ORDER BY number) AS next_number
+            FROM (
+                SELECT number FROM blocks WHERE number BETWEEN $1 AND $2
+                UNION SELECT $1 - 1
+                UNION SELECT $2 + 1
+            ) AS bounded
+        ) AS numbered
+        WHERE next_number > number + 1
+        ORDER BY number
+        "#,
+        from as i64,
+        to as i64,
+    )
+    .fetch_all(pool)
+    .await;
+    
+    match result {
+        Ok(rows) => {
+            let ranges = rows.into_iter()
+                .map(|r| (r.gap_start as u64, r.gap_end as u64))
+                .collect::<Vec<_>>();
+            debug!("Found {} missing block ranges", ranges.len());
+            Ok(ranges)
+        },
+        Err(e) => {
+            error!("Failed to scan for missing blocks: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Find blocks between `from` and `to` whose parent_hash doesn't match the stored parent's hash
+#[instrument(skip(pool))]
+pub async fn find_parent_hash_mismatches(pool: &PgPool, from: u64, to: u64) -> Result<Vec<u64>> {
+    debug!("Scanning for parent hash mismatches between {} and {}", from, to);
+    
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT child.number
+        FROM blocks child
+        JOIN blocks parent ON parent.number = child.number - 1
+        WHERE child.number BETWEEN $1 AND $2
+          AND child.parent_hash <> parent.hash
+        ORDER BY child.number
+        "#,
+        from as i64,
+        to as i64,
+    )
+    .fetch_all(pool)
+    .await;
+    
+    match result {
+        Ok(numbers) => {
+            let numbers = numbers.into_iter().map(|n| n as u64).collect::<Vec<_>>();
+            debug!("Found {} parent hash mismatches", numbers.len());
+            Ok(numbers)
+        },
+        Err(e) => {
+            error!("Failed to scan for parent hash mismatches: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+#[instrument(skip(pool))]
+pub async fn get_earliest_block_number(pool: &PgPool) -> Result<Option<u64>> {
+    debug!("Fetching earliest block number from database");
+    
+    let result = sqlx::query_scalar!("SELECT MIN(number) as earliest FROM blocks")
+        .fetch_one(pool)
+        .await;
+    
+    match result {
+        Ok(earliest) => Ok(earliest.map(|n| n as u64)),
+        Err(e) => {
+            error!("Failed to get earliest block number: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Number and timestamp of the highest stored block
+#[instrument(skip(pool))]
+pub async fn get_latest_block_time(pool: &PgPool) -> Result<Option<(u64, u64)>> {
+    debug!("Fetching latest block number and timestamp from database");
+    
+    let result = sqlx::query!("SELECT number, timestamp FROM blocks ORDER BY number DESC LIMIT 1")
+        .fetch_optional(pool)
+        .await;
+    
+    match result {
+        Ok(row) => Ok(row.map(|r| (r.number as u64, r.timestamp as u64))),
+        Err(e) => {
+            error!("Failed to get latest block time: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+// Helper struct for database queries, checked against the schema by `query_as!`
 #[allow(dead_code)]
 struct BlockRow {
     number: i64,
@@ -256,31 +503,20 @@ struct BlockRow {
     base_fee_per_gas: Option<i64>,
     extra_data: String,
     miner: String,
-    difficulty: String,
+    difficulty: Option<String>,
     total_difficulty: Option<String>,
     size: i64,
     transaction_count: i64,
-    transactions: Json<Vec<crate::models::Transaction>>,
+    transactions: Json<Vec<Transaction>>,
 }
 
 #[allow(dead_code)]
 impl BlockRow {
     fn into_block(self) -> Result<Block> {
-        use ethers::types::U256;
-        
-        // Parse difficulty and total_difficulty from string back to U256
-        let difficulty = U256::from_dec_str(&self.difficulty)
-            .context("Failed to parse difficulty")?;
-        
-        let total_difficulty = if let Some(td) = self.total_difficulty {
-            if !td.is_empty() {
-                Some(U256::from_dec_str(&td).context("Failed to parse total_difficulty")?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        // Parse difficulty and total_difficulty from string back to U256; older rows store an
+        // empty string for a missing total difficulty
+        let difficulty = self.difficulty.as_deref().and_then(parse_difficulty);
+        let total_difficulty = self.total_difficulty.as_deref().and_then(parse_difficulty);
         
         Ok(Block {
             number: self.number as u64,
@@ -300,6 +536,7 @@ impl BlockRow {
             size: self.size as u64,
             transaction_count: self.transaction_count as u64,
             transactions: self.transactions.0,
+            receipts: Vec::new(),
         })
     }
 }
@@ -0,0 +1,78 @@
+use ethers::utils::keccak256;
+use sqlx::PgConnection;
+use tracing::debug;
+
+use crate::models::Block;
+
+/// Store the bytecode of contracts created in a block, deduplicated by code hash.
+/// Runs inside the receipts transaction so deployments never outlive their receipts.
+pub async fn save_deployed_code(conn: &mut PgConnection, block: &Block) -> Result<(), sqlx::Error> {
+    // Deployments left over from a block that was replaced by a reorg. Their code stays in
+    // contract_code, where it is harmless and likely shared with other deployments.
+    sqlx::query!(
+        "DELETE FROM deployed_code WHERE block_number = $1 AND block_hash <> $2",
+        block.number as i64,
+        &block.hash,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let mut addresses = Vec::new();
+    let mut transaction_hashes = Vec::new();
+    let mut code_hashes = Vec::new();
+    let mut codes = Vec::new();
+
+    for receipt in &block.receipts {
+        let (Some(address), Some(code)) = (receipt.created_contract(), &receipt.deployed_code) else {
+            continue;
+        };
+        let Ok(bytes) = hex::decode(code.trim_start_matches("0x")) else {
+            continue;
+        };
+
+        addresses.push(address.to_lowercase());
+        transaction_hashes.push(receipt.transaction_hash.clone());
+        code_hashes.push(format!("0x{}", hex::encode(keccak256(&bytes))));
+        codes.push(code.clone());
+    }
+
+    if addresses.is_empty() {
+        return Ok(());
+    }
+
+    debug!("Saving code of {} contracts created in block {}", addresses.len(), block.number);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO contract_code (code_hash, code)
+        SELECT code_hash, code
+        FROM UNNEST($1::text[], $2::text[]) AS c(code_hash, code)
+        ON CONFLICT (code_hash) DO NOTHING
+        "#,
+        &code_hashes,
+        &codes,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO deployed_code (address, block_number, block_hash, transaction_hash, code_hash)
+        SELECT address, $1, $2, transaction_hash, code_hash
+        FROM UNNEST($3::text[], $4::text[], $5::text[]) AS d(address, transaction_hash, code_hash)
+        ON CONFLICT (address, block_number) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash,
+            transaction_hash = EXCLUDED.transaction_hash,
+            code_hash = EXCLUDED.code_hash
+        "#,
+        block.number as i64,
+        &block.hash,
+        &addresses,
+        &transaction_hashes,
+        &code_hashes,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
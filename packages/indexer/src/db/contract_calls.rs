@@ -0,0 +1,197 @@
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+use tracing::{debug, error, instrument};
+
+/// Width of the `contract_calls` buckets; leaderboard windows are accurate to within one bucket
+pub const BUCKET_SECS: i64 = 300;
+
+/// Leaderboard windows kept in `top_contracts`, with their length in seconds
+pub const LEADERBOARD_PERIODS: &[(&str, i64)] = &[("1h", 3600), ("24h", 86400), ("7d", 604800)];
+
+fn periods() -> (Vec<String>, Vec<i64>) {
+    LEADERBOARD_PERIODS.iter().map(|(period, seconds)| (period.to_string(), *seconds)).unzip()
+}
+
+/// Longest leaderboard window; older buckets are pruned
+fn retention_secs() -> i64 {
+    LEADERBOARD_PERIODS.iter().map(|(_, seconds)| *seconds).max().unwrap_or_default()
+}
+
+/// Count the stored block's contract calls (transactions with a recipient and calldata) in
+/// `contract_calls`, with the gas used from the block's receipts (0 without receipts).
+/// Reads the block row, so it runs after the block is written.
+pub async fn add_block_contract_calls(
+    conn: &mut PgConnection,
+    block_number: u64,
+    receipt_hashes: &[String],
+    receipt_gas_used: &[Option<i64>],
+) -> Result<(), sqlx::Error> {
+    debug!("Recording contract calls for block {}", block_number);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO contract_calls (address, bucket_start, tx_count, gas_used)
+        SELECT t.address, t.timestamp / $2 * $2, COUNT(*), COALESCE(SUM(r.gas_used), 0)::bigint
+        FROM (
+            SELECT DISTINCT tx->>'hash' AS hash, lower(tx->>'to') AS address, b.timestamp
+            FROM blocks b
+            CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+            WHERE b.number = $1 AND tx->>'to' IS NOT NULL AND COALESCE(tx->>'input', '0x') <> '0x'
+        ) t
+        LEFT JOIN UNNEST($3::text[], $4::bigint[]) AS r(hash, gas_used) ON r.hash = t.hash
+        GROUP BY 1, 2
+        ON CONFLICT (address, bucket_start) DO UPDATE SET
+            tx_count = contract_calls.tx_count + EXCLUDED.tx_count,
+            gas_used = contract_calls.gas_used + EXCLUDED.gas_used
+        "#,
+        block_number as i64,
+        BUCKET_SECS,
+        receipt_hashes,
+        receipt_gas_used as &[Option<i64>],
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Undo `add_block_contract_calls` for a stored block that is about to be replaced by a reorg,
+/// taking gas from the receipts stored for it
+pub async fn remove_block_contract_calls(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Removing contract calls of replaced block {}", block_number);
+
+    sqlx::query!(
+        r#"
+        UPDATE contract_calls AS c
+        SET tx_count = GREATEST(c.tx_count - d.tx_count, 0),
+            gas_used = GREATEST(c.gas_used - d.gas_used, 0)
+        FROM (
+            SELECT t.address, t.timestamp / $2 * $2 AS bucket_start,
+                COUNT(*) AS tx_count, COALESCE(SUM(r.gas_used), 0)::bigint AS gas_used
+            FROM (
+                SELECT DISTINCT tx->>'hash' AS hash, lower(tx->>'to') AS address, b.timestamp, b.hash AS block_hash
+                FROM blocks b
+                CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+                WHERE b.number = $1 AND tx->>'to' IS NOT NULL AND COALESCE(tx->>'input', '0x') <> '0x'
+            ) t
+            LEFT JOIN receipts r ON r.transaction_hash = t.hash AND r.block_hash = t.block_hash
+            GROUP BY 1, 2
+        ) d
+        WHERE c.address = d.address AND c.bucket_start = d.bucket_start
+        "#,
+        block_number as i64,
+        BUCKET_SECS,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Recompute `top_contracts` from the buckets, keeping the `size` highest contracts by
+/// transaction count and by gas used in each window, and prune buckets no window reaches.
+/// Windows end at the latest stored block. Returns the number of leaderboard rows.
+#[instrument(skip(pool))]
+pub async fn refresh_top_contracts(pool: &PgPool, size: u32) -> Result<u64> {
+    debug!("Refreshing top {} contracts", size);
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!("DELETE FROM top_contracts")
+            .execute(&mut *tx)
+            .await?;
+
+        let (periods, seconds) = periods();
+        let inserted = sqlx::query!(
+            r#"
+            WITH head AS (
+                SELECT MAX(timestamp) AS timestamp FROM blocks
+            ),
+            totals AS (
+                SELECT p.period, c.address, SUM(c.tx_count)::bigint AS tx_count, SUM(c.gas_used)::bigint AS gas_used
+                FROM UNNEST($1::text[], $2::bigint[]) AS p(period, seconds)
+                CROSS JOIN head
+                JOIN contract_calls c ON c.bucket_start + $3 > head.timestamp - p.seconds
+                GROUP BY p.period, c.address
+            ),
+            ranked AS (
+                SELECT period, address, tx_count, gas_used,
+                    ROW_NUMBER() OVER (PARTITION BY period ORDER BY tx_count DESC, address) AS tx_rank,
+                    ROW_NUMBER() OVER (PARTITION BY period ORDER BY gas_used DESC, address) AS gas_rank
+                FROM totals
+                WHERE tx_count > 0
+            )
+            INSERT INTO top_contracts (period, address, tx_count, gas_used, tx_rank, gas_rank, updated_at)
+            SELECT period, address, tx_count, gas_used, tx_rank, gas_rank, CURRENT_TIMESTAMP
+            FROM ranked
+            WHERE tx_rank <= $4 OR gas_rank <= $4
+            "#,
+            &periods,
+            &seconds,
+            BUCKET_SECS,
+            size as i64,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM contract_calls
+            WHERE bucket_start + $1 <= (SELECT MAX(timestamp) FROM blocks) - $2
+            "#,
+            BUCKET_SECS,
+            retention_secs(),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(inserted.rows_affected())
+    }
+    .await;
+
+    match result {
+        Ok(rows) => {
+            debug!("Refreshed {} top contract rows", rows);
+            Ok(rows)
+        }
+        Err(e) => {
+            error!("Failed to refresh top contracts: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Recompute the buckets from the stored blocks in the longest window and their receipts,
+/// returning the number of buckets (used by the `db` binary)
+#[allow(dead_code)]
+pub async fn rebuild_contract_calls(conn: &mut PgConnection) -> Result<u64, sqlx::Error> {
+    debug!("Rebuilding contract calls");
+
+    sqlx::query!("TRUNCATE contract_calls")
+        .execute(&mut *conn)
+        .await?;
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO contract_calls (address, bucket_start, tx_count, gas_used)
+        SELECT t.address, t.timestamp / $1 * $1, COUNT(*), COALESCE(SUM(r.gas_used), 0)::bigint
+        FROM (
+            SELECT DISTINCT b.number, tx->>'hash' AS hash, lower(tx->>'to') AS address, b.timestamp, b.hash AS block_hash
+            FROM blocks b
+            CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+            WHERE b.timestamp + $1 > (SELECT MAX(timestamp) FROM blocks) - $2
+                AND tx->>'to' IS NOT NULL AND COALESCE(tx->>'input', '0x') <> '0x'
+        ) t
+        LEFT JOIN receipts r ON r.transaction_hash = t.hash AND r.block_hash = t.block_hash
+        GROUP BY 1, 2
+        "#,
+        BUCKET_SECS,
+        retention_secs(),
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,163 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::{debug, error, info, instrument};
+
+/// Counter of block writes skipped as duplicates, by reason (`recent` or `claimed`)
+pub const DEDUPLICATED_BLOCKS_METRIC: &str = "indexer_blocks_deduplicated_total";
+
+/// Claims made between two prunes of expired claims
+const CLAIMS_PER_PRUNE: u64 = 1000;
+
+/// Skips writes of blocks that were already persisted, e.g. when the historic and live
+/// sync both fetch the head of the backfill.
+///
+/// Each block processor remembers the blocks it persisted in a cache shared through the
+/// database handle. With claims enabled, instances sharing a database also claim each
+/// block in `block_claims` before writing it, and skip blocks claimed by another instance
+/// within the claim TTL. A block whose hash changed (a reorg) is always written.
+#[derive(Debug)]
+pub struct BlockDedup {
+    /// Recently persisted blocks, at most `capacity`
+    recent: Mutex<RecentBlocks>,
+    capacity: usize,
+    claims: Option<ClaimSettings>,
+}
+
+#[derive(Debug, Default)]
+struct RecentBlocks {
+    /// Hash persisted for each block number
+    hashes: HashMap<u64, String>,
+    /// Block numbers in the order they were first persisted, oldest first
+    order: VecDeque<u64>,
+}
+
+#[derive(Debug)]
+struct ClaimSettings {
+    /// Identifies this instance in `block_claims`
+    instance_id: String,
+    /// Seconds after which another instance's claim can be taken over
+    ttl_secs: u64,
+    claims_since_prune: AtomicU64,
+}
+
+impl BlockDedup {
+    /// Remember up to `capacity` persisted blocks (0 only deduplicates through claims)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            recent: Mutex::new(RecentBlocks::default()),
+            capacity,
+            claims: None,
+        }
+    }
+
+    /// Claim blocks in `block_claims` before writing them, as `instance_id`
+    pub fn with_claims(mut self, instance_id: String, ttl_secs: u64) -> Self {
+        info!("Claiming blocks as instance {} (claims expire after {}s)", instance_id, ttl_secs);
+        self.claims = Some(ClaimSettings {
+            instance_id,
+            ttl_secs,
+            claims_since_prune: AtomicU64::new(0),
+        });
+        self
+    }
+
+    /// Whether this block, with this hash, was persisted recently by this instance
+    pub fn is_recent(&self, number: u64, hash: &str) -> bool {
+        self.recent.lock().unwrap().hashes.get(&number).is_some_and(|known| known == hash)
+    }
+
+    /// Remember a persisted block, forgetting the oldest one beyond the capacity
+    pub fn remember(&self, number: u64, hash: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.hashes.insert(number, hash.to_string()).is_none() {
+            recent.order.push_back(number);
+        }
+        while recent.order.len() > self.capacity {
+            if let Some(oldest) = recent.order.pop_front() {
+                recent.hashes.remove(&oldest);
+            }
+        }
+    }
+
+    /// Claim a block before writing it. Returns false if another instance claimed it
+    /// within the TTL, in which case the write should be skipped; always true without claims.
+    pub async fn claim(&self, pool: &PgPool, number: u64, hash: &str) -> Result<bool> {
+        let Some(claims) = &self.claims else {
+            return Ok(true);
+        };
+
+        let claimed = claim_block(pool, number, hash, &claims.instance_id, claims.ttl_secs).await?;
+
+        if claims.claims_since_prune.fetch_add(1, Ordering::Relaxed) + 1 >= CLAIMS_PER_PRUNE {
+            claims.claims_since_prune.store(0, Ordering::Relaxed);
+            prune_block_claims(pool, claims.ttl_secs).await?;
+        }
+
+        Ok(claimed)
+    }
+}
+
+/// Claim a block for `instance_id`, taking over claims of other instances older than
+/// `ttl_secs`. Returns whether the claim is now held by `instance_id`.
+#[instrument(skip(pool))]
+async fn claim_block(pool: &PgPool, number: u64, hash: &str, instance_id: &str, ttl_secs: u64) -> Result<bool> {
+    debug!("Claiming block {} ({})", number, hash);
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO block_claims (block_number, block_hash, instance_id, claimed_at)
+        VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+        ON CONFLICT (block_number, block_hash) DO UPDATE SET
+            instance_id = EXCLUDED.instance_id,
+            claimed_at = EXCLUDED.claimed_at
+        WHERE block_claims.instance_id = EXCLUDED.instance_id
+            OR block_claims.claimed_at < CURRENT_TIMESTAMP - make_interval(secs => $4)
+        "#,
+        number as i64,
+        hash,
+        instance_id,
+        ttl_secs as f64,
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(result) => Ok(result.rows_affected() > 0),
+        Err(e) => {
+            error!("Failed to claim block {}: {}", number, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Delete claims older than `ttl_secs`, which any instance may take over anyway
+#[instrument(skip(pool))]
+async fn prune_block_claims(pool: &PgPool, ttl_secs: u64) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM block_claims
+        WHERE claimed_at < CURRENT_TIMESTAMP - make_interval(secs => $1)
+        "#,
+        ttl_secs as f64,
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(result) => {
+            debug!("Pruned {} expired block claims", result.rows_affected());
+            Ok(result.rows_affected())
+        }
+        Err(e) => {
+            error!("Failed to prune block claims: {}", e);
+            Err(e.into())
+        }
+    }
+}
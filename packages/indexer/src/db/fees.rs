@@ -0,0 +1,49 @@
+use sqlx::PgConnection;
+use tracing::debug;
+
+use crate::models::Block;
+
+/// Compute the block's fee statistics from its stored receipts: burnt fees (base fee times
+/// gas used), priority fees (what each transaction paid above the base fee) and the spread
+/// of effective gas prices. Runs after the receipts are written, in the same transaction.
+pub async fn save_block_fees(conn: &mut PgConnection, block: &Block) -> Result<(), sqlx::Error> {
+    debug!("Saving fee statistics for block {}", block.number);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO block_fees (
+            block_number, block_hash, receipt_count, burnt_fees, priority_fees,
+            min_effective_gas_price, median_effective_gas_price, avg_effective_gas_price,
+            max_effective_gas_price, updated_at
+        )
+        SELECT b.number, b.hash, COUNT(r.transaction_hash),
+            COALESCE(b.base_fee_per_gas, 0)::numeric * b.gas_used,
+            COALESCE(SUM((r.effective_gas_price - COALESCE(b.base_fee_per_gas, 0))::numeric * r.gas_used), 0),
+            MIN(r.effective_gas_price),
+            PERCENTILE_DISC(0.5) WITHIN GROUP (ORDER BY r.effective_gas_price),
+            AVG(r.effective_gas_price)::float8,
+            MAX(r.effective_gas_price),
+            CURRENT_TIMESTAMP
+        FROM blocks b
+        LEFT JOIN receipts r ON r.block_number = b.number AND r.block_hash = b.hash
+        WHERE b.number = $1 AND b.hash = $2
+        GROUP BY b.number, b.hash, b.base_fee_per_gas, b.gas_used
+        ON CONFLICT (block_number) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash,
+            receipt_count = EXCLUDED.receipt_count,
+            burnt_fees = EXCLUDED.burnt_fees,
+            priority_fees = EXCLUDED.priority_fees,
+            min_effective_gas_price = EXCLUDED.min_effective_gas_price,
+            median_effective_gas_price = EXCLUDED.median_effective_gas_price,
+            avg_effective_gas_price = EXCLUDED.avg_effective_gas_price,
+            max_effective_gas_price = EXCLUDED.max_effective_gas_price,
+            updated_at = EXCLUDED.updated_at
+        "#,
+        block.number as i64,
+        &block.hash,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Rollup periods and their length in seconds; buckets start at multiples of the length (UTC)
+pub const ROLLUP_PERIODS: &[(&str, i64)] = &[("hour", 3600), ("day", 86400)];
+
+/// When the gas rollups were last refreshed, None if they were never built
+#[instrument(skip(pool))]
+pub async fn get_gas_rollups_refreshed_at(pool: &PgPool) -> Result<Option<DateTime<Utc>>> {
+    debug!("Fetching last gas rollup refresh time");
+
+    let result = sqlx::query_scalar!("SELECT MAX(updated_at) FROM gas_rollups")
+        .fetch_one(pool)
+        .await;
+
+    match result {
+        Ok(refreshed_at) => Ok(refreshed_at),
+        Err(e) => {
+            error!("Failed to get gas rollup refresh time: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Recompute every rollup bucket containing a block saved (or replaced by a reorg) after
+/// `since`, or all of them when None. Returns the number of buckets written.
+#[instrument(skip(pool))]
+pub async fn refresh_gas_rollups(pool: &PgPool, since: Option<DateTime<Utc>>) -> Result<u64> {
+    debug!("Refreshing gas rollups for blocks updated since {:?}", since);
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let mut buckets = 0;
+
+        for (period, seconds) in ROLLUP_PERIODS {
+            // Whole buckets are re-aggregated, so blocks saved earlier in a touched bucket count too
+            let written = sqlx::query!(
+                r#"
+                INSERT INTO gas_rollups (
+                    period, bucket_start, block_count, gas_used, gas_limit, avg_utilization,
+                    avg_base_fee, min_base_fee, max_base_fee, first_block, last_block,
+                    fee_block_count, burnt_fees, priority_fees, avg_effective_gas_price, updated_at
+                )
+                SELECT $1, t.bucket_start, COUNT(*), SUM(b.gas_used)::bigint, SUM(b.gas_limit)::bigint,
+                    AVG(b.gas_used::float8 / NULLIF(b.gas_limit, 0)),
+                    AVG(b.base_fee_per_gas)::float8, MIN(b.base_fee_per_gas), MAX(b.base_fee_per_gas),
+                    MIN(b.number), MAX(b.number),
+                    NULLIF(COUNT(f.block_number), 0), SUM(f.burnt_fees), SUM(f.priority_fees),
+                    SUM(f.avg_effective_gas_price * f.receipt_count) / NULLIF(SUM(f.receipt_count), 0),
+                    CURRENT_TIMESTAMP
+                FROM (
+                    SELECT DISTINCT timestamp / $2 * $2 AS bucket_start
+                    FROM blocks
+                    WHERE $3::timestamptz IS NULL OR updated_at > $3
+                ) t
+                JOIN blocks b ON b.timestamp >= t.bucket_start AND b.timestamp < t.bucket_start + $2
+                -- Statistics left over from a block replaced by a reorg are skipped
+                LEFT JOIN block_fees f ON f.block_number = b.number AND f.block_hash = b.hash
+                GROUP BY t.bucket_start
+                ON CONFLICT (period, bucket_start) DO UPDATE SET
+                    block_count = EXCLUDED.block_count,
+                    gas_used = EXCLUDED.gas_used,
+                    gas_limit = EXCLUDED.gas_limit,
+                    avg_utilization = EXCLUDED.avg_utilization,
+                    avg_base_fee = EXCLUDED.avg_base_fee,
+                    min_base_fee = EXCLUDED.min_base_fee,
+                    max_base_fee = EXCLUDED.max_base_fee,
+                    first_block = EXCLUDED.first_block,
+                    last_block = EXCLUDED.last_block,
+                    fee_block_count = EXCLUDED.fee_block_count,
+                    burnt_fees = EXCLUDED.burnt_fees,
+                    priority_fees = EXCLUDED.priority_fees,
+                    avg_effective_gas_price = EXCLUDED.avg_effective_gas_price,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+                *period,
+                *seconds,
+                since,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            buckets += written.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(buckets)
+    }
+    .await;
+
+    match result {
+        Ok(buckets) => {
+            debug!("Refreshed {} gas rollup buckets", buckets);
+            Ok(buckets)
+        }
+        Err(e) => {
+            error!("Failed to refresh gas rollups: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Replace the block ranges recorded as incomplete for `reason` (e.g. `historic_sync`),
+/// so aggregates over those ranges can be flagged until ingestion catches up.
+/// An empty list clears them.
+#[instrument(skip(pool, ranges), fields(range_count = ranges.len()))]
+pub async fn replace_incomplete_ranges(pool: &PgPool, reason: &str, ranges: &[(u64, u64)]) -> Result<()> {
+    debug!("Recording {} incomplete ranges for {}", ranges.len(), reason);
+
+    let start_blocks = ranges.iter().map(|(start, _)| *start as i64).collect::<Vec<_>>();
+    let end_blocks = ranges.iter().map(|(_, end)| *end as i64).collect::<Vec<_>>();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!("DELETE FROM incomplete_ranges WHERE reason = $1", reason)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO incomplete_ranges (start_block, end_block, reason)
+            SELECT start_block, end_block, $1
+            FROM UNNEST($2::bigint[], $3::bigint[]) AS r(start_block, end_block)
+            "#,
+            reason,
+            &start_blocks,
+            &end_blocks,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to record incomplete ranges for {}: {}", reason, e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Histogram tracking the duration of every instrumented query, labelled by query name
+pub const QUERY_DURATION_METRIC: &str = "indexer_db_query_duration_seconds";
+
+/// Counter incremented each time a query exceeds the slow query threshold
+pub const SLOW_QUERY_METRIC: &str = "indexer_db_slow_queries_total";
+
+/// Default threshold above which a query is reported as slow (ms)
+pub const DEFAULT_SLOW_QUERY_THRESHOLD_MS: u64 = 500;
+
+/// A named query parameter as it should appear in slow query reports
+pub type QueryParam = (&'static str, String);
+
+/// Records per-query durations and reports queries that exceed a threshold
+#[derive(Debug, Clone, Copy)]
+pub struct QueryInstrument {
+    /// Queries taking longer than this are logged as slow
+    slow_query_threshold: Duration,
+}
+
+impl QueryInstrument {
+    pub fn new(slow_query_threshold_ms: u64) -> Self {
+        Self {
+            slow_query_threshold: Duration::from_millis(slow_query_threshold_ms),
+        }
+    }
+
+    /// Run a query future, recording its duration under `name`.
+    ///
+    /// `params` is only evaluated when the query turns out to be slow, so callers
+    /// can build the parameter list lazily without paying for it on the hot path.
+    pub async fn timed<F, T, P>(&self, name: &'static str, params: P, query: F) -> T
+    where
+        F: Future<Output = T>,
+        P: FnOnce() -> Vec<QueryParam>,
+    {
+        let started = Instant::now();
+        let result = query.await;
+        let elapsed = started.elapsed();
+
+        metrics::histogram!(QUERY_DURATION_METRIC, elapsed.as_secs_f64(), "query" => name);
+
+        if elapsed >= self.slow_query_threshold {
+            metrics::increment_counter!(SLOW_QUERY_METRIC, "query" => name);
+
+            let params = params()
+                .into_iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+            warn!(
+                "Slow query '{}' took {}ms (threshold {}ms) with params [{}]",
+                name,
+                elapsed.as_millis(),
+                self.slow_query_threshold.as_millis(),
+                params
+            );
+        } else {
+            debug!("Query '{}' took {}ms", name, elapsed.as_millis());
+        }
+
+        result
+    }
+}
+
+impl Default for QueryInstrument {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_QUERY_THRESHOLD_MS)
+    }
+}
+
+/// Redact a string parameter for logging, keeping only a short prefix and its length
+/// so that hashes and addresses remain recognisable without being written out in full
+pub fn redact(value: &str) -> String {
+    const VISIBLE_PREFIX: usize = 6;
+
+    let length = value.chars().count();
+    if length <= VISIBLE_PREFIX {
+        return "<redacted>".to_string();
+    }
+
+    let prefix: String = value.chars().take(VISIBLE_PREFIX).collect();
+    format!("{}…<redacted {} chars>", prefix, length)
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use tracing::{debug, error, instrument};
+
+/// A notable ingestion event, kept in `ingest_journal` for debugging after logs have rotated
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    pub recorded_at: DateTime<Utc>,
+    /// Component that recorded the event, e.g. `live_sync` or `block_processor`
+    pub component: &'static str,
+    /// What happened, e.g. `ws_subscribed` or `block_persisted`
+    pub event: &'static str,
+    /// Block the event is about, if any
+    pub block_number: Option<u64>,
+    pub details: serde_json::Value,
+}
+
+impl JournalEntry {
+    pub fn new(component: &'static str, event: &'static str) -> Self {
+        Self {
+            recorded_at: Utc::now(),
+            component,
+            event,
+            block_number: None,
+            details: serde_json::Value::Object(Default::default()),
+        }
+    }
+
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block_number = Some(block_number);
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// A journal entry as stored (used by the `db` binary)
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct StoredJournalEntry {
+    pub id: i64,
+    pub recorded_at: DateTime<Utc>,
+    pub component: String,
+    pub event: String,
+    pub block_number: Option<i64>,
+    pub details: serde_json::Value,
+}
+
+/// Insert buffered journal entries in one statement, then drop the oldest entries beyond
+/// the `size` most recent ones. Returns the number of entries pruned.
+#[instrument(skip(pool, entries), fields(entries = entries.len()))]
+pub async fn save_journal_entries(pool: &PgPool, entries: &[JournalEntry], size: u64) -> Result<u64> {
+    debug!("Writing {} journal entries", entries.len());
+
+    let result = async {
+        let mut recorded_at = Vec::with_capacity(entries.len());
+        let mut components = Vec::with_capacity(entries.len());
+        let mut events = Vec::with_capacity(entries.len());
+        let mut block_numbers = Vec::with_capacity(entries.len());
+        let mut details = Vec::with_capacity(entries.len());
+        for entry in entries {
+            recorded_at.push(entry.recorded_at);
+            components.push(entry.component.to_string());
+            events.push(entry.event.to_string());
+            block_numbers.push(entry.block_number.map(|n| n as i64));
+            details.push(entry.details.clone());
+        }
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO ingest_journal (recorded_at, component, event, block_number, details)
+            SELECT * FROM UNNEST($1::timestamptz[], $2::text[], $3::text[], $4::bigint[], $5::jsonb[])
+            "#,
+            &recorded_at,
+            &components,
+            &events,
+            &block_numbers as &[Option<i64>],
+            &details,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Ids only grow, so the newest `size` entries are those within `size` of the highest
+        let pruned = sqlx::query!(
+            r#"
+            DELETE FROM ingest_journal
+            WHERE id <= (SELECT MAX(id) FROM ingest_journal) - $1
+            "#,
+            size as i64,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(pruned.rows_affected())
+    }
+    .await;
+
+    match result {
+        Ok(pruned) => {
+            debug!("Pruned {} journal entries", pruned);
+            Ok(pruned)
+        }
+        Err(e) => {
+            error!("Failed to write journal entries: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Journal entries about blocks within `radius` of `block_number`, along with the entries
+/// recorded between the first and last of those, in order (used by the `db` binary)
+#[allow(dead_code)]
+pub async fn get_journal_around(
+    conn: &mut PgConnection,
+    block_number: u64,
+    radius: u64,
+    limit: i64,
+) -> Result<Vec<StoredJournalEntry>, sqlx::Error> {
+    debug!("Fetching journal entries around block {}", block_number);
+
+    let rows = sqlx::query!(
+        r#"
+        WITH span AS (
+            SELECT MIN(id) AS first_id, MAX(id) AS last_id
+            FROM ingest_journal
+            WHERE block_number BETWEEN $1 AND $2
+        )
+        SELECT j.id, j.recorded_at, j.component, j.event, j.block_number, j.details
+        FROM ingest_journal j, span
+        WHERE j.id BETWEEN span.first_id AND span.last_id
+            AND (j.block_number IS NULL OR j.block_number BETWEEN $1 AND $2)
+        ORDER BY j.id
+        LIMIT $3
+        "#,
+        block_number.saturating_sub(radius) as i64,
+        (block_number + radius) as i64,
+        limit,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| StoredJournalEntry {
+            id: row.id,
+            recorded_at: row.recorded_at,
+            component: row.component,
+            event: row.event,
+            block_number: row.block_number,
+            details: row.details,
+        })
+        .collect())
+}
+
+/// The most recent journal entries, oldest first (used by the `db` binary)
+#[allow(dead_code)]
+pub async fn get_recent_journal(conn: &mut PgConnection, limit: i64) -> Result<Vec<StoredJournalEntry>, sqlx::Error> {
+    debug!("Fetching the latest {} journal entries", limit);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, recorded_at, component, event, block_number, details
+        FROM ingest_journal
+        ORDER BY id DESC
+        LIMIT $1
+        "#,
+        limit,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .rev()
+        .map(|row| StoredJournalEntry {
+            id: row.id,
+            recorded_at: row.recorded_at,
+            component: row.component,
+            event: row.event,
+            block_number: row.block_number,
+            details: row.details,
+        })
+        .collect())
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Quote `name` as an SQL identifier, keeping a `schema.` prefix apart
+fn quote_identifier(name: &str) -> String {
+    name.split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Tables of the current schema with at least `min_changes` rows inserted, updated or
+/// deleted since their statistics were last gathered
+#[instrument(skip(pool))]
+pub async fn get_stale_tables(pool: &PgPool, min_changes: u64) -> Result<Vec<String>> {
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT relname::text AS "relname!"
+        FROM pg_stat_user_tables
+        WHERE schemaname = current_schema() AND n_mod_since_analyze >= $1
+        ORDER BY n_mod_since_analyze DESC
+        "#,
+        min_changes.max(1) as i64,
+    )
+    .fetch_all(pool)
+    .await;
+
+    match result {
+        Ok(tables) => Ok(tables),
+        Err(e) => {
+            error!("Failed to list tables with stale statistics: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Gather planner statistics for `table`
+#[instrument(skip(pool))]
+pub async fn analyze_table(pool: &PgPool, table: &str) -> Result<()> {
+    debug!("Analyzing {}", table);
+
+    let result = sqlx::query(&format!("ANALYZE {}", quote_identifier(table)))
+        .execute(pool)
+        .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to analyze {}: {}", table, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Refresh the materialized view `view`, without blocking readers when it is populated and
+/// has a unique index
+#[instrument(skip(pool))]
+pub async fn refresh_materialized_view(pool: &PgPool, view: &str) -> Result<()> {
+    let result: Result<(), sqlx::Error> = async {
+        let concurrently = sqlx::query_scalar!(
+            r#"
+            SELECT c.relispopulated AND EXISTS (
+                SELECT 1 FROM pg_index i WHERE i.indrelid = c.oid AND i.indisunique
+            ) AS "concurrently!"
+            FROM pg_class c
+            WHERE c.oid = $1::text::regclass AND c.relkind = 'm'
+            "#,
+            quote_identifier(view),
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| sqlx::Error::Protocol(format!("{} is not a materialized view", view)))?;
+
+        debug!("Refreshing materialized view {} (concurrently: {})", view, concurrently);
+        let statement = if concurrently {
+            format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {}", quote_identifier(view))
+        } else {
+            format!("REFRESH MATERIALIZED VIEW {}", quote_identifier(view))
+        };
+        sqlx::query(&statement).execute(pool).await?;
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            error!("Failed to refresh materialized view {}: {}", view, e);
+            Err(e.into())
+        }
+    }
+}
@@ -1,13 +1,69 @@
 use anyhow::Result;
 use sqlx::postgres::{PgPool, PgPoolOptions};
-use std::time::Duration;
-use tracing::info;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
+use crate::decoder;
+use crate::models::{ChainProfile, TagRules};
+use crate::utils::idle::IdleMode;
+use crate::utils::journal::IngestJournal;
+use crate::utils::slo::SloTracker;
+
+mod activity;
+mod audit;
 mod blocks;
+mod code;
+mod contract_calls;
+mod dedup;
+mod fees;
+mod gas_rollups;
+mod incomplete;
+mod instrument;
+mod journal;
+mod maintenance;
 mod migrations;
+mod names;
+mod receipts;
+mod retention;
+mod schema_check;
+mod status;
+mod sync_runs;
+mod sync_state;
+mod throttle;
+mod throughput;
+mod token_metadata;
+mod tokens;
+mod transactions;
+#[cfg(feature = "warehouse")]
+mod warehouse;
+mod watermarks;
+
+pub use audit::AuditEvent;
+pub use dedup::{BlockDedup, DEDUPLICATED_BLOCKS_METRIC};
+pub use instrument::QueryInstrument;
+#[cfg(feature = "prometheus")]
+pub use instrument::QUERY_DURATION_METRIC;
+pub use journal::JournalEntry;
+use instrument::redact;
+pub use status::{ChainHealth, ChainStatus};
+pub use sync_runs::SyncRun;
+pub use sync_state::SyncCheckpoint;
+pub use throttle::WriteThrottle;
+pub use token_metadata::TokenMetadata;
+pub use watermarks::Watermark;
 
 pub struct Database {
     pool: PgPool,
+    instrument: QueryInstrument,
+    tag_rules: TagRules,
+    chain_profile: ChainProfile,
+    write_throttle: Option<WriteThrottle>,
+    slo_tracker: Option<Arc<SloTracker>>,
+    idle_mode: Arc<IdleMode>,
+    journal: Option<Arc<IngestJournal>>,
+    block_dedup: Option<BlockDedup>,
+    dry_run: bool,
 }
 
 impl Database {
@@ -18,30 +74,542 @@ impl Database {
             .connect(database_url)
             .await?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            instrument: QueryInstrument::default(),
+            tag_rules: TagRules::default(),
+            chain_profile: ChainProfile::default(),
+            write_throttle: None,
+            slo_tracker: None,
+            idle_mode: Arc::default(),
+            journal: None,
+            block_dedup: None,
+            dry_run: false,
+        })
+    }
+
+    /// Configure the duration above which queries are reported as slow
+    pub fn with_slow_query_threshold(mut self, threshold_ms: u64) -> Self {
+        info!("Setting slow query threshold to {}ms", threshold_ms);
+        self.instrument = QueryInstrument::new(threshold_ms);
+        self
+    }
+
+    /// Configure the rules used to tag blocks as they are saved
+    pub fn with_tag_rules(mut self, tag_rules: TagRules) -> Self {
+        info!("Tagging blocks with {} rules", tag_rules.len());
+        self.tag_rules = tag_rules;
+        self
+    }
+
+    /// Configure what to expect from the indexed chain's nodes
+    pub fn with_chain_profile(mut self, chain_profile: ChainProfile) -> Self {
+        info!("Using {} chain profile", chain_profile);
+        self.chain_profile = chain_profile;
+        self
+    }
+
+    pub fn chain_profile(&self) -> ChainProfile {
+        self.chain_profile
+    }
+
+    /// Slow block writes down while the database is under pressure
+    pub fn with_write_throttle(mut self, write_throttle: WriteThrottle) -> Self {
+        info!("Enabling adaptive write throttle");
+        self.write_throttle = Some(write_throttle);
+        self
+    }
+
+    /// Report persisted blocks to the SLO tracker
+    pub fn with_slo_tracker(mut self, slo_tracker: Arc<SloTracker>) -> Self {
+        self.slo_tracker = Some(slo_tracker);
+        self
+    }
+
+    /// Track indexed transactions to let background loops slow down while the chain is quiet
+    pub fn with_idle_mode(mut self, idle_mode: IdleMode) -> Self {
+        self.idle_mode = Arc::new(idle_mode);
+        self
+    }
+
+    pub fn idle_mode(&self) -> &IdleMode {
+        &self.idle_mode
+    }
+
+    /// Record ingestion events in the journal buffer
+    pub fn with_journal(mut self, journal: Arc<IngestJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Buffer an ingestion event for the journal writer, if the journal is enabled
+    pub fn record_journal(&self, entry: JournalEntry) {
+        if let Some(journal) = &self.journal {
+            journal.record(entry);
+        }
+    }
+
+    /// Skip writes of blocks that were already persisted
+    pub fn with_block_dedup(mut self, block_dedup: BlockDedup) -> Self {
+        self.block_dedup = Some(block_dedup);
+        self
+    }
+
+    /// Log the blocks and sync records that would be written instead of writing them
+    pub fn with_dry_run(mut self) -> Self {
+        warn!("Dry run: nothing is written to the database");
+        self.dry_run = true;
+        self
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Why writing this block would be redundant, if it would: `recent` when this instance
+    /// persisted it recently, `claimed` when another instance claimed it
+    pub async fn check_duplicate_block(&self, block: &crate::models::Block) -> Result<Option<&'static str>> {
+        let Some(dedup) = &self.block_dedup else {
+            return Ok(None);
+        };
+
+        if dedup.is_recent(block.number, &block.hash) {
+            return Ok(Some("recent"));
+        }
+        if self.dry_run {
+            return Ok(None);
+        }
+
+        let claimed = self.instrument.timed(
+            "claim_block",
+            || vec![("number", block.number.to_string()), ("hash", redact(&block.hash))],
+            dedup.claim(&self.pool, block.number, &block.hash),
+        ).await?;
+        Ok((!claimed).then_some("claimed"))
+    }
+
+    /// Remember a block written in full, so it is skipped if it comes around again
+    pub fn remember_persisted_block(&self, block: &crate::models::Block) {
+        if let Some(dedup) = &self.block_dedup {
+            dedup.remember(block.number, &block.hash);
+        }
     }
 
     pub async fn migrate(self) -> Result<Self> {
+        if self.dry_run {
+            info!("Dry run: not running database migrations");
+            return self.skip_migrations().await;
+        }
+
         info!("Running database migrations");
         migrations::run_migrations(&self.pool).await?;
         Ok(self)
     }
 
+    /// Skip migrations, only checking that the schema has been applied separately
+    pub async fn skip_migrations(self) -> Result<Self> {
+        if !self.dry_run {
+            warn!("Skipping database migrations (SKIP_MIGRATIONS is set)");
+        }
+        migrations::check_schema_present(&self.pool).await?;
+        Ok(self)
+    }
+
+    /// Compare the live schema with the expected one, failing on drift in strict mode
+    pub async fn check_schema_drift(&self, strict: bool) -> Result<()> {
+        schema_check::check_schema_drift(&self.pool, strict).await
+    }
+
     pub async fn save_block(&self, block: &crate::models::Block) -> Result<()> {
-        blocks::save_block(&self.pool, block).await
+        let started = Instant::now();
+        let tags = self.tag_rules.evaluate(block);
+        if self.dry_run {
+            info!(
+                "Dry run: would persist block {} ({}) with {} transactions, {} receipts, {} token transfers, {} NFT transfers, tags {:?}",
+                block.number,
+                block.hash,
+                block.transactions.len(),
+                block.receipts.len(),
+                decoder::decode_erc20_transfers(block).len(),
+                decoder::decode_nft_transfers(block).len(),
+                tags,
+            );
+            return Ok(());
+        }
+        self.instrument.timed(
+            "save_block",
+            || vec![
+                ("number", block.number.to_string()),
+                ("hash", redact(&block.hash)),
+                ("transaction_count", block.transaction_count.to_string()),
+            ],
+            blocks::save_block(&self.pool, block, &tags, self.chain_profile),
+        ).await?;
+
+        if let Some(throttle) = &self.write_throttle {
+            throttle.record_write(started.elapsed());
+        }
+        self.record_journal(
+            JournalEntry::new("indexer", "block_persisted")
+                .with_block_number(block.number)
+                .with_details(serde_json::json!({
+                    "hash": block.hash,
+                    "transaction_count": block.transaction_count,
+                    "receipt_count": block.receipts.len(),
+                    "duration_ms": started.elapsed().as_millis() as u64,
+                }))
+        );
+        if let Some(slo_tracker) = &self.slo_tracker {
+            slo_tracker.record_persisted(block.number, self.chain_profile.timestamp_ms_or_secs(block.timestamp));
+        }
+        if block.transaction_count > 0 {
+            self.idle_mode.record_activity();
+        }
+        Ok(())
     }
 
+    /// Wait for the delay imposed by the write throttle, if any, before the next block write
+    pub async fn throttle_writes(&self) {
+        let Some(throttle) = &self.write_throttle else {
+            return;
+        };
+
+        if throttle.replication_lag_due() {
+            let lag = self.instrument.timed(
+                "get_replication_lag",
+                Vec::new,
+                throttle::get_replication_lag(&self.pool),
+            ).await;
+            // Without a lag reading, e.g. when lacking pg_monitor, only latency is used
+            throttle.record_replication_lag(lag.unwrap_or_default());
+        }
+
+        let delay = throttle.delay();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Store the receipts attached to a block, along with the token and NFT transfers decoded from their logs
     pub async fn get_latest_block_number(&self) -> Result<Option<u64>> {
-        blocks::get_latest_block_number(&self.pool).await
+        self.instrument.timed(
+            "get_latest_block_number",
+            Vec::new,
+            blocks::get_latest_block_number(&self.pool),
+        ).await
+    }
+    
+    pub async fn get_earliest_block_number(&self) -> Result<Option<u64>> {
+        self.instrument.timed(
+            "get_earliest_block_number",
+            Vec::new,
+            blocks::get_earliest_block_number(&self.pool),
+        ).await
+    }
+    
+    /// Number and timestamp of the highest stored block
+    pub async fn get_latest_block_time(&self) -> Result<Option<(u64, u64)>> {
+        self.instrument.timed(
+            "get_latest_block_time",
+            Vec::new,
+            blocks::get_latest_block_time(&self.pool),
+        ).await
+    }
+    
+    /// Find ranges of block numbers missing from the database between `from` and `to`
+    pub async fn find_missing_block_ranges(&self, from: u64, to: u64) -> Result<Vec<(u64, u64)>> {
+        self.instrument.timed(
+            "find_missing_block_ranges",
+            || vec![("from", from.to_string()), ("to", to.to_string())],
+            blocks::find_missing_block_ranges(&self.pool, from, to),
+        ).await
+    }
+    
+    /// Summarize a finished historic sync run in `sync_runs`
+    pub async fn save_sync_run(&self, run: &SyncRun) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.instrument.timed(
+            "save_sync_run",
+            || vec![("from", run.from_block.to_string()), ("to", run.to_block.to_string())],
+            sync_runs::save_sync_run(&self.pool, run),
+        ).await
+    }
+    
+    /// Persisted sync cursor, None before the first checkpoint
+    pub async fn get_sync_checkpoint(&self) -> Result<Option<SyncCheckpoint>> {
+        self.instrument.timed(
+            "get_sync_checkpoint",
+            Vec::new,
+            sync_state::get_sync_checkpoint(&self.pool),
+        ).await
+    }
+    
+    pub async fn save_sync_checkpoint(&self, checkpoint: &SyncCheckpoint) -> Result<()> {
+        self.instrument.timed(
+            "save_sync_checkpoint",
+            || vec![("contiguous_block", format!("{:?}", checkpoint.contiguous_block))],
+            sync_state::save_sync_checkpoint(&self.pool, checkpoint),
+        ).await
+    }
+    
+    pub async fn get_database_size(&self) -> Result<Option<i64>> {
+        self.instrument.timed(
+            "get_database_size",
+            Vec::new,
+            sync_runs::get_database_size(&self.pool),
+        ).await
+    }
+    
+    /// Record an operational event in the audit log. Best effort: a failure is logged, since
+    /// it shouldn't interrupt the work being audited.
+    pub async fn record_audit_event(&self, event: AuditEvent) {
+        if self.dry_run {
+            info!("Dry run: would record {} event from {}", event.action, event.source);
+            return;
+        }
+        let result = self.instrument.timed(
+            "save_audit_event",
+            || vec![("source", event.source.to_string()), ("action", event.action.to_string())],
+            audit::save_audit_event(&self.pool, &event),
+        ).await;
+
+        if let Err(e) = result {
+            warn!("Failed to record {} event in the audit log: {}", event.action, e);
+        }
+    }
+    
+    /// Addresses seen in transactions whose name is missing or older than `refresh_after_secs`
+    pub async fn get_addresses_to_resolve(&self, refresh_after_secs: i64, limit: i64) -> Result<Vec<String>> {
+        self.instrument.timed(
+            "get_addresses_to_resolve",
+            || vec![("limit", limit.to_string())],
+            names::get_addresses_to_resolve(&self.pool, refresh_after_secs, limit),
+        ).await
+    }
+    
+    /// Store names resolved for addresses, None for addresses without one
+    pub async fn save_address_names(&self, names: &[(String, Option<String>)]) -> Result<()> {
+        self.instrument.timed(
+            "save_address_names",
+            || vec![("name_count", names.len().to_string())],
+            names::save_address_names(&self.pool, names),
+        ).await
+    }
+    
+    /// When the gas rollups were last refreshed, None if they were never built
+    pub async fn get_gas_rollups_refreshed_at(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        self.instrument.timed(
+            "get_gas_rollups_refreshed_at",
+            Vec::new,
+            gas_rollups::get_gas_rollups_refreshed_at(&self.pool),
+        ).await
+    }
+    
+    /// Recompute the gas rollup buckets of blocks saved after `since` (all of them when None)
+    pub async fn refresh_gas_rollups(&self, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<u64> {
+        self.instrument.timed(
+            "refresh_gas_rollups",
+            || vec![("since", format!("{:?}", since))],
+            gas_rollups::refresh_gas_rollups(&self.pool, since),
+        ).await
+    }
+    
+    /// Rebuild the top contracts leaderboard, keeping `size` contracts per window and ranking
+    pub async fn refresh_top_contracts(&self, size: u32) -> Result<u64> {
+        self.instrument.timed(
+            "refresh_top_contracts",
+            || vec![("size", size.to_string())],
+            contract_calls::refresh_top_contracts(&self.pool, size),
+        ).await
+    }
+    
+    /// Write buffered journal entries, keeping the latest `size`; returns the number pruned
+    pub async fn save_journal_entries(&self, entries: &[JournalEntry], size: u64) -> Result<u64> {
+        self.instrument.timed(
+            "save_journal_entries",
+            || vec![("entries", entries.len().to_string())],
+            journal::save_journal_entries(&self.pool, entries, size),
+        ).await
+    }
+    
+    /// Tokens seen in transfers whose metadata hasn't been read yet, with their standard
+    pub async fn get_tokens_without_metadata(&self, limit: i64) -> Result<Vec<(String, String)>> {
+        self.instrument.timed(
+            "get_tokens_without_metadata",
+            || vec![("limit", limit.to_string())],
+            token_metadata::get_tokens_without_metadata(&self.pool, limit),
+        ).await
+    }
+    
+    /// Store the metadata read from a token contract
+    pub async fn save_token_metadata(&self, metadata: &TokenMetadata) -> Result<()> {
+        self.instrument.timed(
+            "save_token_metadata",
+            || vec![("address", metadata.address.clone())],
+            token_metadata::save_token_metadata(&self.pool, metadata),
+        ).await
+    }
+    
+    /// Replace the block ranges recorded as incomplete for `reason`; an empty list clears them
+    pub async fn replace_incomplete_ranges(&self, reason: &str, ranges: &[(u64, u64)]) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.instrument.timed(
+            "replace_incomplete_ranges",
+            || vec![("reason", reason.to_string()), ("range_count", ranges.len().to_string())],
+            incomplete::replace_incomplete_ranges(&self.pool, reason, ranges),
+        ).await
+    }
+    
+    /// Find stored blocks whose parent_hash doesn't match the stored parent block
+    pub async fn find_parent_hash_mismatches(&self, from: u64, to: u64) -> Result<Vec<u64>> {
+        self.instrument.timed(
+            "find_parent_hash_mismatches",
+            || vec![("from", from.to_string()), ("to", to.to_string())],
+            blocks::find_parent_hash_mismatches(&self.pool, from, to),
+        ).await
     }
     
     #[allow(dead_code)]
     pub async fn get_block_by_number(&self, block_number: u64) -> Result<Option<crate::models::Block>> {
-        blocks::get_block_by_number(&self.pool, block_number).await
+        self.instrument.timed(
+            "get_block_by_number",
+            || vec![("number", block_number.to_string())],
+            blocks::get_block_by_number(&self.pool, block_number),
+        ).await
     }
     
     #[allow(dead_code)]
     pub async fn get_block_by_hash(&self, block_hash: &str) -> Result<Option<crate::models::Block>> {
-        blocks::get_block_by_hash(&self.pool, block_hash).await
+        self.instrument.timed(
+            "get_block_by_hash",
+            || vec![("hash", redact(block_hash))],
+            blocks::get_block_by_hash(&self.pool, block_hash),
+        ).await
+    }
+
+    /// Record the latest block for a block tag such as `finalized` or `safe`
+    pub async fn save_watermark(&self, tag: &str, block_number: u64, block_hash: &str) -> Result<()> {
+        self.instrument.timed(
+            "save_watermark",
+            || vec![("tag", tag.to_string()), ("number", block_number.to_string())],
+            watermarks::save_watermark(&self.pool, tag, block_number, block_hash),
+        ).await
+    }
+    
+    #[allow(dead_code)]
+    pub async fn get_watermark(&self, tag: &str) -> Result<Option<Watermark>> {
+        self.instrument.timed(
+            "get_watermark",
+            || vec![("tag", tag.to_string())],
+            watermarks::get_watermark(&self.pool, tag),
+        ).await
+    }
+    
+    /// Stored blocks numbered `from` to `to` inclusive, in ascending order
+    #[cfg(feature = "warehouse")]
+    pub async fn get_blocks_range(&self, from: u64, to: u64) -> Result<Vec<crate::models::Block>> {
+        self.instrument.timed(
+            "get_blocks_range",
+            || vec![("from", from.to_string()), ("to", to.to_string())],
+            blocks::get_blocks_range(&self.pool, from, to),
+        ).await
+    }
+    
+    #[cfg(feature = "warehouse")]
+    pub async fn get_export_high_water_mark(&self, sink: &str) -> Result<Option<u64>> {
+        self.instrument.timed(
+            "get_export_high_water_mark",
+            || vec![("sink", sink.to_string())],
+            warehouse::get_export_high_water_mark(&self.pool, sink),
+        ).await
+    }
+    
+    /// Record that blocks up to `last_block` were shipped to `sink`
+    #[cfg(feature = "warehouse")]
+    pub async fn save_export_high_water_mark(&self, sink: &str, last_block: u64, blocks: u64, transactions: u64) -> Result<()> {
+        self.instrument.timed(
+            "save_export_high_water_mark",
+            || vec![("sink", sink.to_string()), ("last_block", last_block.to_string())],
+            warehouse::save_export_high_water_mark(&self.pool, sink, last_block, blocks, transactions),
+        ).await
+    }
+
+    /// Latest stored block produced before `before_timestamp` (in seconds)
+    pub async fn get_last_block_before(&self, before_timestamp: u64) -> Result<Option<u64>> {
+        self.instrument.timed(
+            "get_last_block_before",
+            || vec![("before_timestamp", before_timestamp.to_string())],
+            retention::get_last_block_before(&self.pool, before_timestamp),
+        ).await
+    }
+    
+    /// Lowest block whose transactions haven't been pruned
+    pub async fn get_oldest_detailed_block(&self) -> Result<Option<u64>> {
+        self.instrument.timed(
+            "get_oldest_detailed_block",
+            Vec::new,
+            retention::get_oldest_detailed_block(&self.pool),
+        ).await
+    }
+    
+    /// Drop the transactions, receipts and logs of blocks `from` to `to`, keeping the blocks
+    pub async fn prune_block_details(&self, from: u64, to: u64) -> Result<u64> {
+        self.instrument.timed(
+            "prune_block_details",
+            || vec![("from", from.to_string()), ("to", to.to_string())],
+            retention::prune_block_details(&self.pool, from, to),
+        ).await
+    }
+
+    /// Tables with at least `min_changes` rows changed since their last `ANALYZE`
+    pub async fn get_stale_tables(&self, min_changes: u64) -> Result<Vec<String>> {
+        self.instrument.timed(
+            "get_stale_tables",
+            || vec![("min_changes", min_changes.to_string())],
+            maintenance::get_stale_tables(&self.pool, min_changes),
+        ).await
+    }
+
+    /// Gather planner statistics for `table`
+    pub async fn analyze_table(&self, table: &str) -> Result<()> {
+        self.instrument.timed(
+            "analyze_table",
+            || vec![("table", table.to_string())],
+            maintenance::analyze_table(&self.pool, table),
+        ).await
+    }
+
+    /// Refresh the materialized view `view`
+    pub async fn refresh_materialized_view(&self, view: &str) -> Result<()> {
+        self.instrument.timed(
+            "refresh_materialized_view",
+            || vec![("view", view.to_string())],
+            maintenance::refresh_materialized_view(&self.pool, view),
+        ).await
+    }
+
+    /// Record the current chain status
+    pub async fn save_chain_status(&self, status: &ChainStatus) -> Result<()> {
+        self.instrument.timed(
+            "save_chain_status",
+            || vec![("status", status.health.to_string())],
+            status::save_chain_status(&self.pool, status),
+        ).await
+    }
+
+    /// Record the earliest block the RPC node can serve, None when it isn't limiting the backfill
+    pub async fn save_earliest_available_block(&self, block_number: Option<u64>) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.instrument.timed(
+            "save_earliest_available_block",
+            Vec::new,
+            status::save_earliest_available_block(&self.pool, block_number),
+        ).await
     }
 }
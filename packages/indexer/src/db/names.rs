@@ -0,0 +1,66 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Addresses seen in transactions that were never resolved, or were last resolved more than
+/// `refresh_after_secs` ago, never-resolved ones first
+#[instrument(skip(pool))]
+pub async fn get_addresses_to_resolve(pool: &PgPool, refresh_after_secs: i64, limit: i64) -> Result<Vec<String>> {
+    debug!("Fetching addresses to resolve names for");
+
+    let result = sqlx::query_scalar!(
+        r#"
+        SELECT a.address
+        FROM address_activity a
+        LEFT JOIN address_names n ON n.address = a.address
+        WHERE n.resolved_at IS NULL
+            OR n.resolved_at < CURRENT_TIMESTAMP - make_interval(secs => $1::bigint)
+        ORDER BY n.resolved_at NULLS FIRST
+        LIMIT $2
+        "#,
+        refresh_after_secs,
+        limit,
+    )
+    .fetch_all(pool)
+    .await;
+
+    match result {
+        Ok(addresses) => Ok(addresses),
+        Err(e) => {
+            error!("Failed to get addresses to resolve: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Store the names resolved for addresses, None for addresses without a verified name
+#[instrument(skip(pool, names), fields(name_count = names.len()))]
+pub async fn save_address_names(pool: &PgPool, names: &[(String, Option<String>)]) -> Result<()> {
+    debug!("Saving {} resolved names", names.len());
+
+    let addresses = names.iter().map(|(address, _)| address.clone()).collect::<Vec<_>>();
+    let resolved = names.iter().map(|(_, name)| name.clone()).collect::<Vec<_>>();
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO address_names (address, name, resolved_at)
+        SELECT address, name, CURRENT_TIMESTAMP
+        FROM UNNEST($1::text[], $2::text[]) AS n(address, name)
+        ON CONFLICT (address) DO UPDATE SET
+            name = EXCLUDED.name,
+            resolved_at = EXCLUDED.resolved_at
+        "#,
+        &addresses,
+        &resolved as &[Option<String>],
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save resolved names: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use sqlx::PgConnection;
+use tracing::debug;
+
+use crate::db::{code, fees, tokens};
+use crate::decoder;
+use crate::models::Block;
+
+/// Store a block's receipts and logs, with the token transfers, deployed code and fees
+/// derived from them. Runs inside the block's transaction, so a block is never stored
+/// without its receipts.
+pub async fn save_receipts(conn: &mut PgConnection, block: &Block) -> Result<(), sqlx::Error> {
+    debug!("Saving {} receipts for block {}", block.receipts.len(), block.number);
+
+    let mut transaction_hashes = Vec::with_capacity(block.receipts.len());
+    let mut transaction_indexes = Vec::with_capacity(block.receipts.len());
+    let mut from_addresses = Vec::with_capacity(block.receipts.len());
+    let mut to_addresses = Vec::with_capacity(block.receipts.len());
+    let mut statuses = Vec::with_capacity(block.receipts.len());
+    let mut gas_used = Vec::with_capacity(block.receipts.len());
+    let mut cumulative_gas_used = Vec::with_capacity(block.receipts.len());
+    let mut effective_gas_prices = Vec::with_capacity(block.receipts.len());
+    let mut contract_addresses = Vec::with_capacity(block.receipts.len());
+    let mut logs = Vec::with_capacity(block.receipts.len());
+
+    // Logs are also stored flattened, one row per log with its topics in separate columns
+    let mut log_transaction_hashes = Vec::new();
+    let mut log_transaction_indexes = Vec::new();
+    let mut log_indexes = Vec::new();
+    let mut log_addresses = Vec::new();
+    let mut log_topics: [Vec<Option<String>>; 4] = Default::default();
+    let mut log_data = Vec::new();
+
+    for (receipt, log_index, log) in block.indexed_logs() {
+        log_transaction_hashes.push(receipt.transaction_hash.clone());
+        log_transaction_indexes.push(receipt.transaction_index as i64);
+        log_indexes.push(log_index as i64);
+        log_addresses.push(log.address.to_lowercase());
+        for (i, topics) in log_topics.iter_mut().enumerate() {
+            topics.push(log.topics.get(i).cloned());
+        }
+        log_data.push(log.data.clone());
+    }
+
+    for receipt in &block.receipts {
+        transaction_hashes.push(receipt.transaction_hash.clone());
+        transaction_indexes.push(receipt.transaction_index as i64);
+        from_addresses.push(receipt.from.clone());
+        to_addresses.push(receipt.to.clone());
+        statuses.push(receipt.status.map(|status| status as i16));
+        gas_used.push(receipt.gas_used.map(|gas| gas as i64));
+        cumulative_gas_used.push(receipt.cumulative_gas_used as i64);
+        effective_gas_prices.push(receipt.effective_gas_price.map(|price| price as i64));
+        contract_addresses.push(receipt.contract_address.clone());
+        // Logs are plain strings and numbers, which always serialize
+        logs.push(serde_json::to_value(&receipt.logs).unwrap_or_default());
+    }
+
+    // Receipts and logs left over from a block that was replaced by a reorg
+    sqlx::query!(
+        "DELETE FROM receipts WHERE block_number = $1 AND block_hash <> $2",
+        block.number as i64,
+        &block.hash,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM logs WHERE block_number = $1 AND block_hash <> $2",
+        block.number as i64,
+        &block.hash,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO receipts (
+            transaction_hash, block_number, block_hash, transaction_index, from_address, to_address,
+            status, gas_used, cumulative_gas_used, effective_gas_price, contract_address, logs
+        )
+        SELECT transaction_hash, $1, $2, transaction_index, from_address, to_address,
+            status, gas_used, cumulative_gas_used, effective_gas_price, contract_address, logs
+        FROM UNNEST(
+            $3::text[], $4::bigint[], $5::text[], $6::text[], $7::smallint[],
+            $8::bigint[], $9::bigint[], $10::bigint[], $11::text[], $12::jsonb[]
+        ) AS r(
+            transaction_hash, transaction_index, from_address, to_address, status,
+            gas_used, cumulative_gas_used, effective_gas_price, contract_address, logs
+        )
+        ON CONFLICT (transaction_hash) DO UPDATE SET
+            block_number = EXCLUDED.block_number,
+            block_hash = EXCLUDED.block_hash,
+            transaction_index = EXCLUDED.transaction_index,
+            from_address = EXCLUDED.from_address,
+            to_address = EXCLUDED.to_address,
+            status = EXCLUDED.status,
+            gas_used = EXCLUDED.gas_used,
+            cumulative_gas_used = EXCLUDED.cumulative_gas_used,
+            effective_gas_price = EXCLUDED.effective_gas_price,
+            contract_address = EXCLUDED.contract_address,
+            logs = EXCLUDED.logs
+        "#,
+        block.number as i64,
+        &block.hash,
+        &transaction_hashes,
+        &transaction_indexes,
+        &from_addresses,
+        &to_addresses as &[Option<String>],
+        &statuses as &[Option<i16>],
+        &gas_used as &[Option<i64>],
+        &cumulative_gas_used,
+        &effective_gas_prices as &[Option<i64>],
+        &contract_addresses as &[Option<String>],
+        &logs,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let [topic0, topic1, topic2, topic3] = &log_topics;
+    sqlx::query!(
+        r#"
+        INSERT INTO logs (
+            block_number, log_index, block_hash, transaction_hash, transaction_index,
+            address, topic0, topic1, topic2, topic3, data
+        )
+        SELECT $1, log_index, $2, transaction_hash, transaction_index,
+            address, topic0, topic1, topic2, topic3, data
+        FROM UNNEST(
+            $3::bigint[], $4::text[], $5::bigint[], $6::text[],
+            $7::text[], $8::text[], $9::text[], $10::text[], $11::text[]
+        ) AS l(
+            log_index, transaction_hash, transaction_index, address,
+            topic0, topic1, topic2, topic3, data
+        )
+        ON CONFLICT (block_number, log_index) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash,
+            transaction_hash = EXCLUDED.transaction_hash,
+            transaction_index = EXCLUDED.transaction_index,
+            address = EXCLUDED.address,
+            topic0 = EXCLUDED.topic0,
+            topic1 = EXCLUDED.topic1,
+            topic2 = EXCLUDED.topic2,
+            topic3 = EXCLUDED.topic3,
+            data = EXCLUDED.data
+        "#,
+        block.number as i64,
+        &block.hash,
+        &log_indexes,
+        &log_transaction_hashes,
+        &log_transaction_indexes,
+        &log_addresses,
+        topic0 as &[Option<String>],
+        topic1 as &[Option<String>],
+        topic2 as &[Option<String>],
+        topic3 as &[Option<String>],
+        &log_data,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    tokens::save_token_transfers(&mut *conn, block, &decoder::decode_erc20_transfers(block)).await?;
+    tokens::save_nft_transfers(&mut *conn, block, &decoder::decode_nft_transfers(block)).await?;
+    code::save_deployed_code(&mut *conn, block).await?;
+    fees::save_block_fees(&mut *conn, block).await?;
+
+    debug!("Saved {} receipts and {} logs for block {}", block.receipts.len(), log_indexes.len(), block.number);
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Latest stored block produced before `before_timestamp` (in seconds)
+#[instrument(skip(pool))]
+pub async fn get_last_block_before(pool: &PgPool, before_timestamp: u64) -> Result<Option<u64>> {
+    let result = sqlx::query_scalar!(
+        "SELECT number FROM blocks WHERE timestamp < $1 ORDER BY timestamp DESC, number DESC LIMIT 1",
+        before_timestamp as i64,
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match result {
+        Ok(number) => Ok(number.map(|n| n as u64)),
+        Err(e) => {
+            error!("Failed to find the last block before {}: {}", before_timestamp, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Lowest block whose transactions are still stored
+#[instrument(skip(pool))]
+pub async fn get_oldest_detailed_block(pool: &PgPool) -> Result<Option<u64>> {
+    let result = sqlx::query_scalar!("SELECT MIN(block_number) FROM transactions")
+        .fetch_one(pool)
+        .await;
+
+    match result {
+        Ok(number) => Ok(number.map(|n| n as u64)),
+        Err(e) => {
+            error!("Failed to find the oldest block with transactions: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Drop the transactions, receipts and logs of blocks `from` to `to` inclusive, keeping the
+/// block rows with their counts. Returns the number of transactions removed.
+#[instrument(skip(pool))]
+pub async fn prune_block_details(pool: &PgPool, from: u64, to: u64) -> Result<u64> {
+    debug!("Pruning transactions, receipts and logs of blocks {} to {}", from, to);
+
+    let result: Result<u64, sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE blocks SET transactions = '[]' WHERE number BETWEEN $1 AND $2 AND transactions <> '[]'",
+            from as i64,
+            to as i64,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM logs WHERE block_number BETWEEN $1 AND $2",
+            from as i64,
+            to as i64,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM receipts WHERE block_number BETWEEN $1 AND $2",
+            from as i64,
+            to as i64,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let transactions = sqlx::query!(
+            "DELETE FROM transactions WHERE block_number BETWEEN $1 AND $2",
+            from as i64,
+            to as i64,
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(transactions)
+    }
+    .await;
+
+    match result {
+        Ok(transactions) => Ok(transactions),
+        Err(e) => {
+            error!("Failed to prune blocks {} to {}: {}", from, to, e);
+            Err(e.into())
+        }
+    }
+}
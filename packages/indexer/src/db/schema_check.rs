@@ -0,0 +1,228 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::fmt;
+use tracing::{error, info, instrument, warn};
+
+/// Columns of the `blocks` table as created by the migrations: (name, data type, nullable).
+/// The API reads this table directly, so its shape is checked column by column.
+const EXPECTED_BLOCK_COLUMNS: &[(&str, &str, bool)] = &[
+    ("number", "bigint", false),
+    ("hash", "text", false),
+    ("parent_hash", "text", false),
+    ("timestamp", "bigint", false),
+    ("transactions_root", "text", false),
+    ("state_root", "text", false),
+    ("receipts_root", "text", false),
+    ("gas_used", "bigint", false),
+    ("gas_limit", "bigint", false),
+    ("base_fee_per_gas", "bigint", true),
+    ("extra_data", "text", false),
+    ("miner", "text", false),
+    ("difficulty", "text", true),
+    ("total_difficulty", "text", true),
+    ("size", "bigint", false),
+    ("transaction_count", "bigint", false),
+    ("transactions", "jsonb", false),
+    ("tags", "ARRAY", false),
+    ("created_at", "timestamp with time zone", true),
+    ("updated_at", "timestamp with time zone", true),
+    ("timestamp_ms", "bigint", true),
+];
+
+/// Other tables the indexer writes to, checked for existence only
+const EXPECTED_TABLES: &[&str] = &["blocks", "chain_watermarks", "chain_status", "trusted_ranges", "receipts", "logs", "token_transfers", "nft_transfers", "address_activity", "incomplete_ranges", "contract_code", "deployed_code", "audit_log", "tokens", "address_names", "gas_rollups", "throughput_rollups", "contract_calls", "top_contracts", "block_fees", "ingest_journal", "block_claims", "sync_runs", "warehouse_exports", "sync_state", "transactions", "schema_meta"];
+
+/// Indexes created by the migrations: (table, index name)
+const EXPECTED_INDEXES: &[(&str, &str)] = &[
+    ("blocks", "idx_blocks_parent_hash"),
+    ("blocks", "idx_blocks_timestamp"),
+    ("blocks", "idx_blocks_number_desc"),
+    ("blocks", "idx_blocks_tags"),
+    ("blocks", "idx_blocks_updated_at"),
+    ("receipts", "idx_receipts_block_number"),
+    ("logs", "idx_logs_address"),
+    ("logs", "idx_logs_topic0"),
+    ("token_transfers", "idx_token_transfers_token"),
+    ("token_transfers", "idx_token_transfers_from"),
+    ("token_transfers", "idx_token_transfers_to"),
+    ("nft_transfers", "idx_nft_transfers_token"),
+    ("nft_transfers", "idx_nft_transfers_from"),
+    ("nft_transfers", "idx_nft_transfers_to"),
+    ("deployed_code", "idx_deployed_code_code_hash"),
+    ("audit_log", "idx_audit_log_action"),
+    ("tokens", "idx_tokens_pending"),
+    ("contract_calls", "idx_contract_calls_bucket_start"),
+    ("ingest_journal", "idx_ingest_journal_block_number"),
+    ("block_claims", "idx_block_claims_claimed_at"),
+];
+
+/// A difference between the live schema and what the indexer expects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDrift {
+    MissingTable(String),
+    MissingColumn { table: String, column: String },
+    TypeMismatch { table: String, column: String, expected: String, actual: String },
+    NullabilityMismatch { table: String, column: String, expected_nullable: bool },
+    UnexpectedColumn { table: String, column: String },
+    MissingIndex { table: String, index: String },
+}
+
+impl SchemaDrift {
+    /// Whether this drift can break the indexer or the API.
+    /// Extra columns are reported but are harmless to existing queries.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, SchemaDrift::UnexpectedColumn { .. })
+    }
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaDrift::MissingTable(table) => write!(f, "- table {} is missing", table),
+            SchemaDrift::MissingColumn { table, column } => {
+                write!(f, "- {}.{} is missing", table, column)
+            }
+            SchemaDrift::TypeMismatch { table, column, expected, actual } => {
+                write!(f, "~ {}.{} has type {} (expected {})", table, column, actual, expected)
+            }
+            SchemaDrift::NullabilityMismatch { table, column, expected_nullable } => {
+                let expected = if *expected_nullable { "NULL" } else { "NOT NULL" };
+                write!(f, "~ {}.{} nullability differs (expected {})", table, column, expected)
+            }
+            SchemaDrift::UnexpectedColumn { table, column } => {
+                write!(f, "+ {}.{} is not known to the indexer", table, column)
+            }
+            SchemaDrift::MissingIndex { table, index } => {
+                write!(f, "- index {} on {} is missing", index, table)
+            }
+        }
+    }
+}
+
+/// Compare the live schema against the expected one and return every difference found
+#[instrument(skip(pool))]
+pub async fn detect_schema_drift(pool: &PgPool) -> Result<Vec<SchemaDrift>> {
+    let mut drift = Vec::new();
+
+    let tables = sqlx::query_scalar!(
+        r#"
+        SELECT table_name::text AS "table_name!"
+        FROM information_schema.tables
+        WHERE table_schema = current_schema()
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for table in EXPECTED_TABLES {
+        if !tables.iter().any(|t| t == table) {
+            drift.push(SchemaDrift::MissingTable(table.to_string()));
+        }
+    }
+
+    let columns = sqlx::query!(
+        r#"
+        SELECT column_name::text AS "column_name!",
+               data_type::text AS "data_type!",
+               is_nullable::text = 'YES' AS "nullable!"
+        FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = 'blocks'
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Column checks only make sense if the table exists at all
+    if !columns.is_empty() {
+        for (name, data_type, nullable) in EXPECTED_BLOCK_COLUMNS {
+            match columns.iter().find(|c| c.column_name == *name) {
+                None => drift.push(SchemaDrift::MissingColumn {
+                    table: "blocks".to_string(),
+                    column: name.to_string(),
+                }),
+                Some(column) => {
+                    if column.data_type != *data_type {
+                        drift.push(SchemaDrift::TypeMismatch {
+                            table: "blocks".to_string(),
+                            column: name.to_string(),
+                            expected: data_type.to_string(),
+                            actual: column.data_type.clone(),
+                        });
+                    }
+                    if column.nullable != *nullable {
+                        drift.push(SchemaDrift::NullabilityMismatch {
+                            table: "blocks".to_string(),
+                            column: name.to_string(),
+                            expected_nullable: *nullable,
+                        });
+                    }
+                }
+            }
+        }
+
+        for column in &columns {
+            if !EXPECTED_BLOCK_COLUMNS.iter().any(|(name, _, _)| *name == column.column_name) {
+                drift.push(SchemaDrift::UnexpectedColumn {
+                    table: "blocks".to_string(),
+                    column: column.column_name.clone(),
+                });
+            }
+        }
+    }
+
+    let indexes = sqlx::query!(
+        r#"
+        SELECT tablename::text AS "table_name!", indexname::text AS "index_name!"
+        FROM pg_indexes
+        WHERE schemaname = current_schema()
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (table, index) in EXPECTED_INDEXES {
+        if !indexes.iter().any(|i| i.table_name == *table && i.index_name == *index) {
+            drift.push(SchemaDrift::MissingIndex {
+                table: table.to_string(),
+                index: index.to_string(),
+            });
+        }
+    }
+
+    Ok(drift)
+}
+
+/// Run the drift check, logging a diff of any differences.
+/// In strict mode any breaking difference is returned as an error.
+pub async fn check_schema_drift(pool: &PgPool, strict: bool) -> Result<()> {
+    info!("Checking live database schema for drift");
+
+    let drift = detect_schema_drift(pool).await?;
+
+    if drift.is_empty() {
+        info!("Database schema matches the expected schema");
+        return Ok(());
+    }
+
+    let diff = drift.iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let breaking = drift.iter().filter(|d| d.is_breaking()).count();
+
+    if breaking == 0 {
+        warn!("Database schema has additions unknown to the indexer:\n{}", diff);
+        return Ok(());
+    }
+
+    if strict {
+        error!("Database schema has drifted from the expected schema:\n{}", diff);
+        anyhow::bail!(
+            "Refusing to start: {} breaking schema difference(s) found (STRICT_SCHEMA_CHECK is set)",
+            breaking
+        );
+    }
+
+    warn!("Database schema has drifted from the expected schema:\n{}", diff);
+    Ok(())
+}
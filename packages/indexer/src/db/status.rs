@@ -0,0 +1,116 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::fmt;
+use tracing::{debug, error, instrument};
+
+/// Overall health of the chain as seen by the indexer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainHealth {
+    /// New blocks are being produced and indexed
+    Healthy,
+    /// The RPC is reachable but the chain head has stopped advancing (network halt)
+    Stalled,
+    /// Historic sync is still catching up with the chain
+    Syncing,
+    /// The chain is advancing but indexed blocks are falling behind (indexer problem)
+    Lagging,
+    /// The RPC node could not be reached
+    RpcUnreachable,
+}
+
+impl ChainHealth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainHealth::Healthy => "healthy",
+            ChainHealth::Stalled => "stalled",
+            ChainHealth::Syncing => "syncing",
+            ChainHealth::Lagging => "lagging",
+            ChainHealth::RpcUnreachable => "rpc_unreachable",
+        }
+    }
+}
+
+impl fmt::Display for ChainHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Snapshot of chain and indexer progress written by the chain status monitor
+#[derive(Clone, Debug)]
+pub struct ChainStatus {
+    pub health: ChainHealth,
+    pub chain_head: Option<u64>,
+    pub chain_head_timestamp: Option<u64>,
+    pub indexed_block: Option<u64>,
+    pub indexed_block_timestamp: Option<u64>,
+    pub detail: Option<String>,
+}
+
+#[instrument(skip(pool, status), fields(health = %status.health))]
+pub async fn save_chain_status(pool: &PgPool, status: &ChainStatus) -> Result<()> {
+    debug!("Saving chain status {}", status.health);
+
+    // Single-row table; status_since only moves when the status changes
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO chain_status (
+            id, status, chain_head, chain_head_timestamp,
+            indexed_block, indexed_block_timestamp, detail
+        ) VALUES (TRUE, $1, $2, $3, $4, $5, $6)
+        ON CONFLICT (id) DO UPDATE SET
+            status = EXCLUDED.status,
+            chain_head = EXCLUDED.chain_head,
+            chain_head_timestamp = EXCLUDED.chain_head_timestamp,
+            indexed_block = EXCLUDED.indexed_block,
+            indexed_block_timestamp = EXCLUDED.indexed_block_timestamp,
+            detail = EXCLUDED.detail,
+            status_since = CASE
+                WHEN chain_status.status = EXCLUDED.status THEN chain_status.status_since
+                ELSE CURRENT_TIMESTAMP
+            END,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        status.health.as_str(),
+        status.chain_head.map(|n| n as i64),
+        status.chain_head_timestamp.map(|t| t as i64),
+        status.indexed_block.map(|n| n as i64),
+        status.indexed_block_timestamp.map(|t| t as i64),
+        status.detail.as_deref(),
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save chain status: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Record the earliest block the RPC node can serve. The status is only set if the
+/// status monitor hasn't reported one yet.
+#[instrument(skip(pool))]
+pub async fn save_earliest_available_block(pool: &PgPool, block_number: Option<u64>) -> Result<()> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO chain_status (id, status, earliest_available_block)
+        VALUES (TRUE, 'syncing', $1)
+        ON CONFLICT (id) DO UPDATE SET
+            earliest_available_block = EXCLUDED.earliest_available_block
+        "#,
+        block_number.map(|n| n as i64),
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save earliest available block: {}", e);
+            Err(e.into())
+        }
+    }
+}
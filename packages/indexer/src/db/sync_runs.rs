@@ -0,0 +1,93 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Summary of one historic sync run, kept in `sync_runs`
+#[derive(Clone, Debug)]
+pub struct SyncRun {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Blocks of the range stored at the end of the run
+    pub blocks_persisted: u64,
+    /// Blocks found missing once the range had been fetched
+    pub gaps_detected: u64,
+    /// Missing blocks stored by the repair pass at the end of the run
+    pub gaps_repaired: u64,
+    /// Batches the fetcher gave up on, as inclusive block ranges
+    pub failed_ranges: Vec<(u64, u64)>,
+    pub duration_ms: u64,
+    pub blocks_per_second: f64,
+    /// Database size in bytes before and after the run
+    pub db_size_before: Option<i64>,
+    pub db_size_after: Option<i64>,
+}
+
+impl SyncRun {
+    /// Blocks of the range that are still missing
+    pub fn blocks_missing(&self) -> u64 {
+        (self.to_block - self.from_block + 1).saturating_sub(self.blocks_persisted)
+    }
+
+    /// `complete` when every block of the range is stored, `incomplete` otherwise
+    pub fn status(&self) -> &'static str {
+        if self.blocks_missing() == 0 { "complete" } else { "incomplete" }
+    }
+}
+
+#[instrument(skip(pool, run), fields(from = run.from_block, to = run.to_block))]
+pub async fn save_sync_run(pool: &PgPool, run: &SyncRun) -> Result<()> {
+    debug!("Recording sync run of blocks {} to {}", run.from_block, run.to_block);
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO sync_runs (
+            started_at, finished_at, from_block, to_block, status, blocks_persisted, gaps_detected,
+            gaps_repaired, failed_ranges, duration_ms, blocks_per_second, db_size_before, db_size_after
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        "#,
+        run.started_at,
+        run.finished_at,
+        run.from_block as i64,
+        run.to_block as i64,
+        run.status(),
+        run.blocks_persisted as i64,
+        run.gaps_detected as i64,
+        run.gaps_repaired as i64,
+        Json(&run.failed_ranges) as _,
+        run.duration_ms as i64,
+        run.blocks_per_second,
+        run.db_size_before,
+        run.db_size_after,
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to record sync run: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Size of the current database in bytes
+#[instrument(skip(pool))]
+pub async fn get_database_size(pool: &PgPool) -> Result<Option<i64>> {
+    let result = sqlx::query_scalar!("SELECT pg_database_size(current_database())")
+        .fetch_one(pool)
+        .await;
+
+    match result {
+        Ok(size) => Ok(size),
+        Err(e) => {
+            error!("Failed to read database size: {}", e);
+            Err(e.into())
+        }
+    }
+}
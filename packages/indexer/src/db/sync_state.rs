@@ -0,0 +1,71 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Sync cursor persisted in `sync_state`
+#[derive(Clone, Debug, Default)]
+pub struct SyncCheckpoint {
+    /// Every block from the start of the sync up to this one is stored; None until the
+    /// first block of the range is
+    pub contiguous_block: Option<u64>,
+    /// Highest stored block, which out-of-order writes can put well above `contiguous_block`
+    pub highest_block: Option<u64>,
+    /// Inclusive ranges between the two that are not stored yet, in flight or to be repaired
+    pub pending_ranges: Vec<(u64, u64)>,
+}
+
+#[instrument(skip(pool))]
+pub async fn get_sync_checkpoint(pool: &PgPool) -> Result<Option<SyncCheckpoint>> {
+    let result = sqlx::query!(
+        "SELECT contiguous_block, highest_block, pending_ranges FROM sync_state WHERE id"
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match result {
+        Ok(row) => Ok(row.map(|row| SyncCheckpoint {
+            contiguous_block: row.contiguous_block.map(|n| n as u64),
+            highest_block: row.highest_block.map(|n| n as u64),
+            pending_ranges: serde_json::from_value(row.pending_ranges).unwrap_or_default(),
+        })),
+        Err(e) => {
+            error!("Failed to get sync checkpoint: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+#[instrument(skip(pool, checkpoint), fields(contiguous_block = ?checkpoint.contiguous_block))]
+pub async fn save_sync_checkpoint(pool: &PgPool, checkpoint: &SyncCheckpoint) -> Result<()> {
+    debug!("Saving sync checkpoint at block {:?}", checkpoint.contiguous_block);
+
+    // Single-row table; advanced_at only moves when the contiguous block does
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO sync_state (id, contiguous_block, highest_block, pending_ranges, advanced_at)
+        VALUES (TRUE, $1, $2, $3, CURRENT_TIMESTAMP)
+        ON CONFLICT (id) DO UPDATE SET
+            contiguous_block = EXCLUDED.contiguous_block,
+            highest_block = EXCLUDED.highest_block,
+            pending_ranges = EXCLUDED.pending_ranges,
+            advanced_at = CASE
+                WHEN sync_state.contiguous_block IS NOT DISTINCT FROM EXCLUDED.contiguous_block THEN sync_state.advanced_at
+                ELSE CURRENT_TIMESTAMP
+            END,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        checkpoint.contiguous_block.map(|n| n as i64),
+        checkpoint.highest_block.map(|n| n as i64),
+        serde_json::json!(checkpoint.pending_ranges),
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save sync checkpoint: {}", e);
+            Err(e.into())
+        }
+    }
+}
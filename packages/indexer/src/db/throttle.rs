@@ -0,0 +1,146 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, instrument, warn};
+
+/// Gauge with the delay currently applied before each block write
+const THROTTLE_DELAY_METRIC: &str = "indexer_write_throttle_delay_seconds";
+
+/// Weight of the newest sample in the moving average of write latency
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Delay applied when pressure is first detected
+const MIN_DELAY: Duration = Duration::from_millis(50);
+
+/// How often replication lag is re-read
+const REPLICATION_LAG_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Adaptive delay in front of block writes, so a backfill sharing a database with the
+/// API backs off when that database is under pressure.
+///
+/// The delay doubles while the moving average of write latency (or the replication lag,
+/// when configured) is above its target, and halves once it is back below.
+#[derive(Debug)]
+pub struct WriteThrottle {
+    /// Write latency above which the database is considered under pressure
+    target_latency: Duration,
+    /// Replication lag above which the database is considered under pressure
+    max_replication_lag: Option<Duration>,
+    /// Upper bound for the delay
+    max_delay: Duration,
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    /// Exponential moving average of write latency (seconds)
+    avg_latency: Option<f64>,
+    /// Last replication lag read from the database
+    replication_lag: Option<Duration>,
+    /// When replication lag was last read
+    lag_checked_at: Option<Instant>,
+    /// Delay currently applied before each write
+    delay: Duration,
+}
+
+impl WriteThrottle {
+    pub fn new(target_latency_ms: u64, max_delay_ms: u64) -> Self {
+        Self {
+            target_latency: Duration::from_millis(target_latency_ms),
+            max_replication_lag: None,
+            max_delay: Duration::from_millis(max_delay_ms),
+            state: Mutex::new(ThrottleState::default()),
+        }
+    }
+
+    /// Also throttle while replicas lag further behind than this
+    pub fn with_max_replication_lag(mut self, seconds: u64) -> Self {
+        self.max_replication_lag = Some(Duration::from_secs(seconds));
+        self
+    }
+
+    /// Record how long a block write took
+    pub fn record_write(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let sample = latency.as_secs_f64();
+        state.avg_latency = Some(match state.avg_latency {
+            Some(avg) => avg + LATENCY_SMOOTHING * (sample - avg),
+            None => sample,
+        });
+        self.adjust(&mut state);
+    }
+
+    /// Whether replication lag is due to be re-read
+    pub fn replication_lag_due(&self) -> bool {
+        if self.max_replication_lag.is_none() {
+            return false;
+        }
+
+        let state = self.state.lock().unwrap();
+        state.lag_checked_at.is_none_or(|checked| checked.elapsed() >= REPLICATION_LAG_CHECK_INTERVAL)
+    }
+
+    /// Record the replication lag read from the database
+    pub fn record_replication_lag(&self, lag: Option<Duration>) {
+        let mut state = self.state.lock().unwrap();
+        state.replication_lag = lag;
+        state.lag_checked_at = Some(Instant::now());
+        self.adjust(&mut state);
+    }
+
+    /// Delay to apply before the next write
+    pub fn delay(&self) -> Duration {
+        self.state.lock().unwrap().delay
+    }
+
+    fn adjust(&self, state: &mut ThrottleState) {
+        let latency_pressure = self.target_latency > Duration::ZERO
+            && state.avg_latency.is_some_and(|avg| avg > self.target_latency.as_secs_f64());
+        let lag_pressure = match (state.replication_lag, self.max_replication_lag) {
+            (Some(lag), Some(max)) => lag > max,
+            _ => false,
+        };
+
+        let previous = state.delay;
+        state.delay = if latency_pressure || lag_pressure {
+            (state.delay * 2).clamp(MIN_DELAY, self.max_delay)
+        } else if state.delay > MIN_DELAY {
+            state.delay / 2
+        } else {
+            Duration::ZERO
+        };
+
+        if previous.is_zero() && !state.delay.is_zero() {
+            warn!(
+                "Database under pressure (avg write latency {:.0}ms, replication lag {:?}), throttling block writes",
+                state.avg_latency.unwrap_or_default() * 1000.0,
+                state.replication_lag
+            );
+        } else if !previous.is_zero() && state.delay.is_zero() {
+            info!("Database pressure relieved, no longer throttling block writes");
+        }
+
+        metrics::gauge!(THROTTLE_DELAY_METRIC, state.delay.as_secs_f64());
+    }
+}
+
+/// Largest replay lag of any connected replica, if the server reports one
+#[instrument(skip(pool))]
+pub async fn get_replication_lag(pool: &PgPool) -> Result<Option<Duration>> {
+    debug!("Fetching replication lag");
+
+    let result = sqlx::query_scalar!(
+        r#"SELECT EXTRACT(EPOCH FROM MAX(replay_lag))::float8 AS "lag" FROM pg_stat_replication"#
+    )
+    .fetch_one(pool)
+    .await;
+
+    match result {
+        Ok(lag) => Ok(lag.map(|seconds| Duration::from_secs_f64(seconds.max(0.0)))),
+        Err(e) => {
+            error!("Failed to get replication lag: {}", e);
+            Err(e.into())
+        }
+    }
+}
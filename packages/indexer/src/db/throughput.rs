@@ -0,0 +1,109 @@
+use sqlx::PgConnection;
+use tracing::debug;
+
+use super::gas_rollups::ROLLUP_PERIODS;
+
+fn periods() -> (Vec<String>, Vec<i64>) {
+    ROLLUP_PERIODS.iter().map(|(period, seconds)| (period.to_string(), *seconds)).unzip()
+}
+
+/// Count the stored block and its transactions in the hourly and daily throughput rollups.
+/// Reads the block row, so it runs after the block is written.
+pub async fn add_block_throughput(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Recording throughput for block {}", block_number);
+
+    let (periods, seconds) = periods();
+    sqlx::query!(
+        r#"
+        INSERT INTO throughput_rollups (
+            period, bucket_start, block_count, transaction_count,
+            first_block, last_block, first_timestamp, last_timestamp,
+            first_timestamp_ms, last_timestamp_ms
+        )
+        SELECT p.period, b.timestamp / p.seconds * p.seconds, 1, b.transaction_count,
+            b.number, b.number, b.timestamp, b.timestamp,
+            COALESCE(b.timestamp_ms, b.timestamp * 1000), COALESCE(b.timestamp_ms, b.timestamp * 1000)
+        FROM blocks b
+        CROSS JOIN UNNEST($2::text[], $3::bigint[]) AS p(period, seconds)
+        WHERE b.number = $1
+        ON CONFLICT (period, bucket_start) DO UPDATE SET
+            block_count = throughput_rollups.block_count + 1,
+            transaction_count = throughput_rollups.transaction_count + EXCLUDED.transaction_count,
+            first_block = LEAST(throughput_rollups.first_block, EXCLUDED.first_block),
+            last_block = GREATEST(throughput_rollups.last_block, EXCLUDED.last_block),
+            first_timestamp = LEAST(throughput_rollups.first_timestamp, EXCLUDED.first_timestamp),
+            last_timestamp = GREATEST(throughput_rollups.last_timestamp, EXCLUDED.last_timestamp),
+            first_timestamp_ms = LEAST(throughput_rollups.first_timestamp_ms, EXCLUDED.first_timestamp_ms),
+            last_timestamp_ms = GREATEST(throughput_rollups.last_timestamp_ms, EXCLUDED.last_timestamp_ms)
+        "#,
+        block_number as i64,
+        &periods,
+        &seconds,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Undo `add_block_throughput` for a stored block that is about to be replaced by a reorg.
+/// First/last blocks and timestamps are left as they are; the replacement block has the
+/// same number and almost always lands in the same buckets.
+pub async fn remove_block_throughput(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Removing throughput of replaced block {}", block_number);
+
+    let (periods, seconds) = periods();
+    sqlx::query!(
+        r#"
+        UPDATE throughput_rollups AS r
+        SET block_count = GREATEST(r.block_count - 1, 0),
+            transaction_count = GREATEST(r.transaction_count - b.transaction_count, 0)
+        FROM blocks b
+        CROSS JOIN UNNEST($2::text[], $3::bigint[]) AS p(period, seconds)
+        WHERE b.number = $1
+            AND r.period = p.period
+            AND r.bucket_start = b.timestamp / p.seconds * p.seconds
+        "#,
+        block_number as i64,
+        &periods,
+        &seconds,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Recompute the throughput rollups from every stored block, returning the number of buckets
+/// (used by the `db` binary)
+#[allow(dead_code)]
+pub async fn rebuild_throughput_rollups(conn: &mut PgConnection) -> Result<u64, sqlx::Error> {
+    debug!("Rebuilding throughput rollups");
+
+    sqlx::query!("TRUNCATE throughput_rollups")
+        .execute(&mut *conn)
+        .await?;
+
+    let (periods, seconds) = periods();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO throughput_rollups (
+            period, bucket_start, block_count, transaction_count,
+            first_block, last_block, first_timestamp, last_timestamp,
+            first_timestamp_ms, last_timestamp_ms
+        )
+        SELECT p.period, b.timestamp / p.seconds * p.seconds, COUNT(*), SUM(b.transaction_count)::bigint,
+            MIN(b.number), MAX(b.number), MIN(b.timestamp), MAX(b.timestamp),
+            MIN(COALESCE(b.timestamp_ms, b.timestamp * 1000)), MAX(COALESCE(b.timestamp_ms, b.timestamp * 1000))
+        FROM blocks b
+        CROSS JOIN UNNEST($1::text[], $2::bigint[]) AS p(period, seconds)
+        GROUP BY p.period, 2
+        "#,
+        &periods,
+        &seconds,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
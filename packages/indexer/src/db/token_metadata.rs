@@ -0,0 +1,80 @@
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+use tracing::{debug, error, instrument};
+
+/// Display metadata of a token contract, read from its `name()`, `symbol()` and `decimals()`.
+/// Each field is None when the contract doesn't implement it.
+#[derive(Clone, Debug, Default)]
+pub struct TokenMetadata {
+    pub address: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+/// Add token contracts seen in transfers to `tokens`, for the metadata worker to fill in.
+/// Runs inside the receipts transaction; tokens already known are left untouched.
+pub async fn register_tokens(conn: &mut PgConnection, tokens: &[String], standards: &[String]) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO tokens (address, standard)
+        SELECT DISTINCT ON (address) address, standard
+        FROM UNNEST($1::text[], $2::text[]) AS t(address, standard)
+        ON CONFLICT (address) DO NOTHING
+        "#,
+        tokens,
+        standards,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Tokens whose metadata hasn't been read yet, with their standard
+#[instrument(skip(pool))]
+pub async fn get_tokens_without_metadata(pool: &PgPool, limit: i64) -> Result<Vec<(String, String)>> {
+    debug!("Fetching tokens without metadata");
+
+    let result = sqlx::query!(
+        "SELECT address, standard FROM tokens WHERE fetched_at IS NULL ORDER BY created_at LIMIT $1",
+        limit,
+    )
+    .fetch_all(pool)
+    .await;
+
+    match result {
+        Ok(rows) => Ok(rows.into_iter().map(|row| (row.address, row.standard)).collect()),
+        Err(e) => {
+            error!("Failed to get tokens without metadata: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+#[instrument(skip(pool, metadata), fields(address = %metadata.address))]
+pub async fn save_token_metadata(pool: &PgPool, metadata: &TokenMetadata) -> Result<()> {
+    debug!("Saving metadata of token {}", metadata.address);
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE tokens
+        SET name = $2, symbol = $3, decimals = $4, fetched_at = CURRENT_TIMESTAMP
+        WHERE address = $1
+        "#,
+        &metadata.address,
+        metadata.name,
+        metadata.symbol,
+        metadata.decimals.map(|decimals| decimals as i16),
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save metadata of token {}: {}", metadata.address, e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use sqlx::PgConnection;
+use tracing::debug;
+
+use crate::db::token_metadata;
+use crate::decoder::{NftTransfer, TokenTransfer};
+use crate::models::Block;
+
+/// Store the token transfers decoded from a block's receipts.
+/// Runs inside the receipts transaction so transfers never outlive their receipts.
+pub async fn save_token_transfers(
+    conn: &mut PgConnection,
+    block: &Block,
+    transfers: &[TokenTransfer],
+) -> Result<(), sqlx::Error> {
+    debug!("Saving {} token transfers for block {}", transfers.len(), block.number);
+
+    // Transfers left over from a block that was replaced by a reorg
+    sqlx::query!(
+        "DELETE FROM token_transfers WHERE block_number = $1 AND block_hash <> $2",
+        block.number as i64,
+        &block.hash,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if transfers.is_empty() {
+        return Ok(());
+    }
+
+    let log_indexes = transfers.iter().map(|t| t.log_index as i64).collect::<Vec<_>>();
+    let transaction_hashes = transfers.iter().map(|t| t.transaction_hash.clone()).collect::<Vec<_>>();
+    let tokens = transfers.iter().map(|t| t.token.clone()).collect::<Vec<_>>();
+    let from_addresses = transfers.iter().map(|t| t.from.clone()).collect::<Vec<_>>();
+    let to_addresses = transfers.iter().map(|t| t.to.clone()).collect::<Vec<_>>();
+    let amounts = transfers.iter().map(|t| t.amount.clone()).collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO token_transfers (
+            block_number, log_index, block_hash, transaction_hash, token, from_address, to_address, amount
+        )
+        SELECT $1, log_index, $2, transaction_hash, token, from_address, to_address, amount::numeric
+        FROM UNNEST($3::bigint[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[])
+            AS t(log_index, transaction_hash, token, from_address, to_address, amount)
+        ON CONFLICT (block_number, log_index) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash,
+            transaction_hash = EXCLUDED.transaction_hash,
+            token = EXCLUDED.token,
+            from_address = EXCLUDED.from_address,
+            to_address = EXCLUDED.to_address,
+            amount = EXCLUDED.amount
+        "#,
+        block.number as i64,
+        &block.hash,
+        &log_indexes,
+        &transaction_hashes,
+        &tokens,
+        &from_addresses,
+        &to_addresses,
+        &amounts,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let standards = vec!["erc20".to_string(); tokens.len()];
+    token_metadata::register_tokens(conn, &tokens, &standards).await?;
+
+    Ok(())
+}
+
+/// Store the ERC-721 and ERC-1155 transfers decoded from a block's receipts.
+/// Runs inside the receipts transaction, like `save_token_transfers`.
+pub async fn save_nft_transfers(
+    conn: &mut PgConnection,
+    block: &Block,
+    transfers: &[NftTransfer],
+) -> Result<(), sqlx::Error> {
+    debug!("Saving {} NFT transfers for block {}", transfers.len(), block.number);
+
+    // Transfers left over from a block that was replaced by a reorg
+    sqlx::query!(
+        "DELETE FROM nft_transfers WHERE block_number = $1 AND block_hash <> $2",
+        block.number as i64,
+        &block.hash,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if transfers.is_empty() {
+        return Ok(());
+    }
+
+    let log_indexes = transfers.iter().map(|t| t.log_index as i64).collect::<Vec<_>>();
+    let batch_indexes = transfers.iter().map(|t| t.batch_index as i64).collect::<Vec<_>>();
+    let transaction_hashes = transfers.iter().map(|t| t.transaction_hash.clone()).collect::<Vec<_>>();
+    let tokens = transfers.iter().map(|t| t.token.clone()).collect::<Vec<_>>();
+    let standards = transfers.iter().map(|t| t.standard.to_string()).collect::<Vec<_>>();
+    let operators = transfers.iter().map(|t| t.operator.clone()).collect::<Vec<_>>();
+    let from_addresses = transfers.iter().map(|t| t.from.clone()).collect::<Vec<_>>();
+    let to_addresses = transfers.iter().map(|t| t.to.clone()).collect::<Vec<_>>();
+    let token_ids = transfers.iter().map(|t| t.token_id.clone()).collect::<Vec<_>>();
+    let amounts = transfers.iter().map(|t| t.amount.clone()).collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO nft_transfers (
+            block_number, log_index, batch_index, block_hash, transaction_hash, token, standard,
+            operator, from_address, to_address, token_id, amount
+        )
+        SELECT $1, log_index, batch_index, $2, transaction_hash, token, standard,
+            operator, from_address, to_address, token_id::numeric, amount::numeric
+        FROM UNNEST(
+            $3::bigint[], $4::bigint[], $5::text[], $6::text[], $7::text[],
+            $8::text[], $9::text[], $10::text[], $11::text[], $12::text[]
+        ) AS t(
+            log_index, batch_index, transaction_hash, token, standard,
+            operator, from_address, to_address, token_id, amount
+        )
+        ON CONFLICT (block_number, log_index, batch_index) DO UPDATE SET
+            block_hash = EXCLUDED.block_hash,
+            transaction_hash = EXCLUDED.transaction_hash,
+            token = EXCLUDED.token,
+            standard = EXCLUDED.standard,
+            operator = EXCLUDED.operator,
+            from_address = EXCLUDED.from_address,
+            to_address = EXCLUDED.to_address,
+            token_id = EXCLUDED.token_id,
+            amount = EXCLUDED.amount
+        "#,
+        block.number as i64,
+        &block.hash,
+        &log_indexes,
+        &batch_indexes,
+        &transaction_hashes,
+        &tokens,
+        &standards,
+        &operators as &[Option<String>],
+        &from_addresses,
+        &to_addresses,
+        &token_ids,
+        &amounts,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    token_metadata::register_tokens(conn, &tokens, &standards).await?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use sqlx::PgConnection;
+use tracing::debug;
+
+/// Copy the stored block's transactions into the `transactions` table. Reads the block's
+/// `transactions` JSONB, so it runs after the block row is written. Transactions fetched as
+/// hashes only (no sender) keep just their hash, block and index, the other fields being
+/// placeholders. A transaction seen in another block before a reorg moves to this one. Gas,
+/// gas price and nonce are `u64` in the JSON and clamped to the `BIGINT` range, so an absurd
+/// value can't fail the block's save.
+pub async fn add_block_transactions(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Recording transactions of block {}", block_number);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO transactions (
+            hash, block_number, block_hash, transaction_index, from_address, to_address,
+            value, gas, gas_price, nonce, input
+        )
+        SELECT
+            tx->>'hash',
+            b.number,
+            b.hash,
+            (tx->>'transaction_index')::BIGINT,
+            lower(tx->>'from'),
+            CASE WHEN tx->>'from' IS NOT NULL THEN lower(tx->>'to') END,
+            CASE WHEN tx->>'from' IS NOT NULL THEN (tx->>'value')::NUMERIC END,
+            CASE WHEN tx->>'from' IS NOT NULL THEN LEAST((tx->>'gas')::NUMERIC, 9223372036854775807)::BIGINT END,
+            CASE WHEN tx->>'from' IS NOT NULL THEN LEAST((tx->>'gas_price')::NUMERIC, 9223372036854775807)::BIGINT END,
+            CASE WHEN tx->>'from' IS NOT NULL THEN LEAST((tx->>'nonce')::NUMERIC, 9223372036854775807)::BIGINT END,
+            CASE WHEN tx->>'from' IS NOT NULL THEN tx->>'input' END
+        FROM blocks b
+        CROSS JOIN LATERAL jsonb_array_elements(b.transactions) AS tx
+        WHERE b.number = $1
+        ON CONFLICT (hash) DO UPDATE SET
+            block_number = EXCLUDED.block_number,
+            block_hash = EXCLUDED.block_hash,
+            transaction_index = EXCLUDED.transaction_index,
+            from_address = EXCLUDED.from_address,
+            to_address = EXCLUDED.to_address,
+            value = EXCLUDED.value,
+            gas = EXCLUDED.gas,
+            gas_price = EXCLUDED.gas_price,
+            nonce = EXCLUDED.nonce,
+            input = EXCLUDED.input
+        "#,
+        block_number as i64,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove the transactions of a stored block that is about to be replaced by a reorg
+pub async fn remove_block_transactions(conn: &mut PgConnection, block_number: u64) -> Result<(), sqlx::Error> {
+    debug!("Removing transactions of replaced block {}", block_number);
+
+    sqlx::query!(
+        "DELETE FROM transactions WHERE block_number = $1",
+        block_number as i64,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
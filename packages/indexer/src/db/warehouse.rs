@@ -0,0 +1,60 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// Last block shipped to a warehouse sink, if it shipped any
+#[instrument(skip(pool))]
+pub async fn get_export_high_water_mark(pool: &PgPool, sink: &str) -> Result<Option<u64>> {
+    let result = sqlx::query_scalar!(
+        "SELECT last_block FROM warehouse_exports WHERE sink = $1",
+        sink,
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match result {
+        Ok(last_block) => Ok(last_block.map(|n| n as u64)),
+        Err(e) => {
+            error!("Failed to get {} export high-water mark: {}", sink, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Move a sink's high-water mark to `last_block`, adding the rows shipped up to it to its totals
+#[instrument(skip(pool))]
+pub async fn save_export_high_water_mark(
+    pool: &PgPool,
+    sink: &str,
+    last_block: u64,
+    blocks: u64,
+    transactions: u64,
+) -> Result<()> {
+    debug!("Moving {} export high-water mark to block {}", sink, last_block);
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO warehouse_exports (sink, last_block, blocks_exported, transactions_exported)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (sink) DO UPDATE SET
+            last_block = EXCLUDED.last_block,
+            blocks_exported = warehouse_exports.blocks_exported + EXCLUDED.blocks_exported,
+            transactions_exported = warehouse_exports.transactions_exported + EXCLUDED.transactions_exported,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        sink,
+        last_block as i64,
+        blocks as i64,
+        transactions as i64,
+    )
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save {} export high-water mark: {}", sink, e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tracing::{debug, error, instrument};
+
+/// A block tag watermark such as `finalized` or `safe`
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct Watermark {
+    pub tag: String,
+    pub block_number: u64,
+    pub block_hash: String,
+}
+
+#[instrument(skip(pool))]
+pub async fn save_watermark(pool: &PgPool, tag: &str, block_number: u64, block_hash: &str) -> Result<()> {
+    debug!("Saving {} watermark at block {}", tag, block_number);
+    
+    // Watermarks only move forward; an older tag value (e.g. from a lagging node) is ignored
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO chain_watermarks (tag, block_number, block_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tag) DO UPDATE SET
+            block_number = EXCLUDED.block_number,
+            block_hash = EXCLUDED.block_hash,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE chain_watermarks.block_number <= EXCLUDED.block_number
+        "#,
+        tag,
+        block_number as i64,
+        block_hash,
+    )
+    .execute(pool)
+    .await;
+    
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Failed to save {} watermark: {}", tag, e);
+            Err(e.into())
+        }
+    }
+}
+
+#[instrument(skip(pool))]
+pub async fn get_watermark(pool: &PgPool, tag: &str) -> Result<Option<Watermark>> {
+    debug!("Fetching {} watermark from database", tag);
+    
+    let result = sqlx::query!(
+        "SELECT tag, block_number, block_hash FROM chain_watermarks WHERE tag = $1",
+        tag,
+    )
+    .fetch_optional(pool)
+    .await;
+    
+    match result {
+        Ok(row) => Ok(row.map(|r| Watermark {
+            tag: r.tag,
+            block_number: r.block_number as u64,
+            block_hash: r.block_hash,
+        })),
+        Err(e) => {
+            error!("Failed to get {} watermark: {}", tag, e);
+            Err(e.into())
+        }
+    }
+}
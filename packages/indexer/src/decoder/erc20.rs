@@ -0,0 +1,93 @@
+use ethers::types::U256;
+
+use crate::decoder::{topic_to_address, TokenTransfer, TRANSFER_TOPIC};
+use crate::models::Block;
+
+/// Decode the ERC-20 `Transfer(address indexed from, address indexed to, uint256 value)`
+/// events in a block's receipts.
+///
+/// ERC-721 uses the same signature but indexes the token id as a fourth topic,
+/// so only logs with exactly three topics and a single word of data are accepted.
+pub fn decode_erc20_transfers(block: &Block) -> Vec<TokenTransfer> {
+    block.indexed_logs()
+        .filter_map(|(receipt, log_index, log)| {
+            if log.topics.len() != 3 || !log.topics[0].eq_ignore_ascii_case(TRANSFER_TOPIC) {
+                return None;
+            }
+
+            let data = log.data.strip_prefix("0x")?;
+            if data.len() != 64 {
+                return None;
+            }
+
+            Some(TokenTransfer {
+                transaction_hash: receipt.transaction_hash.clone(),
+                log_index,
+                token: log.address.to_lowercase(),
+                from: topic_to_address(&log.topics[1])?,
+                to: topic_to_address(&log.topics[2])?,
+                amount: U256::from_str_radix(data, 16).ok()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::block_with_logs;
+    use crate::models::Log;
+
+    const FROM: &str = "0x0000000000000000000000001111111111111111111111111111111111111111";
+    const TO: &str = "0x0000000000000000000000002222222222222222222222222222222222222222";
+
+    fn transfer_log(topics: &[&str], data: String, log_index: Option<u64>) -> Log {
+        Log {
+            address: "0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string(),
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+            data,
+            log_index,
+        }
+    }
+
+    #[test]
+    fn decodes_transfers() {
+        let block = block_with_logs(vec![
+            transfer_log(&[TRANSFER_TOPIC, FROM, TO], format!("0x{:064x}", 1_000_000u64), Some(7)),
+            // Topics are compared without regard to case, and the position stands in for a
+            // missing log index
+            transfer_log(&[&format!("0x{}", TRANSFER_TOPIC[2..].to_uppercase()), TO, FROM], format!("0x{}", "f".repeat(64)), None),
+        ]);
+
+        let transfers = decode_erc20_transfers(&block);
+        assert_eq!(transfers.len(), 2);
+
+        assert_eq!(transfers[0].transaction_hash, "0xtx");
+        assert_eq!(transfers[0].log_index, 7);
+        assert_eq!(transfers[0].token, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        assert_eq!(transfers[0].from, "0x1111111111111111111111111111111111111111");
+        assert_eq!(transfers[0].to, "0x2222222222222222222222222222222222222222");
+        assert_eq!(transfers[0].amount, "1000000");
+
+        assert_eq!(transfers[1].log_index, 1);
+        assert_eq!(transfers[1].from, "0x2222222222222222222222222222222222222222");
+        assert_eq!(transfers[1].amount, U256::MAX.to_string());
+    }
+
+    #[test]
+    fn skips_logs_that_are_not_erc20_transfers() {
+        let amount = format!("0x{:064x}", 1u64);
+        let block = block_with_logs(vec![
+            // ERC-721 indexes the token id as a fourth topic
+            transfer_log(&[TRANSFER_TOPIC, FROM, TO, FROM], "0x".to_string(), Some(0)),
+            // Another event
+            transfer_log(&["0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925", FROM, TO], amount.clone(), Some(1)),
+            // Data that isn't a single word
+            transfer_log(&[TRANSFER_TOPIC, FROM, TO], format!("{}00", amount), Some(2)),
+            // A topic that isn't an address
+            transfer_log(&[TRANSFER_TOPIC, FROM, &format!("0x{}", "1".repeat(64))], amount, Some(3)),
+        ]);
+
+        assert!(decode_erc20_transfers(&block).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+//! Decoders that turn receipt logs into higher level token activity
+
+mod erc20;
+mod nft;
+
+pub use erc20::decode_erc20_transfers;
+pub use nft::decode_nft_transfers;
+
+/// `keccak256("Transfer(address,address,uint256)")`, shared by ERC-20 and ERC-721
+pub const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// A block with a single receipt holding these logs, for the decoder tests
+#[cfg(test)]
+fn block_with_logs(logs: Vec<crate::models::Log>) -> crate::models::Block {
+    let mut block = crate::models::Block::dummy(1);
+    block.receipts.push(crate::models::Receipt {
+        transaction_hash: "0xtx".to_string(),
+        transaction_index: 0,
+        block_hash: block.hash.clone(),
+        block_number: block.number,
+        from: "0x1111111111111111111111111111111111111111".to_string(),
+        to: Some("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string()),
+        status: Some(1),
+        gas_used: Some(50000),
+        cumulative_gas_used: 50000,
+        effective_gas_price: None,
+        contract_address: None,
+        logs,
+        deployed_code: None,
+    });
+    block
+}
+
+/// A fungible token transfer decoded from a log
+#[derive(Clone, Debug)]
+pub struct TokenTransfer {
+    pub transaction_hash: String,
+    pub log_index: u64,
+    /// Address of the token contract that emitted the event
+    pub token: String,
+    pub from: String,
+    pub to: String,
+    /// Amount in the token's smallest unit, as a decimal string
+    pub amount: String,
+}
+
+/// An ERC-721 or ERC-1155 transfer decoded from a log
+#[derive(Clone, Debug)]
+pub struct NftTransfer {
+    pub transaction_hash: String,
+    pub log_index: u64,
+    /// Position within an ERC-1155 batch transfer, 0 otherwise
+    pub batch_index: u64,
+    /// Address of the token contract that emitted the event
+    pub token: String,
+    /// `erc721` or `erc1155`
+    pub standard: &'static str,
+    /// Account that performed an ERC-1155 transfer
+    pub operator: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub token_id: String,
+    /// Number of tokens transferred, always 1 for ERC-721
+    pub amount: String,
+}
+
+/// Extract the address from an indexed address topic (left-padded to 32 bytes)
+fn topic_to_address(topic: &str) -> Option<String> {
+    let hex = topic.strip_prefix("0x")?;
+    if hex.len() != 64 || !hex[..24].bytes().all(|b| b == b'0') {
+        return None;
+    }
+    Some(format!("0x{}", hex[24..].to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_padded_address_topics() {
+        let topic = "0x000000000000000000000000AbCdEf0123456789abcdef0123456789ABCDEF01";
+        assert_eq!(topic_to_address(topic).as_deref(), Some("0xabcdef0123456789abcdef0123456789abcdef01"));
+    }
+
+    #[test]
+    fn rejects_topics_that_are_not_addresses() {
+        // Non-zero padding, wrong length, missing prefix
+        assert_eq!(topic_to_address("0x100000000000000000000000abcdef0123456789abcdef0123456789abcdef01"), None);
+        assert_eq!(topic_to_address("0x000000000000000000000000abcdef0123456789abcdef0123456789abcdef"), None);
+        assert_eq!(topic_to_address("000000000000000000000000abcdef0123456789abcdef0123456789abcdef01"), None);
+    }
+}
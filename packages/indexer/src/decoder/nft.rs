@@ -0,0 +1,211 @@
+use ethers::types::U256;
+
+use crate::decoder::{topic_to_address, NftTransfer, TRANSFER_TOPIC};
+use crate::models::{Block, Log};
+
+/// `keccak256("TransferSingle(address,address,address,uint256,uint256)")`
+const TRANSFER_SINGLE_TOPIC: &str = "0xc3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62";
+
+/// `keccak256("TransferBatch(address,address,address,uint256[],uint256[])")`
+const TRANSFER_BATCH_TOPIC: &str = "0x4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb";
+
+/// Decode ERC-721 `Transfer` and ERC-1155 `TransferSingle`/`TransferBatch` events in a
+/// block's receipts. A batch transfer yields one row per token id.
+pub fn decode_nft_transfers(block: &Block) -> Vec<NftTransfer> {
+    let mut transfers = Vec::new();
+
+    for (receipt, log_index, log) in block.indexed_logs() {
+        let Some(topic0) = log.topics.first() else {
+            continue;
+        };
+
+        let decoded = if topic0.eq_ignore_ascii_case(TRANSFER_TOPIC) {
+            decode_erc721_transfer(log)
+        } else if topic0.eq_ignore_ascii_case(TRANSFER_SINGLE_TOPIC) {
+            decode_transfer_single(log)
+        } else if topic0.eq_ignore_ascii_case(TRANSFER_BATCH_TOPIC) {
+            decode_transfer_batch(log)
+        } else {
+            None
+        };
+
+        for (batch_index, mut transfer) in decoded.into_iter().flatten().enumerate() {
+            transfer.transaction_hash = receipt.transaction_hash.clone();
+            transfer.log_index = log_index;
+            transfer.batch_index = batch_index as u64;
+            transfers.push(transfer);
+        }
+    }
+
+    transfers
+}
+
+/// `Transfer(address indexed from, address indexed to, uint256 indexed tokenId)`;
+/// unlike ERC-20 the token id is indexed, so there are four topics and no data
+fn decode_erc721_transfer(log: &Log) -> Option<Vec<NftTransfer>> {
+    if log.topics.len() != 4 {
+        return None;
+    }
+
+    let token_id = U256::from_str_radix(log.topics[3].strip_prefix("0x")?, 16).ok()?;
+    Some(vec![nft_transfer(log, "erc721", None, &log.topics[1], &log.topics[2], token_id, U256::one())?])
+}
+
+/// `TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)`
+fn decode_transfer_single(log: &Log) -> Option<Vec<NftTransfer>> {
+    if log.topics.len() != 4 {
+        return None;
+    }
+
+    let data = hex::decode(log.data.strip_prefix("0x")?).ok()?;
+    let token_id = word(&data, 0)?;
+    let amount = word(&data, 32)?;
+    Some(vec![nft_transfer(log, "erc1155", Some(&log.topics[1]), &log.topics[2], &log.topics[3], token_id, amount)?])
+}
+
+/// `TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)`
+fn decode_transfer_batch(log: &Log) -> Option<Vec<NftTransfer>> {
+    if log.topics.len() != 4 {
+        return None;
+    }
+
+    let data = hex::decode(log.data.strip_prefix("0x")?).ok()?;
+    let ids = array(&data, 0)?;
+    let values = array(&data, 32)?;
+    if ids.len() != values.len() {
+        return None;
+    }
+
+    ids.into_iter()
+        .zip(values)
+        .map(|(token_id, amount)| {
+            nft_transfer(log, "erc1155", Some(&log.topics[1]), &log.topics[2], &log.topics[3], token_id, amount)
+        })
+        .collect()
+}
+
+/// Build a transfer; the position fields are filled in by the caller
+fn nft_transfer(
+    log: &Log,
+    standard: &'static str,
+    operator: Option<&str>,
+    from: &str,
+    to: &str,
+    token_id: U256,
+    amount: U256,
+) -> Option<NftTransfer> {
+    Some(NftTransfer {
+        transaction_hash: String::new(),
+        log_index: 0,
+        batch_index: 0,
+        token: log.address.to_lowercase(),
+        standard,
+        operator: match operator {
+            Some(operator) => Some(topic_to_address(operator)?),
+            None => None,
+        },
+        from: topic_to_address(from)?,
+        to: topic_to_address(to)?,
+        token_id: token_id.to_string(),
+        amount: amount.to_string(),
+    })
+}
+
+/// Read the ABI word at a byte offset
+fn word(data: &[u8], offset: usize) -> Option<U256> {
+    let end = offset.checked_add(32)?;
+    data.get(offset..end).map(U256::from_big_endian)
+}
+
+/// Read a dynamic `uint256[]` whose head (offset to the array) is at `head`
+fn array(data: &[u8], head: usize) -> Option<Vec<U256>> {
+    let start = usize::try_from(word(data, head)?).ok()?;
+    let len = usize::try_from(word(data, start)?).ok()?;
+
+    // Reject lengths the data can't hold before allocating
+    if len > data.len() / 32 {
+        return None;
+    }
+
+    (0..len).map(|i| word(data, start + 32 + i * 32)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::block_with_logs;
+
+    const OPERATOR: &str = "0x0000000000000000000000003333333333333333333333333333333333333333";
+    const FROM: &str = "0x0000000000000000000000001111111111111111111111111111111111111111";
+    const TO: &str = "0x0000000000000000000000002222222222222222222222222222222222222222";
+
+    fn log(topics: &[&str], words: &[u64], log_index: u64) -> Log {
+        Log {
+            address: "0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string(),
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+            data: format!("0x{}", words.iter().map(|word| format!("{:064x}", word)).collect::<String>()),
+            log_index: Some(log_index),
+        }
+    }
+
+    #[test]
+    fn decodes_erc721_transfers() {
+        let token_id = format!("0x{:064x}", 42u64);
+        let block = block_with_logs(vec![log(&[TRANSFER_TOPIC, FROM, TO, &token_id], &[], 3)]);
+
+        let transfers = decode_nft_transfers(&block);
+        assert_eq!(transfers.len(), 1);
+        let transfer = &transfers[0];
+        assert_eq!((transfer.transaction_hash.as_str(), transfer.log_index, transfer.batch_index), ("0xtx", 3, 0));
+        assert_eq!(transfer.token, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        assert_eq!(transfer.standard, "erc721");
+        assert_eq!(transfer.operator, None);
+        assert_eq!(transfer.from, "0x1111111111111111111111111111111111111111");
+        assert_eq!(transfer.to, "0x2222222222222222222222222222222222222222");
+        assert_eq!((transfer.token_id.as_str(), transfer.amount.as_str()), ("42", "1"));
+    }
+
+    #[test]
+    fn decodes_erc1155_single_transfers() {
+        let block = block_with_logs(vec![log(&[TRANSFER_SINGLE_TOPIC, OPERATOR, FROM, TO], &[7, 250], 0)]);
+
+        let transfers = decode_nft_transfers(&block);
+        assert_eq!(transfers.len(), 1);
+        let transfer = &transfers[0];
+        assert_eq!(transfer.standard, "erc1155");
+        assert_eq!(transfer.operator.as_deref(), Some("0x3333333333333333333333333333333333333333"));
+        assert_eq!(transfer.from, "0x1111111111111111111111111111111111111111");
+        assert_eq!(transfer.to, "0x2222222222222222222222222222222222222222");
+        assert_eq!((transfer.token_id.as_str(), transfer.amount.as_str()), ("7", "250"));
+    }
+
+    #[test]
+    fn decodes_erc1155_batch_transfers_into_one_row_per_token() {
+        // Heads point at the ids (0x40) and values (0xa0) arrays, each of length 2
+        let block = block_with_logs(vec![log(&[TRANSFER_BATCH_TOPIC, OPERATOR, FROM, TO], &[0x40, 0xa0, 2, 1, 2, 2, 10, 20], 5)]);
+
+        let transfers = decode_nft_transfers(&block);
+        let rows: Vec<_> = transfers.iter()
+            .map(|transfer| (transfer.log_index, transfer.batch_index, transfer.token_id.as_str(), transfer.amount.as_str()))
+            .collect();
+        assert_eq!(rows, vec![(5, 0, "1", "10"), (5, 1, "2", "20")]);
+    }
+
+    #[test]
+    fn skips_malformed_and_unrelated_logs() {
+        let block = block_with_logs(vec![
+            // ERC-20 transfers have three topics
+            log(&[TRANSFER_TOPIC, FROM, TO], &[1], 0),
+            // Truncated TransferSingle data
+            log(&[TRANSFER_SINGLE_TOPIC, OPERATOR, FROM, TO], &[7], 1),
+            // Batch arrays of different lengths
+            log(&[TRANSFER_BATCH_TOPIC, OPERATOR, FROM, TO], &[0x40, 0x80, 1, 1, 0], 2),
+            // Batch length the data can't hold
+            log(&[TRANSFER_BATCH_TOPIC, OPERATOR, FROM, TO], &[0x40, 0x40, u64::MAX], 3),
+            // No topics
+            log(&[], &[], 4),
+        ]);
+
+        assert!(decode_nft_transfers(&block).is_empty());
+    }
+}
@@ -1,11 +1,13 @@
 use anyhow::{Result, Context};
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tokio::time::Duration;
 use tracing::{error, info};
-use ethers::providers::{Provider, Http, Middleware};
+use ethers::providers::Middleware;
 
 mod config;
 mod db;
+mod decoder;
 mod models;
 mod sync;
 mod utils;
@@ -13,7 +15,7 @@ mod utils;
 /// Helper function to get the latest block number from the chain
 async fn historic_sync_get_latest_block(config: &Config) -> Result<u64> {
     // Create a temporary HTTP provider
-    let provider = Provider::<Http>::try_from(config.http_provider_url.clone())
+    let provider = rpc::http_provider(&config.http_provider_url)
         .context("Failed to create HTTP provider")?;
         
     // Fetch the latest block number
@@ -24,8 +26,18 @@ async fn historic_sync_get_latest_block(config: &Config) -> Result<u64> {
 }
 
 use config::Config;
-use db::Database;
-use sync::{HistoricSync, LiveSync, SyncManager};
+use db::{BlockDedup, Database, WriteThrottle};
+use models::{CommitOrder, Finality, TagRules};
+use sync::{ChainStatusMonitor, FinalityTracker, GapRepairer, GasRollupWorker, HistoricSync, JournalWriter, LiveSync, MaintenanceWorker, NameResolver, RetentionWorker, SloEvaluator, SyncAlertMonitor, SyncCheckpointer, SyncManager, TokenMetadataWorker, TopContractsWorker};
+#[cfg(feature = "remote-sync")]
+use sync::RemoteSync;
+#[cfg(feature = "warehouse")]
+use sync::WarehousePublisher;
+use utils::idle::IdleMode;
+use utils::journal::IngestJournal;
+use utils::rate_limit::RateLimiter;
+use utils::rpc;
+use utils::slo::SloTracker;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -36,12 +48,79 @@ async fn main() -> Result<()> {
     // Load configuration
     let config = Config::load().expect("Failed to load configuration");
     info!("Configuration loaded");
+    
+    // Authenticate to the RPC endpoints, trust their CA and reach them through the proxy
+    // before the first connection
+    if config.rpc_authorization.is_some() || config.rpc_ca_cert.is_some() || config.rpc_tls_insecure || config.rpc_proxy.is_some() {
+        rpc::configure(&config)?;
+    }
+    if (config.rpc_ca_cert.is_some() || config.rpc_tls_insecure)
+        && config.ws_provider_urls.iter().any(|url| url.starts_with("wss://"))
+    {
+        tracing::warn!("RPC_CA_CERT and RPC_TLS_INSECURE don't apply to WebSocket endpoints; wss:// endpoints must have certificates trusted by default, or the indexer falls back to HTTP");
+    }
+    if config.rpc_proxy.is_some() && !config.ws_provider_urls.is_empty() {
+        tracing::warn!("RPC_PROXY doesn't apply to WebSocket endpoints, they are connected to directly; the indexer falls back to HTTP if they can't be reached");
+    }
+
+    // Start the metrics exporter if configured
+    if let Some(metrics_addr) = &config.metrics_addr {
+        #[cfg(feature = "prometheus")]
+        utils::metrics::init_metrics(metrics_addr)?;
+        #[cfg(not(feature = "prometheus"))]
+        tracing::warn!("METRICS_ADDR is {} but the indexer was built without the `prometheus` feature, metrics are not served", metrics_addr);
+    }
 
     // Initialize database connection
-    let db = Database::new(&config.database_url).await?
-        .migrate()
-        .await?;
-    info!("Database connection established and migrations applied");
+    let mut db = Database::new(&config.database_url).await?
+        .with_slow_query_threshold(config.slow_query_threshold_ms)
+        .with_chain_profile(config.chain_profile);
+    if let Some(tag_rules_file) = &config.tag_rules_file {
+        db = db.with_tag_rules(TagRules::from_file(tag_rules_file)?);
+    }
+    if config.write_throttle_latency_ms > 0 || config.write_throttle_max_replication_lag.is_some() {
+        let mut throttle = WriteThrottle::new(config.write_throttle_latency_ms, config.write_throttle_max_delay_ms);
+        if let Some(max_lag) = config.write_throttle_max_replication_lag {
+            throttle = throttle.with_max_replication_lag(max_lag);
+        }
+        db = db.with_write_throttle(throttle);
+    }
+
+    if config.idle_after > 0 {
+        info!("Going idle after {}s without transactions, stretching intervals {}x",
+            config.idle_after, config.idle_interval_multiplier);
+        db = db.with_idle_mode(IdleMode::new(config.idle_after, config.idle_interval_multiplier));
+    }
+
+    let slo_tracker = (config.slo_evaluation_interval > 0).then(|| Arc::new(
+        SloTracker::new(config.slo_window)
+            .with_persist_latency(config.slo_persist_latency_ms, config.slo_persist_target)
+            .with_sync_lag(config.slo_max_lag_blocks, config.slo_lag_target)
+    ));
+    if let Some(slo_tracker) = &slo_tracker {
+        db = db.with_slo_tracker(slo_tracker.clone());
+    }
+    if config.block_dedup_cache_size > 0 || config.block_claim_ttl > 0 {
+        let mut block_dedup = BlockDedup::new(config.block_dedup_cache_size);
+        if config.block_claim_ttl > 0 {
+            block_dedup = block_dedup.with_claims(config.instance_id.clone(), config.block_claim_ttl);
+        }
+        db = db.with_block_dedup(block_dedup);
+    }
+    let journal = (config.ingest_journal_size > 0).then(|| Arc::new(IngestJournal::new()));
+    if let Some(journal) = &journal {
+        db = db.with_journal(journal.clone());
+    }
+    if config.dry_run {
+        db = db.with_dry_run();
+    }
+    let db = if config.skip_migrations {
+        db.skip_migrations().await?
+    } else {
+        db.migrate().await?
+    };
+    db.check_schema_drift(config.strict_schema_check).await?;
+    info!("Database connection established and schema verified");
 
     // Log configuration settings
     utils::config_logger::log_config(&config);
@@ -49,22 +128,54 @@ async fn main() -> Result<()> {
     // Create sync components
     let db_arc = Arc::new(db);
     
+    #[cfg(not(feature = "warehouse"))]
+    if config.warehouse_url.is_some() {
+        anyhow::bail!("WAREHOUSE_URL is set but the indexer was built without the `warehouse` feature");
+    }
+    
+    // Mirror another explorer instance instead of the chain
+    #[cfg(not(feature = "remote-sync"))]
+    if config.remote_explorer_url.is_some() {
+        anyhow::bail!("REMOTE_EXPLORER_URL is set but the indexer was built without the `remote-sync` feature");
+    }
+    #[cfg(feature = "remote-sync")]
+    if let Some(remote_explorer_url) = &config.remote_explorer_url {
+        let remote_sync = RemoteSync::new(remote_explorer_url, config.remote_explorer_api_key.as_deref(), db_arc.clone())?
+            .with_start_block(config.start_block)
+            .with_concurrency(config.max_concurrent_requests)
+            .with_retry_settings(config.retry_delay, config.max_retries);
+        
+        match remote_sync.start(config.db_workers).await {
+            Ok(_) => info!("Indexer shutdown gracefully"),
+            Err(e) => error!("Remote sync failed: {}", e),
+        }
+        return Ok(());
+    }
+    
     // Get the latest block number from the chain
     let current_chain_tip = historic_sync_get_latest_block(&config).await?;
     info!("Current chain tip: {}", current_chain_tip);
     
+    // Resume after the blocks stored contiguously, below any gap left by out-of-order writes;
+    // before the first checkpoint, after the highest stored block
+    let resume_block = match db_arc.get_sync_checkpoint().await? {
+        Some(checkpoint) => {
+            info!("Found sync checkpoint: contiguous up to block {:?}, highest stored block {:?}, {} ranges pending",
+                  checkpoint.contiguous_block, checkpoint.highest_block, checkpoint.pending_ranges.len());
+            checkpoint.contiguous_block
+        }
+        None => db_arc.get_latest_block_number().await?,
+    };
+    
     // Determine start block based on configuration and DB state
-    let latest_synced_block = match db_arc.get_latest_block_number().await? {
+    let latest_synced_block = match resume_block {
         Some(block_number) => {
             info!("Found latest synced block in database: {}", block_number);
             
             // If blocks_from_tip is set, calculate starting point
             if let Some(blocks_from_tip) = config.blocks_from_tip {
-                let calculated_start = if current_chain_tip > blocks_from_tip {
-                    current_chain_tip - blocks_from_tip
-                } else {
-                    0 // If blocks_from_tip is larger than chain length, start from 0
-                };
+                // If blocks_from_tip is larger than chain length, start from 0
+                let calculated_start = current_chain_tip.saturating_sub(blocks_from_tip);
                 
                 // Use the max of config.start_block, calculated_start, and block_number
                 let start = calculated_start.max(config.start_block).max(block_number);
@@ -85,11 +196,7 @@ async fn main() -> Result<()> {
         None => {
             // No blocks in DB yet
             if let Some(blocks_from_tip) = config.blocks_from_tip {
-                let calculated_start = if current_chain_tip > blocks_from_tip {
-                    current_chain_tip - blocks_from_tip
-                } else {
-                    0
-                };
+                let calculated_start = current_chain_tip.saturating_sub(blocks_from_tip);
                 
                 let start = calculated_start.max(config.start_block);
                 info!("No blocks found in database, starting from block {} (blocks_from_tip: {}, chain tip: {})", 
@@ -104,6 +211,15 @@ async fn main() -> Result<()> {
     
     let sync_state = Arc::new(Mutex::new(sync::SyncState::new(latest_synced_block)));
     
+    // One request budget for every block fetcher, so historic sync and gap repair together
+    // stay under the provider's rate limit
+    let rpc_rate_limiter = (config.rpc_rate_limit > 0).then(|| Arc::new(RateLimiter::new(config.rpc_rate_limit)));
+    
+    // One reorder buffer for the historic and live sync, so blocks are persisted in order
+    // across the hand-over between them
+    let commit_order = config.ordered_commits
+        .then(|| Arc::new(CommitOrder::new(Duration::from_secs(config.ordered_commit_max_wait))));
+    
     let mut historic_sync = HistoricSync::new(
         config.http_provider_url.clone(),
         Some(config.ws_provider_url.clone()),
@@ -118,7 +234,15 @@ async fn main() -> Result<()> {
     historic_sync = historic_sync
         .with_rpc_batch_size(config.rpc_batch_size)
         .with_retry_settings(config.retry_delay, config.max_retries)
-        .with_max_concurrent_batches(config.max_concurrent_batches);
+        .with_max_concurrent_batches(config.max_concurrent_batches)
+        .with_full_transactions(config.fetch_full_transactions)
+        .with_receipts(config.fetch_receipts)
+        .with_rate_limiter(rpc_rate_limiter.clone())
+        .with_commit_order(commit_order.clone())
+        .with_end_block(config.end_block);
+    if config.ws_provider_urls.len() > 1 {
+        historic_sync = historic_sync.with_ws_endpoints(config.ws_provider_urls.clone());
+    }
         
     // Start the database processor workers
     historic_sync.start_processor(config.db_workers).await;
@@ -131,10 +255,162 @@ async fn main() -> Result<()> {
     )
     .with_polling_interval(2) // 2 seconds polling interval for HTTP fallback
     .with_max_parallel_blocks(20) // Process up to 20 blocks in parallel when catching up
-    .with_block_queue_size(config.block_queue_size); // Use the same queue size as historic sync
+    .with_block_queue_size(config.block_queue_size) // Use the same queue size as historic sync
+    .with_full_transactions(config.fetch_full_transactions)
+    .with_receipts(config.fetch_receipts)
+    .with_head_source(config.chain_profile.head_source)
+    .with_commit_order(commit_order);
+    let live_sync = if config.ws_head_timeout_blocks > 0 {
+        live_sync.with_head_timeout(Duration::from_millis(config.chain_profile.block_time_ms * config.ws_head_timeout_blocks))
+    } else {
+        live_sync
+    };
+    let live_sync = if config.ws_provider_urls.len() > 1 {
+        live_sync.with_ws_endpoints(config.ws_provider_urls.clone())
+    } else {
+        live_sync
+    };
+
+    // Background workers only write to the database, so a dry run only syncs blocks
+    if config.dry_run {
+        info!("Dry run: not starting background workers");
+    } else {
+        // Checkpoint how far the stored blocks are contiguous, to resume from there
+        SyncCheckpointer::new(db_arc.clone(), sync_state.clone())
+            .with_interval(config.sync_checkpoint_interval)
+            .start();
+
+        // Track finalized/safe watermarks alongside syncing, unless the chain has no finality signal
+        if config.chain_profile.finality == Finality::None {
+            info!("Chain profile has no finality, not tracking finalized/safe watermarks");
+        } else if config.finality_poll_interval > 0 {
+            FinalityTracker::new(&config.http_provider_url, db_arc.clone())?
+                .with_poll_interval(config.finality_poll_interval)
+                .with_finality(config.chain_profile.finality)
+                .start();
+        }
+
+        // Record whether the chain or the indexer is stuck
+        if config.chain_stall_threshold > 0 {
+            ChainStatusMonitor::new(&config.http_provider_url, db_arc.clone(), sync_state.clone())?
+                .with_stall_threshold(config.chain_stall_threshold)
+                .start();
+        }
+
+        // Alert operators when blocks stop being persisted or the indexer falls behind
+        if config.alert_stall_seconds > 0 || config.alert_max_lag_blocks > 0 {
+            SyncAlertMonitor::new(&config.http_provider_url, db_arc.clone(), sync_state.clone())?
+                .with_poll_interval(config.alert_check_interval)
+                .with_stall_threshold(config.alert_stall_seconds)
+                .with_max_lag(config.alert_max_lag_blocks)
+                .with_webhook(config.alert_webhook_url.clone())
+                .with_pagerduty(config.alert_pagerduty_routing_key.clone(), &config.alert_pagerduty_url)
+                .start();
+        }
+
+        // Persist notable ingestion events for debugging after logs have rotated
+        if let Some(journal) = journal {
+            JournalWriter::new(db_arc.clone(), journal)
+                .with_size(config.ingest_journal_size)
+                .start();
+        }
+
+        // Track latency objectives and alert on fast error budget burn
+        if let Some(slo_tracker) = slo_tracker {
+            SloEvaluator::new(&config.http_provider_url, db_arc.clone(), sync_state.clone(), slo_tracker)?
+                .with_evaluation_interval(config.slo_evaluation_interval)
+                .with_burn_rate_threshold(config.slo_burn_rate_threshold)
+                .start();
+        }
+
+        // Read display metadata of tokens seen in transfers
+        if config.token_metadata_interval > 0 && config.fetch_receipts {
+            TokenMetadataWorker::new(&config.http_provider_url, db_arc.clone())?
+                .with_poll_interval(config.token_metadata_interval)
+                .start();
+        }
+
+        // Aggregate gas usage per hour and day for charts
+        if config.gas_rollup_interval > 0 {
+            GasRollupWorker::new(db_arc.clone())
+                .with_poll_interval(config.gas_rollup_interval)
+                .start();
+        }
+
+        // Rank the most called contracts over the last hour, day and week
+        if config.top_contracts_interval > 0 {
+            TopContractsWorker::new(db_arc.clone())
+                .with_poll_interval(config.top_contracts_interval)
+                .with_size(config.top_contracts_size)
+                .start();
+        }
+
+        // Prune the transactions, receipts and logs of old blocks, keeping the blocks
+        if config.retention_days > 0 {
+            RetentionWorker::new(db_arc.clone(), config.retention_days)
+                .with_poll_interval(config.retention_interval)
+                .start();
+        }
+
+        // ANALYZE tables with stale statistics and refresh materialized views
+        if config.maintenance_interval > 0 {
+            MaintenanceWorker::new(db_arc.clone())
+                .with_poll_interval(config.maintenance_interval)
+                .with_analyze_threshold(config.maintenance_analyze_threshold)
+                .with_materialized_views(config.maintenance_materialized_views.clone())
+                .start();
+        }
+
+        // Resolve names of addresses seen in transactions
+        if let Some(registry_address) = &config.name_registry_address {
+            NameResolver::new(&config.http_provider_url, registry_address, db_arc.clone())?
+                .with_refresh_interval(config.name_refresh_interval)
+                .start();
+        }
+
+        // Ship finalized blocks to the analytics warehouse; without a finalized watermark, up to
+        // the latest stored block
+        #[cfg(feature = "warehouse")]
+        if let Some(warehouse_url) = &config.warehouse_url {
+            let publisher = WarehousePublisher::new(
+                warehouse_url,
+                config.warehouse_user.as_deref(),
+                config.warehouse_password.as_deref(),
+                db_arc.clone(),
+            )?
+            .with_database(&config.warehouse_database)
+            .with_poll_interval(config.warehouse_interval)
+            .with_batch_size(config.warehouse_batch_size);
+            if config.chain_profile.finality == Finality::None || config.finality_poll_interval == 0 {
+                publisher.without_finality().start();
+            } else {
+                publisher.start();
+            }
+        }
+
+        // Periodically scan for and repair missing blocks
+        if config.gap_repair_interval > 0 {
+            GapRepairer::new(config.ws_provider_url.clone(), db_arc.clone(), sync_state.clone())
+                .with_ws_endpoints(config.ws_provider_urls.clone())
+                .with_http_fallback(config.http_provider_url.clone())
+                .with_scan_interval(config.gap_repair_interval)
+                .with_rpc_batch_size(config.rpc_batch_size)
+                .with_retry_settings(config.retry_delay, config.max_retries)
+                .with_full_transactions(config.fetch_full_transactions)
+                .with_receipts(config.fetch_receipts)
+                .with_rate_limiter(rpc_rate_limiter.clone())
+                .start()
+                .await;
+        }
+    }
 
     // Create sync manager
     let sync_manager = SyncManager::new(historic_sync, live_sync);
+    let sync_manager = if config.end_block.is_some() {
+        sync_manager.without_live_sync()
+    } else {
+        sync_manager
+    };
 
     // Start syncing
     match sync_manager.start().await {
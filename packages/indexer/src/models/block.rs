@@ -1,6 +1,8 @@
 use ethers::types::U256;
 use serde::{Deserialize, Serialize};
 
+use crate::models::{Log, Receipt};
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Block {
     pub number: u64,
@@ -15,11 +17,15 @@ pub struct Block {
     pub base_fee_per_gas: Option<u64>,
     pub extra_data: String,
     pub miner: String,
-    pub difficulty: U256,
+    /// None on chains without proof-of-work difficulty
+    pub difficulty: Option<U256>,
     pub total_difficulty: Option<U256>,
     pub size: u64,
     pub transactions: Vec<Transaction>,
     pub transaction_count: u64,
+    /// Transaction receipts, only populated when receipt fetching is enabled
+    #[serde(default)]
+    pub receipts: Vec<Receipt>,
 }
 
 impl Block {
@@ -39,15 +45,71 @@ impl Block {
             base_fee_per_gas: Some(1000000000),
             extra_data: "0x".to_string(),
             miner: "0xminer".to_string(),
-            difficulty: U256::from(2),
+            difficulty: Some(U256::from(2)),
             total_difficulty: Some(U256::from(100)),
             size: 1000,
             transactions: vec![],
             transaction_count: 0,
+            receipts: vec![],
         }
     }
 }
 
+impl Block {
+    /// Build a block model from an RPC block and its already converted transactions.
+    /// Returns None for pending blocks, which have no number yet.
+    pub fn from_eth_block<TX>(eth_block: &ethers::types::Block<TX>, transactions: Vec<Transaction>) -> Option<Self> {
+        let number = eth_block.number?.as_u64();
+
+        Some(Self {
+            number,
+            hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
+            parent_hash: format!("{:?}", eth_block.parent_hash),
+            timestamp: eth_block.timestamp.as_u64(),
+            transactions_root: format!("{:?}", eth_block.transactions_root),
+            state_root: format!("{:?}", eth_block.state_root),
+            receipts_root: format!("{:?}", eth_block.receipts_root),
+            gas_used: eth_block.gas_used.as_u64(),
+            gas_limit: eth_block.gas_limit.as_u64(),
+            base_fee_per_gas: eth_block.base_fee_per_gas.map(|fee| fee.as_u64()),
+            extra_data: format!("0x{}", hex::encode(&eth_block.extra_data)),
+            miner: format!("{:?}", eth_block.author.unwrap_or_default()),
+            difficulty: Some(eth_block.difficulty),
+            total_difficulty: eth_block.total_difficulty,
+            size: eth_block.size.unwrap_or_default().as_u64(),
+            transaction_count: transactions.len() as u64,
+            transactions,
+            receipts: Vec::new(),
+        })
+    }
+
+    /// Attach the receipts returned by `eth_getBlockReceipts` for this block.
+    /// Returns false, leaving the block unchanged, if any receipt belongs to a different
+    /// block hash (the block was reorged between the two requests).
+    pub fn attach_receipts(&mut self, receipts: &[ethers::types::TransactionReceipt]) -> bool {
+        let receipts = receipts.iter()
+            .filter_map(Receipt::from_eth_receipt)
+            .collect::<Vec<_>>();
+
+        if receipts.iter().any(|receipt| receipt.block_hash != self.hash) {
+            return false;
+        }
+
+        self.receipts = receipts;
+        true
+    }
+
+    /// The logs of the block's receipts in order, with their receipt and index in the block.
+    /// Logs the node sent without `logIndex` are numbered by their position in the block, as
+    /// the index is unique per block, not per receipt.
+    pub fn indexed_logs(&self) -> impl Iterator<Item = (&Receipt, u64, &Log)> {
+        self.receipts.iter()
+            .flat_map(|receipt| receipt.logs.iter().map(move |log| (receipt, log)))
+            .enumerate()
+            .map(|(position, (receipt, log))| (receipt, log.log_index.unwrap_or(position as u64), log))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Transaction {
     pub hash: String,
@@ -63,8 +125,48 @@ pub struct Transaction {
     pub block_number: u64,
 }
 
+impl From<ethers::types::Transaction> for Transaction {
+    /// Convert a full RPC transaction (from `eth_getBlockByNumber(.., true)`)
+    fn from(tx: ethers::types::Transaction) -> Self {
+        Self {
+            hash: format!("{:?}", tx.hash),
+            from: Some(format!("{:?}", tx.from)),
+            to: tx.to.map(|to| format!("{:?}", to)),
+            value: tx.value.to_string(),
+            gas: u64::try_from(tx.gas).unwrap_or(u64::MAX),
+            gas_price: tx.gas_price.and_then(|price| u64::try_from(price).ok()),
+            input: format!("0x{}", hex::encode(&tx.input)),
+            nonce: u64::try_from(tx.nonce).unwrap_or(u64::MAX),
+            transaction_index: tx.transaction_index.unwrap_or_default().as_u64(),
+            block_hash: format!("{:?}", tx.block_hash.unwrap_or_default()),
+            block_number: tx.block_number.unwrap_or_default().as_u64(),
+        }
+    }
+}
+
+impl Transaction {
+    /// A transaction known only by its hash (from `eth_getBlockByNumber(.., false)`).
+    /// The other fields need the full transaction, so they are left empty.
+    pub fn from_hash<TX>(hash: ethers::types::H256, transaction_index: usize, eth_block: &ethers::types::Block<TX>) -> Self {
+        Self {
+            hash: format!("{:?}", hash),
+            from: None,
+            to: None,
+            value: "0".to_string(),
+            gas: 0,
+            gas_price: None,
+            input: "0x".to_string(),
+            nonce: 0,
+            transaction_index: transaction_index as u64,
+            block_hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
+            block_number: eth_block.number.unwrap_or_default().as_u64(),
+        }
+    }
+}
+
 // Block with transaction hashes only (used in websocket streaming)
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct BlockHeader {
     pub number: u64,
     pub hash: String,
@@ -78,9 +180,52 @@ pub struct BlockHeader {
     pub base_fee_per_gas: Option<u64>,
     pub extra_data: String,
     pub miner: String,
-    pub difficulty: U256,
+    /// None on chains without proof-of-work difficulty
+    pub difficulty: Option<U256>,
     pub total_difficulty: Option<U256>,
     pub size: u64,
     pub transactions: Vec<String>, // Just transaction hashes
     pub transaction_count: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(transaction_index: u64, log_indexes: &[Option<u64>]) -> Receipt {
+        Receipt {
+            transaction_hash: format!("0xtx{}", transaction_index),
+            transaction_index,
+            block_hash: "0xhash1".to_string(),
+            block_number: 1,
+            from: "0xfrom".to_string(),
+            to: None,
+            status: Some(1),
+            gas_used: Some(21000),
+            cumulative_gas_used: 21000,
+            effective_gas_price: None,
+            contract_address: None,
+            logs: log_indexes.iter()
+                .map(|&log_index| Log {
+                    address: "0xtoken".to_string(),
+                    topics: vec![],
+                    data: "0x".to_string(),
+                    log_index,
+                })
+                .collect(),
+            deployed_code: None,
+        }
+    }
+
+    #[test]
+    fn indexed_logs_number_missing_indexes_across_receipts() {
+        let mut block = Block::dummy(1);
+        block.receipts = vec![receipt(0, &[None, None]), receipt(1, &[None]), receipt(2, &[Some(9)])];
+
+        let logs = block.indexed_logs()
+            .map(|(receipt, log_index, _)| (receipt.transaction_index, log_index))
+            .collect::<Vec<_>>();
+
+        assert_eq!(logs, vec![(0, 0), (0, 1), (1, 2), (2, 9)]);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::models::Block;
+use crate::db::{JournalEntry, DEDUPLICATED_BLOCKS_METRIC};
+use crate::models::{Block, CommitOrder, PersistRetry};
 use crossbeam_queue::SegQueue;
 use std::sync::Arc;
 use tokio::sync::{Mutex, Semaphore};
@@ -123,6 +124,8 @@ pub struct BlockProcessor {
     queue: BlockQueue,
     /// Status mutex to control processing
     status: Arc<Mutex<ProcessorStatus>>,
+    /// Reorder buffer persisting blocks in ascending order, if enabled
+    commit_order: Option<Arc<CommitOrder>>,
 }
 
 impl BlockProcessor {
@@ -131,8 +134,15 @@ impl BlockProcessor {
         Self {
             queue,
             status: Arc::new(Mutex::new(ProcessorStatus::Stopped)),
+            commit_order: None,
         }
     }
+    
+    /// Persist blocks strictly in ascending order through a reorder buffer
+    pub fn with_commit_order(mut self, commit_order: Option<Arc<CommitOrder>>) -> Self {
+        self.commit_order = commit_order;
+        self
+    }
 
     /// Start the processor
     pub async fn start(&self, db: Arc<crate::db::Database>) {
@@ -146,10 +156,11 @@ impl BlockProcessor {
         // Clone necessary data for the worker task
         let queue = self.queue.clone_queue();
         let status_arc = Arc::clone(&self.status);
+        let commit_order = self.commit_order.clone();
         
         // Spawn a worker task
         tokio::spawn(async move {
-            Self::worker_loop(queue, db, status_arc).await;
+            Self::worker_loop(queue, db, status_arc, commit_order).await;
         });
     }
 
@@ -201,8 +212,140 @@ impl BlockProcessor {
         *self.status.lock().await
     }
 
+    /// Add a block to the reorder buffer, then persist the blocks that are ready in
+    /// ascending order. A block that fails to save goes back into the buffer, holding back
+    /// the blocks after it until it is retried with exponential backoff. After too many
+    /// attempts it is left to the gap repairer. The first failure is journaled, then a
+    /// summary at most once a minute while it keeps failing.
+    async fn commit_in_order(commit_order: &CommitOrder, db: &crate::db::Database, block: Option<Block>) {
+        let mut pending = commit_order.lock().await;
+        if let Some(block) = block {
+            pending.insert(block);
+        }
+        
+        if let Some((from, to)) = pending.skip_overdue() {
+            warn!("Gave up waiting for blocks {} to {}, persisting the blocks after them", from, to);
+            db.record_journal(
+                JournalEntry::new("block_processor", "ordered_commit_skipped")
+                    .with_block_number(from)
+                    .with_details(serde_json::json!({ "fromBlock": from, "toBlock": to }))
+            );
+        }
+        
+        while let Some(block) = pending.pop_ready() {
+            let block_number = block.number;
+            match Self::persist_block(db, block).await {
+                Ok(()) => pending.committed(block_number),
+                Err((block, error_str)) => {
+                    match pending.failed(block) {
+                        PersistRetry::Retrying { attempts, delay, report } => {
+                            warn!("Retrying block {} in {:?} (attempt {} failed)", block_number, delay, attempts);
+                            if report {
+                                let action = if attempts == 1 { "persist_failed" } else { "persist_retrying" };
+                                db.record_journal(
+                                    JournalEntry::new("block_processor", action)
+                                        .with_block_number(block_number)
+                                        .with_details(serde_json::json!({
+                                            "error": error_str,
+                                            "requeued": true,
+                                            "attempts": attempts,
+                                            "retryInMs": delay.as_millis() as u64,
+                                        }))
+                                );
+                            }
+                            break;
+                        }
+                        PersistRetry::GaveUp { attempts } => {
+                            error!(
+                                "Gave up on block {} after {} failed attempts, persisting the blocks after it; \
+                                 the gap repairer will fetch it again",
+                                block_number, attempts
+                            );
+                            db.record_journal(
+                                JournalEntry::new("block_processor", "persist_abandoned")
+                                    .with_block_number(block_number)
+                                    .with_details(serde_json::json!({ "error": error_str, "attempts": attempts }))
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Save a block unless it was already persisted. Returns the block and the error if the
+    /// save failed in a way worth retrying.
+    async fn persist_block(db: &crate::db::Database, block: Block) -> Result<(), (Block, String)> {
+        // Skip blocks already persisted, e.g. fetched by both historic and live sync
+        match db.check_duplicate_block(&block).await {
+            Ok(Some(reason)) => {
+                debug!("Skipping block {}: already persisted ({})", block.number, reason);
+                metrics::increment_counter!(DEDUPLICATED_BLOCKS_METRIC, "reason" => reason);
+                return Ok(());
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to check block {} for duplicates, saving it anyway: {}", block.number, e),
+        }
+        
+        // Back off while the database is under pressure
+        db.throttle_writes().await;
+        
+        // Process the block
+        let block_number = block.number; // Store block number for error reporting
+        // Try saving the block, with special handling for transaction serialization errors
+        match db.save_block(&block).await {
+            Ok(_) => {
+                debug!("Saved block {} to database", block_number);
+                db.remember_persisted_block(&block);
+                Ok(())
+            }
+            Err(e) => {
+                // Check for transaction serialization errors
+                let error_str = e.to_string();
+                if error_str.contains("could not access status of transaction") {
+                    error!("Transaction serialization error for block {}: {}", block_number, e);
+                    db.record_journal(
+                        JournalEntry::new("block_processor", "persist_fallback")
+                            .with_block_number(block_number)
+                            .with_details(serde_json::json!({ "error": error_str }))
+                    );
+                    
+                    // Create a version of the block with empty transactions as a fallback
+                    let mut fixed_block = block.clone();
+                    fixed_block.transactions = Vec::new();
+                    
+                    // Try to save the block without transactions
+                    match db.save_block(&fixed_block).await {
+                        Ok(_) => {
+                            warn!("Saved block {} with empty transactions as a fallback", block_number);
+                        }
+                        Err(retry_err) => {
+                            error!("Failed to save block {} even with empty transactions: {}", 
+                                block_number, retry_err);
+                            db.record_journal(
+                                JournalEntry::new("block_processor", "persist_failed")
+                                    .with_block_number(block_number)
+                                    .with_details(serde_json::json!({ "error": retry_err.to_string(), "requeued": false }))
+                            );
+                            // Don't requeue at this point - it's likely a fundamental issue
+                        }
+                    }
+                    Ok(())
+                } else {
+                    error!("Failed to save block {} to database: {}", block_number, e);
+                    Err((block, error_str))
+                }
+            }
+        }
+    }
+    
     /// Worker loop for processing blocks
-    async fn worker_loop(queue: BlockQueue, db: Arc<crate::db::Database>, status: Arc<Mutex<ProcessorStatus>>) {
+    async fn worker_loop(
+        queue: BlockQueue,
+        db: Arc<crate::db::Database>,
+        status: Arc<Mutex<ProcessorStatus>>,
+        commit_order: Option<Arc<CommitOrder>>,
+    ) {
         info!("Block processor worker started");
         
         let mut consecutive_empty = 0;
@@ -226,44 +369,30 @@ impl BlockProcessor {
                     if let Some(block) = queue.try_pop() {
                         consecutive_empty = 0;
                         
-                        // Process the block
+                        if let Some(commit_order) = &commit_order {
+                            Self::commit_in_order(commit_order, &db, Some(block)).await;
+                            continue;
+                        }
+                        
                         let block_number = block.number; // Store block number for error reporting
-                        // Try saving the block, with special handling for transaction serialization errors
-                        match db.save_block(&block).await {
-                            Ok(_) => {
-                                debug!("Saved block {} to database", block_number);
-                            }
-                            Err(e) => {
-                                // Check for transaction serialization errors
-                                let error_str = e.to_string();
-                                if error_str.contains("could not access status of transaction") {
-                                    error!("Transaction serialization error for block {}: {}", block_number, e);
-                                    
-                                    // Create a version of the block with empty transactions as a fallback
-                                    let mut fixed_block = block.clone();
-                                    fixed_block.transactions = Vec::new();
-                                    
-                                    // Try to save the block without transactions
-                                    match db.save_block(&fixed_block).await {
-                                        Ok(_) => {
-                                            warn!("Saved block {} with empty transactions as a fallback", block_number);
-                                        }
-                                        Err(retry_err) => {
-                                            error!("Failed to save block {} even with empty transactions: {}", 
-                                                block_number, retry_err);
-                                            // Don't requeue at this point - it's likely a fundamental issue
-                                        }
-                                    }
-                                } else {
-                                    error!("Failed to save block {} to database: {}", block_number, e);
-                                    // Re-push failed blocks to the queue for non-serialization errors
-                                    if !queue.try_push(block) {
-                                        error!("Could not requeue block {} due to full queue", block_number);
-                                    }
-                                }
+                        if let Err((block, error_str)) = Self::persist_block(&db, block).await {
+                            // Re-push failed blocks to the queue for non-serialization errors
+                            let requeued = queue.try_push(block);
+                            if !requeued {
+                                error!("Could not requeue block {} due to full queue", block_number);
                             }
+                            db.record_journal(
+                                JournalEntry::new("block_processor", "persist_failed")
+                                    .with_block_number(block_number)
+                                    .with_details(serde_json::json!({ "error": error_str, "requeued": requeued }))
+                            );
                         }
                     } else {
+                        // Blocks held back may be overdue, or waiting for a retry
+                        if let Some(commit_order) = &commit_order {
+                            Self::commit_in_order(commit_order, &db, None).await;
+                        }
+                        
                         consecutive_empty += 1;
                         if consecutive_empty >= 10 {
                             // If queue has been empty for a while, sleep a bit longer
@@ -281,6 +410,19 @@ impl BlockProcessor {
         
         // Process any remaining blocks before exiting
         info!("Processing remaining blocks before shutdown");
+        if let Some(commit_order) = &commit_order {
+            // Keep the order for what is left, but don't wait for missing blocks any more
+            let mut pending = commit_order.lock().await;
+            while let Some(block) = queue.try_pop() {
+                pending.insert(block);
+            }
+            for block in pending.drain() {
+                let block_number = block.number;
+                if let Err((_, error_str)) = Self::persist_block(&db, block).await {
+                    error!("Failed to save block {} before shutdown: {}", block_number, error_str);
+                }
+            }
+        }
         while let Some(block) = queue.try_pop() {
             let block_number = block.number; // Store block number for error reporting
             // Use the same error handling approach as in the main worker
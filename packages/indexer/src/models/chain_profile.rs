@@ -0,0 +1,254 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ethers::types::U256;
+use serde::Deserialize;
+
+/// Kind of chain being indexed, the preset a `ChainProfile` starts from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainKind {
+    /// Every header field is stored as reported by the node
+    #[default]
+    Ethereum,
+    /// Rollup-style chains such as RISE, which are not proof-of-work: difficulty and total
+    /// difficulty are stored as NULL whatever the node reports
+    Rollup,
+}
+
+impl ChainKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainKind::Ethereum => "ethereum",
+            ChainKind::Rollup => "rollup",
+        }
+    }
+}
+
+impl FromStr for ChainKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ethereum" => Ok(ChainKind::Ethereum),
+            "rollup" | "rise" => Ok(ChainKind::Rollup),
+            other => Err(format!("unknown chain profile '{}', expected ethereum, rise or rollup", other)),
+        }
+    }
+}
+
+/// How live sync learns about new blocks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadSource {
+    /// `eth_subscribe("newHeads")` over WebSocket, falling back to polling when it fails
+    #[default]
+    Subscription,
+    /// Poll `eth_blockNumber` over HTTP, for nodes without a usable `newHeads` subscription
+    Polling,
+}
+
+impl HeadSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HeadSource::Subscription => "subscription",
+            HeadSource::Polling => "polling",
+        }
+    }
+}
+
+impl FromStr for HeadSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "subscription" => Ok(HeadSource::Subscription),
+            "polling" => Ok(HeadSource::Polling),
+            other => Err(format!("unknown head source '{}', expected subscription or polling", other)),
+        }
+    }
+}
+
+/// How the `finalized` and `safe` watermarks are obtained
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Finality {
+    /// The node reports them through the `finalized` and `safe` block tags
+    #[default]
+    Tags,
+    /// Blocks are final once produced, so the latest block is both finalized and safe
+    Instant,
+    /// The chain has no finality signal, no watermarks are recorded
+    None,
+}
+
+impl Finality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Finality::Tags => "tags",
+            Finality::Instant => "instant",
+            Finality::None => "none",
+        }
+    }
+}
+
+impl FromStr for Finality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tags" => Ok(Finality::Tags),
+            "instant" => Ok(Finality::Instant),
+            "none" => Ok(Finality::None),
+            other => Err(format!("unknown finality '{}', expected tags, instant or none", other)),
+        }
+    }
+}
+
+/// Resolution of the block `timestamp` field reported by the node
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+}
+
+impl TimestampUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimestampUnit::Seconds => "seconds",
+            TimestampUnit::Milliseconds => "milliseconds",
+        }
+    }
+}
+
+impl FromStr for TimestampUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "seconds" | "s" => Ok(TimestampUnit::Seconds),
+            "milliseconds" | "ms" => Ok(TimestampUnit::Milliseconds),
+            other => Err(format!("unknown timestamp unit '{}', expected seconds or milliseconds", other)),
+        }
+    }
+}
+
+/// What the indexer can expect from the chain's nodes: which header fields are meaningful,
+/// how new heads are delivered, what finality looks like, how timestamps are expressed and
+/// how often blocks are produced.
+/// Built from a preset for the chain kind, with individual aspects overridable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct ChainProfile {
+    pub kind: ChainKind,
+    pub head_source: HeadSource,
+    pub finality: Finality,
+    pub timestamp_unit: TimestampUnit,
+    /// Expected time between blocks, in milliseconds
+    pub block_time_ms: u64,
+}
+
+impl Default for ChainProfile {
+    fn default() -> Self {
+        Self::preset(ChainKind::default())
+    }
+}
+
+impl ChainProfile {
+    /// Preset for a chain kind
+    pub fn preset(kind: ChainKind) -> Self {
+        // Both kinds share the other node-facing defaults; RISE nodes serve `newHeads`,
+        // the finality tags and second timestamps like any execution client
+        let block_time_ms = match kind {
+            ChainKind::Ethereum => 12_000,
+            ChainKind::Rollup => 1_000,
+        };
+
+        Self {
+            kind,
+            head_source: HeadSource::default(),
+            finality: Finality::default(),
+            timestamp_unit: TimestampUnit::default(),
+            block_time_ms,
+        }
+    }
+
+    pub fn with_head_source(mut self, head_source: HeadSource) -> Self {
+        self.head_source = head_source;
+        self
+    }
+
+    pub fn with_finality(mut self, finality: Finality) -> Self {
+        self.finality = finality;
+        self
+    }
+
+    pub fn with_timestamp_unit(mut self, timestamp_unit: TimestampUnit) -> Self {
+        self.timestamp_unit = timestamp_unit;
+        self
+    }
+
+    pub fn with_block_time_ms(mut self, block_time_ms: u64) -> Self {
+        self.block_time_ms = block_time_ms;
+        self
+    }
+
+    /// Whether difficulty and total difficulty carry meaning on this chain
+    pub fn has_difficulty(&self) -> bool {
+        self.kind == ChainKind::Ethereum
+    }
+
+    /// Convert a timestamp reported by the node to the seconds stored in the database
+    pub fn timestamp_secs(&self, timestamp: u64) -> u64 {
+        match self.timestamp_unit {
+            TimestampUnit::Seconds => timestamp,
+            TimestampUnit::Milliseconds => timestamp / 1000,
+        }
+    }
+
+    /// The exact millisecond timestamp, when the node reports one
+    pub fn timestamp_ms(&self, timestamp: u64) -> Option<u64> {
+        match self.timestamp_unit {
+            TimestampUnit::Seconds => None,
+            TimestampUnit::Milliseconds => Some(timestamp),
+        }
+    }
+
+    /// Millisecond timestamp of a block, exact when the node reports one, otherwise
+    /// the start of its second
+    pub fn timestamp_ms_or_secs(&self, timestamp: u64) -> u64 {
+        self.timestamp_ms(timestamp).unwrap_or(timestamp * 1000)
+    }
+}
+
+impl fmt::Display for ChainProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (heads: {}, finality: {}, timestamps: {}, block time: {}ms)",
+            self.kind.as_str(), self.head_source.as_str(), self.finality.as_str(), self.timestamp_unit.as_str(),
+            self.block_time_ms
+        )
+    }
+}
+
+impl FromStr for ChainProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(ChainProfile::preset)
+    }
+}
+
+/// Parse a stored or remote difficulty value, decimal or `0x`-prefixed hex.
+/// Returns None for empty or unparseable values rather than failing the whole block.
+pub fn parse_difficulty(value: &str) -> Option<U256> {
+    let value = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) if !hex.is_empty() => U256::from_str_radix(hex, 16).ok(),
+        Some(_) => None,
+        None if !value.is_empty() => U256::from_dec_str(value).ok(),
+        None => None,
+    }
+}
@@ -0,0 +1,327 @@
+//! Reorder buffer releasing blocks to the database strictly in ascending order, for
+//! consumers that need the latest persisted block to only move forward one block at a time
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::info;
+
+use crate::models::Block;
+
+/// Gauge of blocks held back until the blocks before them are persisted
+const PENDING_BLOCKS_METRIC: &str = "indexer_ordered_commit_pending_blocks";
+
+/// Delay before the first retry of a block that failed to persist, doubled with each failure
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound for the delay between retries of a block that failed to persist
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Failed attempts after which a block is left to the gap repairer, so a persistent error
+/// doesn't hold back every block after it
+const MAX_PERSIST_ATTEMPTS: u32 = 10;
+
+/// Minimum time between reports of a block that keeps failing to persist
+const RETRY_SUMMARY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shared by the block processors of the historic and live sync, so the order holds
+/// across the hand-over between them. Holding the lock while persisting the released
+/// blocks keeps concurrent workers from writing them out of order.
+#[derive(Debug)]
+pub struct CommitOrder {
+    pending: Mutex<PendingBlocks>,
+}
+
+#[derive(Debug)]
+pub struct PendingBlocks {
+    /// Next block number to persist, unknown until the sync knows where it starts
+    next: Option<u64>,
+    /// Blocks fetched ahead of `next`, by number
+    blocks: BTreeMap<u64, Block>,
+    /// When the buffer started waiting for `next` while holding later blocks
+    waiting_since: Option<Instant>,
+    /// How long to wait for a missing block before releasing the blocks after it
+    max_wait: Duration,
+    /// The block that failed to persist and is waiting for its retry
+    failure: Option<FailedBlock>,
+}
+
+/// A block that failed to persist, retried with exponential backoff
+#[derive(Debug)]
+struct FailedBlock {
+    number: u64,
+    /// Failed attempts so far
+    attempts: u32,
+    /// When the block may be persisted again
+    retry_at: Instant,
+    /// When the failure was last reported
+    reported_at: Instant,
+}
+
+/// What happens to a block that failed to persist
+#[derive(Debug, PartialEq, Eq)]
+pub enum PersistRetry {
+    /// The block is held for another attempt after `delay`. `report` is set on the first
+    /// failure and then at most once per summary interval.
+    Retrying { attempts: u32, delay: Duration, report: bool },
+    /// The block was dropped after too many attempts and the blocks after it are released;
+    /// the gap repairer fetches it again
+    GaveUp { attempts: u32 },
+}
+
+impl CommitOrder {
+    pub fn new(max_wait: Duration) -> Self {
+        Self {
+            pending: Mutex::new(PendingBlocks {
+                next: None,
+                blocks: BTreeMap::new(),
+                waiting_since: None,
+                max_wait,
+                failure: None,
+            }),
+        }
+    }
+
+    /// Release blocks from `number` on, unless a start was already set
+    pub async fn start_at(&self, number: u64) {
+        let mut pending = self.pending.lock().await;
+        if pending.next.is_none() {
+            info!("Persisting blocks in order from block {}", number);
+            pending.next = Some(number);
+        }
+    }
+
+    /// Lock the buffer to add blocks and persist the ones that are ready
+    pub async fn lock(&self) -> MutexGuard<'_, PendingBlocks> {
+        self.pending.lock().await
+    }
+}
+
+impl PendingBlocks {
+    /// Hold a block until it is its turn, replacing a pending block with the same number
+    pub fn insert(&mut self, block: Block) {
+        self.blocks.insert(block.number, block);
+        metrics::gauge!(PENDING_BLOCKS_METRIC, self.blocks.len() as f64);
+    }
+
+    /// Take the next block to persist: the one the buffer waits for, or a block below it
+    /// that is written again (e.g. after a reorg). Without a start, the lowest block held
+    /// is the first one.
+    pub fn pop_ready(&mut self) -> Option<Block> {
+        let (&lowest, _) = self.blocks.first_key_value()?;
+        let next = *self.next.get_or_insert(lowest);
+
+        if lowest > next {
+            self.waiting_since.get_or_insert_with(Instant::now);
+            return None;
+        }
+
+        // Hold the failed block, and the blocks after it, until its retry is due
+        if self.failure.as_ref().is_some_and(|failure| failure.number == lowest && Instant::now() < failure.retry_at) {
+            return None;
+        }
+
+        let block = self.blocks.pop_first().map(|(_, block)| block);
+        metrics::gauge!(PENDING_BLOCKS_METRIC, self.blocks.len() as f64);
+        block
+    }
+
+    /// Record a released block as persisted, moving on to the block after it
+    pub fn committed(&mut self, number: u64) {
+        if self.failure.as_ref().is_some_and(|failure| failure.number == number) {
+            self.failure = None;
+        }
+        if self.next.is_none_or(|next| number >= next) {
+            self.next = Some(number + 1);
+            self.waiting_since = None;
+        }
+    }
+
+    /// Take back a released block that failed to persist, to retry it after a backoff, or
+    /// give up on it after too many attempts
+    pub fn failed(&mut self, block: Block) -> PersistRetry {
+        let number = block.number;
+        let now = Instant::now();
+        let (attempts, reported_at) = match &self.failure {
+            Some(failure) if failure.number == number => (failure.attempts + 1, Some(failure.reported_at)),
+            _ => (1, None),
+        };
+
+        if attempts >= MAX_PERSIST_ATTEMPTS {
+            self.failure = None;
+            self.committed(number);
+            return PersistRetry::GaveUp { attempts };
+        }
+
+        let delay = RETRY_BASE_DELAY.saturating_mul(1 << (attempts - 1).min(16)).min(RETRY_MAX_DELAY);
+        let report = reported_at.is_none_or(|at| now.duration_since(at) >= RETRY_SUMMARY_INTERVAL);
+        self.failure = Some(FailedBlock {
+            number,
+            attempts,
+            retry_at: now + delay,
+            reported_at: reported_at.filter(|_| !report).unwrap_or(now),
+        });
+        self.insert(block);
+
+        PersistRetry::Retrying { attempts, delay, report }
+    }
+
+    /// Stop waiting for missing blocks once they are overdue, returning the inclusive range
+    /// of block numbers given up on
+    pub fn skip_overdue(&mut self) -> Option<(u64, u64)> {
+        let next = self.next?;
+        let (&lowest, _) = self.blocks.first_key_value()?;
+        if lowest <= next || self.waiting_since.is_none_or(|since| since.elapsed() < self.max_wait) {
+            return None;
+        }
+
+        self.next = Some(lowest);
+        self.waiting_since = None;
+        Some((next, lowest - 1))
+    }
+
+    /// Take every block held, in ascending order, whether or not the blocks before them arrived
+    pub fn drain(&mut self) -> Vec<Block> {
+        let blocks = std::mem::take(&mut self.blocks).into_values().collect();
+        metrics::gauge!(PENDING_BLOCKS_METRIC, 0.0);
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Persist every block that is ready, returning their numbers
+    fn release(pending: &mut PendingBlocks) -> Vec<u64> {
+        let mut numbers = Vec::new();
+        while let Some(block) = pending.pop_ready() {
+            pending.committed(block.number);
+            numbers.push(block.number);
+        }
+        numbers
+    }
+
+    /// Make the failed block's retry due now
+    fn retry_now(pending: &mut PendingBlocks) {
+        pending.failure.as_mut().expect("a failed block").retry_at = Instant::now();
+    }
+
+    #[tokio::test]
+    async fn releases_out_of_order_arrivals_in_order() {
+        let order = CommitOrder::new(Duration::from_secs(30));
+        order.start_at(10).await;
+        let mut pending = order.lock().await;
+
+        pending.insert(Block::dummy(12));
+        pending.insert(Block::dummy(11));
+        assert!(release(&mut pending).is_empty());
+
+        pending.insert(Block::dummy(10));
+        assert_eq!(release(&mut pending), vec![10, 11, 12]);
+
+        pending.insert(Block::dummy(14));
+        assert!(release(&mut pending).is_empty());
+        pending.insert(Block::dummy(13));
+        assert_eq!(release(&mut pending), vec![13, 14]);
+    }
+
+    #[tokio::test]
+    async fn starts_from_the_lowest_block_without_a_start() {
+        let order = CommitOrder::new(Duration::from_secs(30));
+        let mut pending = order.lock().await;
+
+        pending.insert(Block::dummy(7));
+        pending.insert(Block::dummy(5));
+        assert_eq!(release(&mut pending), vec![5]);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_first_start_after_a_resume() {
+        let order = CommitOrder::new(Duration::from_secs(30));
+        order.start_at(100).await;
+        // The live sync starting later doesn't move the resume point
+        order.start_at(120).await;
+        let mut pending = order.lock().await;
+
+        pending.insert(Block::dummy(101));
+        assert!(release(&mut pending).is_empty());
+
+        // Blocks below the resume point are re-writes and go through right away
+        pending.insert(Block::dummy(99));
+        assert_eq!(release(&mut pending), vec![99]);
+
+        pending.insert(Block::dummy(100));
+        assert_eq!(release(&mut pending), vec![100, 101]);
+    }
+
+    #[tokio::test]
+    async fn skips_overdue_missing_blocks() {
+        let order = CommitOrder::new(Duration::ZERO);
+        order.start_at(1).await;
+        let mut pending = order.lock().await;
+
+        pending.insert(Block::dummy(4));
+        assert!(release(&mut pending).is_empty());
+        assert_eq!(pending.skip_overdue(), Some((1, 3)));
+        assert_eq!(release(&mut pending), vec![4]);
+    }
+
+    #[tokio::test]
+    async fn retries_a_failed_head_block_with_backoff() {
+        let order = CommitOrder::new(Duration::ZERO);
+        order.start_at(1).await;
+        let mut pending = order.lock().await;
+
+        pending.insert(Block::dummy(1));
+        pending.insert(Block::dummy(2));
+        let block = pending.pop_ready().expect("block 1 is ready");
+        assert_eq!(
+            pending.failed(block),
+            PersistRetry::Retrying { attempts: 1, delay: Duration::from_millis(500), report: true }
+        );
+
+        // Neither the failed block nor the ones after it are released before the retry,
+        // and they aren't skipped as overdue
+        assert!(pending.pop_ready().is_none());
+        assert_eq!(pending.skip_overdue(), None);
+
+        retry_now(&mut pending);
+        let block = pending.pop_ready().expect("block 1 is retried");
+        assert_eq!(block.number, 1);
+        assert_eq!(
+            pending.failed(block),
+            PersistRetry::Retrying { attempts: 2, delay: Duration::from_secs(1), report: false }
+        );
+
+        retry_now(&mut pending);
+        assert_eq!(release(&mut pending), vec![1, 2]);
+        assert!(pending.failure.is_none());
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_a_block_that_keeps_failing() {
+        let order = CommitOrder::new(Duration::from_secs(30));
+        order.start_at(1).await;
+        let mut pending = order.lock().await;
+
+        pending.insert(Block::dummy(1));
+        pending.insert(Block::dummy(2));
+        let mut delays = Vec::new();
+        let attempts = loop {
+            let block = pending.pop_ready().expect("block 1 is ready");
+            assert_eq!(block.number, 1);
+            match pending.failed(block) {
+                PersistRetry::Retrying { delay, .. } => {
+                    delays.push(delay);
+                    retry_now(&mut pending);
+                }
+                PersistRetry::GaveUp { attempts } => break attempts,
+            }
+        };
+
+        assert_eq!(attempts, MAX_PERSIST_ATTEMPTS);
+        assert_eq!(delays.iter().max(), Some(&RETRY_MAX_DELAY));
+        assert_eq!(release(&mut pending), vec![2]);
+    }
+}
@@ -1,5 +1,13 @@
 mod block;
 mod block_queue;
+mod chain_profile;
+mod commit_order;
+mod receipt;
+mod tag_rules;
 
 pub use block::*;
 pub use block_queue::{BlockQueue, BlockProcessor};
+pub use chain_profile::{parse_difficulty, ChainProfile, Finality, HeadSource};
+pub use commit_order::{CommitOrder, PersistRetry};
+pub use receipt::{Log, Receipt};
+pub use tag_rules::TagRules;
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+/// Transaction receipt as returned by `eth_getBlockReceipts`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Receipt {
+    pub transaction_hash: String,
+    pub transaction_index: u64,
+    pub block_hash: String,
+    pub block_number: u64,
+    pub from: String,
+    pub to: Option<String>,
+    /// 1 for success, 0 for failure; None for pre-Byzantium receipts
+    pub status: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub cumulative_gas_used: u64,
+    pub effective_gas_price: Option<u64>,
+    pub contract_address: Option<String>,
+    pub logs: Vec<Log>,
+    /// Runtime bytecode of the contract this transaction created, fetched separately
+    /// with `eth_getCode` since receipts only carry the address
+    #[serde(default)]
+    pub deployed_code: Option<String>,
+}
+
+/// Event log emitted by a transaction
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Log {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub log_index: Option<u64>,
+}
+
+impl Receipt {
+    /// Build a receipt model from an RPC receipt.
+    /// Returns None for receipts of pending transactions, which have no block yet.
+    pub fn from_eth_receipt(receipt: &ethers::types::TransactionReceipt) -> Option<Self> {
+        Some(Self {
+            transaction_hash: format!("{:?}", receipt.transaction_hash),
+            transaction_index: receipt.transaction_index.as_u64(),
+            block_hash: format!("{:?}", receipt.block_hash?),
+            block_number: receipt.block_number?.as_u64(),
+            from: format!("{:?}", receipt.from),
+            to: receipt.to.map(|to| format!("{:?}", to)),
+            status: receipt.status.map(|status| status.as_u64()),
+            gas_used: receipt.gas_used.and_then(|gas| u64::try_from(gas).ok()),
+            cumulative_gas_used: u64::try_from(receipt.cumulative_gas_used).unwrap_or(u64::MAX),
+            effective_gas_price: receipt.effective_gas_price.and_then(|price| u64::try_from(price).ok()),
+            contract_address: receipt.contract_address.map(|address| format!("{:?}", address)),
+            logs: receipt.logs.iter().map(Log::from).collect(),
+            deployed_code: None,
+        })
+    }
+
+    /// Address of the contract this transaction created, if it succeeded in creating one
+    pub fn created_contract(&self) -> Option<&str> {
+        if self.status == Some(0) {
+            return None;
+        }
+        self.contract_address.as_deref()
+    }
+}
+
+impl From<&ethers::types::Log> for Log {
+    fn from(log: &ethers::types::Log) -> Self {
+        Self {
+            address: format!("{:?}", log.address),
+            topics: log.topics.iter().map(|topic| format!("{:?}", topic)).collect(),
+            data: format!("0x{}", hex::encode(&log.data)),
+            log_index: log.log_index.and_then(|index| u64::try_from(index).ok()),
+        }
+    }
+}
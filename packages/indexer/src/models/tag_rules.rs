@@ -0,0 +1,118 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use tracing::info;
+
+use crate::models::Block;
+
+/// A condition evaluated against a block.
+///
+/// Predicates are written in JSON as single-key objects, e.g.
+/// `{"tx_count_gt": 100}` or `{"any": [{"from": "0x..."}, {"to": "0x..."}]}`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Predicate {
+    /// More than this many transactions
+    TxCountGt(u64),
+    /// Fewer than this many transactions
+    TxCountLt(u64),
+    /// More than this much gas used
+    GasUsedGt(u64),
+    /// Gas used above this percentage of the gas limit
+    GasUsedPctGt(f64),
+    /// Base fee above this value (wei)
+    BaseFeeGt(u64),
+    /// Any transaction sent from or to this address
+    ContainsAddress(String),
+    /// Any transaction sent from this address
+    From(String),
+    /// Any transaction sent to this address
+    To(String),
+    /// Block produced by this address
+    Miner(String),
+    /// All nested predicates match
+    All(Vec<Predicate>),
+    /// At least one nested predicate matches
+    Any(Vec<Predicate>),
+    /// The nested predicate does not match
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Check whether the block satisfies this predicate
+    pub fn matches(&self, block: &Block) -> bool {
+        match self {
+            Predicate::TxCountGt(n) => block.transaction_count > *n,
+            Predicate::TxCountLt(n) => block.transaction_count < *n,
+            Predicate::GasUsedGt(n) => block.gas_used > *n,
+            Predicate::GasUsedPctGt(pct) => {
+                block.gas_limit > 0 && (block.gas_used as f64 / block.gas_limit as f64) * 100.0 > *pct
+            }
+            Predicate::BaseFeeGt(n) => block.base_fee_per_gas.is_some_and(|fee| fee > *n),
+            Predicate::ContainsAddress(address) => block.transactions.iter().any(|tx| {
+                address_eq(tx.from.as_deref(), address) || address_eq(tx.to.as_deref(), address)
+            }),
+            Predicate::From(address) => block.transactions.iter().any(|tx| address_eq(tx.from.as_deref(), address)),
+            Predicate::To(address) => block.transactions.iter().any(|tx| address_eq(tx.to.as_deref(), address)),
+            Predicate::Miner(address) => address_eq(Some(&block.miner), address),
+            Predicate::All(predicates) => predicates.iter().all(|p| p.matches(block)),
+            Predicate::Any(predicates) => predicates.iter().any(|p| p.matches(block)),
+            Predicate::Not(predicate) => !predicate.matches(block),
+        }
+    }
+}
+
+/// Compare addresses ignoring checksum casing
+fn address_eq(address: Option<&str>, expected: &str) -> bool {
+    address.is_some_and(|a| a.eq_ignore_ascii_case(expected))
+}
+
+/// A tag applied to every block matching its predicate
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagRule {
+    pub tag: String,
+    pub when: Predicate,
+}
+
+/// Set of configured tagging rules, evaluated as blocks are persisted
+#[derive(Clone, Debug, Default)]
+pub struct TagRules {
+    rules: Vec<TagRule>,
+}
+
+impl TagRules {
+    /// Load rules from a JSON file containing an array of `{"tag": ..., "when": ...}` objects
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read tag rules from {}", path.display()))?;
+        let rules: Vec<TagRule> = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid tag rules in {}", path.display()))?;
+
+        for rule in &rules {
+            if rule.tag.trim().is_empty() {
+                bail!("Tag rules in {} contain an empty tag", path.display());
+            }
+        }
+
+        info!("Loaded {} block tag rules from {}", rules.len(), path.display());
+        Ok(Self { rules })
+    }
+
+    /// Number of configured rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Tags of every rule matching the block, sorted and deduplicated
+    pub fn evaluate(&self, block: &Block) -> Vec<String> {
+        let mut tags: Vec<String> = self.rules.iter()
+            .filter(|rule| rule.when.matches(block))
+            .map(|rule| rule.tag.clone())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+}
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+use ethers::providers::{Provider, Http, Middleware};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+use crate::db::{AuditEvent, Database};
+use crate::sync::{SharedSyncState, SyncError};
+use crate::utils::rpc;
+
+/// 1 while the alert is firing, 0 otherwise, per alert
+const FIRING_METRIC: &str = "indexer_sync_alert_firing";
+
+/// PagerDuty Events API v2 endpoint (US service region)
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Conditions the monitor alerts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncAlert {
+    /// No new block was persisted for too long
+    Stalled,
+    /// The latest indexed block is too far behind the chain head
+    Lagging,
+}
+
+impl SyncAlert {
+    fn as_str(self) -> &'static str {
+        match self {
+            SyncAlert::Stalled => "sync_stalled",
+            SyncAlert::Lagging => "sync_lagging",
+        }
+    }
+}
+
+/// Alerts operators when the indexer stops persisting blocks or falls too far behind the
+/// chain head, before users notice stale data. Alerts are logged at error level, recorded
+/// in the audit log, and sent to a webhook and PagerDuty when configured, each with a
+/// follow-up once resolved.
+///
+/// Unlike the chain status monitor, the stall alert is based on the wall clock time since
+/// the latest block number last changed, so it also fires while the RPC node is unreachable.
+pub struct SyncAlertMonitor {
+    /// HTTP provider used to query the chain head
+    provider: Provider<Http>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Shared state between sync components
+    sync_state: SharedSyncState,
+    /// Client posting to the webhook and PagerDuty
+    client: reqwest::Client,
+    /// Interval between checks
+    poll_interval: Duration,
+    /// Time without a new block after which the stall alert fires
+    stall_after: Option<Duration>,
+    /// Blocks behind the chain head from which the lag alert fires (0 disables)
+    max_lag_blocks: u64,
+    /// URL receiving a JSON POST for each alert and resolution
+    webhook_url: Option<String>,
+    /// PagerDuty integration key and Events API endpoint
+    pagerduty: Option<(String, String)>,
+}
+
+impl SyncAlertMonitor {
+    /// Create a new sync alert monitor
+    pub fn new(http_provider_url: &str, db: Arc<Database>, sync_state: SharedSyncState) -> Result<Self, SyncError> {
+        let provider = rpc::http_provider(http_provider_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| SyncError::Http(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            provider,
+            db,
+            sync_state,
+            client,
+            poll_interval: Duration::from_secs(10), // Default 10 seconds
+            stall_after: None,
+            max_lag_blocks: 0,
+            webhook_url: None,
+            pagerduty: None,
+        })
+    }
+
+    /// Configure the interval between checks
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Configure how long without a new block before the stall alert fires (0 disables)
+    pub fn with_stall_threshold(mut self, seconds: u64) -> Self {
+        if seconds > 0 {
+            info!("Alerting when no block is persisted for {}s", seconds);
+        }
+        self.stall_after = (seconds > 0).then(|| Duration::from_secs(seconds));
+        self
+    }
+
+    /// Configure how many blocks behind the chain head the lag alert fires at (0 disables)
+    pub fn with_max_lag(mut self, blocks: u64) -> Self {
+        if blocks > 0 {
+            info!("Alerting when the indexer is more than {} blocks behind", blocks);
+        }
+        self.max_lag_blocks = blocks;
+        self
+    }
+
+    /// Configure a URL receiving a JSON POST for each alert and resolution
+    pub fn with_webhook(mut self, url: Option<String>) -> Self {
+        self.webhook_url = url;
+        self
+    }
+
+    /// Configure a PagerDuty Events API v2 integration key, triggering an incident per alert
+    /// that is resolved with it
+    pub fn with_pagerduty(mut self, routing_key: Option<String>, events_url: &str) -> Self {
+        self.pagerduty = routing_key.map(|key| (key, events_url.to_string()));
+        self
+    }
+
+    /// Spawn the monitor as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting sync alert monitor");
+
+        tokio::spawn(async move {
+            let mut latest = None;
+            let mut persisted_at = Instant::now();
+            let mut firing = [false; 2];
+
+            loop {
+                self.db.idle_mode().sleep(self.poll_interval).await;
+
+                match self.db.get_latest_block_number().await {
+                    Ok(number) if number != latest => {
+                        latest = number;
+                        persisted_at = Instant::now();
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Failed to read latest indexed block: {}", e),
+                }
+
+                if let Some(stall_after) = self.stall_after {
+                    let stalled_for = persisted_at.elapsed();
+                    let stalled = stalled_for >= stall_after;
+                    let detail = match latest {
+                        Some(number) if !stalled => format!("Block #{} persisted", number),
+                        Some(number) => format!("No block persisted for {}s (latest #{})", stalled_for.as_secs(), number),
+                        None => format!("No block persisted for {}s", stalled_for.as_secs()),
+                    };
+                    self.update(SyncAlert::Stalled, stalled, &detail, &mut firing[0]).await;
+                }
+
+                // The indexer is far behind by design during a backfill
+                if self.max_lag_blocks > 0 && self.sync_state.lock().await.historic_sync_complete {
+                    match self.get_head().await {
+                        Ok(head) => {
+                            let lag = head.saturating_sub(latest.unwrap_or_default());
+                            let detail = format!(
+                                "Indexer is {} blocks behind chain head #{} (threshold {})",
+                                lag, head, self.max_lag_blocks
+                            );
+                            self.update(SyncAlert::Lagging, lag > self.max_lag_blocks, &detail, &mut firing[1]).await;
+                        }
+                        Err(e) => error!("Failed to get chain head for the lag alert: {}", e),
+                    }
+                }
+            }
+        })
+    }
+
+    /// Fetch the latest block number on chain
+    async fn get_head(&self) -> Result<u64, SyncError> {
+        let head = self.provider.get_block_number()
+            .await
+            .map_err(|e| SyncError::Provider(format!("Failed to get latest block number: {}", e)))?;
+
+        Ok(head.as_u64())
+    }
+
+    /// Notify when the alert starts or stops firing
+    async fn update(&self, alert: SyncAlert, condition: bool, detail: &str, firing: &mut bool) {
+        metrics::gauge!(FIRING_METRIC, if condition { 1.0 } else { 0.0 }, "alert" => alert.as_str());
+        debug!("Sync alert {}: {} ({})", alert.as_str(), condition, detail);
+
+        if condition != *firing {
+            *firing = condition;
+            self.notify(alert, condition, detail).await;
+        }
+    }
+
+    /// Log, audit and send an alert firing or resolving, at error level so log-based alerting
+    /// picks it up. Delivery failures are logged, not retried.
+    async fn notify(&self, alert: SyncAlert, firing: bool, detail: &str) {
+        let action = if firing {
+            error!("ALERT: {}: {}", alert.as_str(), detail);
+            "alert_fired"
+        } else {
+            info!("Alert {} resolved: {}", alert.as_str(), detail);
+            "alert_resolved"
+        };
+
+        self.db.record_audit_event(
+            AuditEvent::new("sync_alerts", action)
+                .with_details(serde_json::json!({
+                    "alert": alert.as_str(),
+                    "detail": detail,
+                }))
+        ).await;
+
+        if let Some(url) = &self.webhook_url {
+            let body = serde_json::json!({
+                "alert": alert.as_str(),
+                "status": if firing { "firing" } else { "resolved" },
+                "detail": detail,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            });
+            self.post("webhook", url, &body).await;
+        }
+
+        if let Some((routing_key, events_url)) = &self.pagerduty {
+            let body = serde_json::json!({
+                "routing_key": routing_key,
+                "event_action": if firing { "trigger" } else { "resolve" },
+                "dedup_key": format!("indexer-{}", alert.as_str()),
+                "payload": {
+                    "summary": format!("Indexer {}: {}", alert.as_str(), detail),
+                    "source": "indexer",
+                    "severity": "critical",
+                },
+            });
+            self.post("PagerDuty", events_url, &body).await;
+        }
+    }
+
+    /// POST the body as JSON, logging failures
+    async fn post(&self, target: &str, url: &str, body: &serde_json::Value) {
+        match self.client.post(url).json(body).send().await {
+            Ok(response) if !response.status().is_success() => {
+                warn!("Alert {} returned {}", target, response.status());
+            }
+            Ok(_) => debug!("Alert sent to {}", target),
+            Err(e) => warn!("Failed to send alert to {}: {}", target, e),
+        }
+    }
+}
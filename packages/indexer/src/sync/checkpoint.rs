@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::db::{Database, SyncCheckpoint};
+use crate::sync::{SharedSyncState, SyncError};
+
+/// Gauge of the block up to which every block of the sync is stored
+const CONTIGUOUS_BLOCK_METRIC: &str = "indexer_sync_contiguous_block";
+
+/// Keeps the `sync_state` checkpoint up to date, so a restart resumes from the last block
+/// below which nothing is missing rather than the highest stored block.
+///
+/// Block processors write out of order, so the highest stored block can be ahead of gaps
+/// that are still in flight when the indexer stops. Each pass moves the cursor up through
+/// the blocks stored since the previous one, and records the ranges still missing above it.
+pub struct SyncCheckpointer {
+    /// Database connection
+    db: Arc<Database>,
+    /// Shared sync state, for the start of the synced range
+    sync_state: SharedSyncState,
+    /// Interval between passes
+    interval: Duration,
+}
+
+impl SyncCheckpointer {
+    /// Create a new checkpointer for the sync sharing `sync_state`
+    pub fn new(db: Arc<Database>, sync_state: SharedSyncState) -> Self {
+        Self {
+            db,
+            sync_state,
+            interval: Duration::from_secs(5), // Default 5 seconds
+        }
+    }
+
+    /// Configure the interval between passes
+    pub fn with_interval(mut self, seconds: u64) -> Self {
+        info!("Setting sync checkpoint interval to {}s", seconds);
+        self.interval = Duration::from_secs(seconds.max(1));
+        self
+    }
+
+    /// Spawn the checkpointer as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting sync checkpointer");
+
+        tokio::spawn(async move {
+            let mut checkpoint = SyncCheckpoint::default();
+            // First block not known to be stored
+            let mut next = self.sync_state.lock().await.sync_start;
+
+            loop {
+                // The range starts later when historic sync skipped blocks the node pruned
+                let sync_start = self.sync_state.lock().await.sync_start;
+                if next < sync_start {
+                    next = sync_start;
+                    checkpoint.contiguous_block = None;
+                }
+
+                match self.advance(&mut checkpoint, next).await {
+                    Ok(reached) => next = reached,
+                    Err(e) => error!("Sync checkpoint pass failed: {}", e),
+                }
+
+                tokio::time::sleep(self.interval).await;
+            }
+        })
+    }
+
+    /// Move the checkpoint up through the blocks stored from `next` on and save it,
+    /// returning the first block still missing
+    async fn advance(&self, checkpoint: &mut SyncCheckpoint, next: u64) -> Result<u64, SyncError> {
+        let Some(highest_block) = self.db.get_latest_block_number().await? else {
+            return Ok(next);
+        };
+
+        let pending_ranges = if highest_block >= next {
+            self.db.find_missing_block_ranges(next, highest_block).await?
+        } else {
+            Vec::new()
+        };
+        let reached = pending_ranges.first()
+            .map(|(start, _)| *start)
+            .unwrap_or_else(|| next.max(highest_block + 1));
+
+        if reached > next {
+            debug!("Blocks stored contiguously up to {}", reached - 1);
+            checkpoint.contiguous_block = Some(reached - 1);
+            metrics::gauge!(CONTIGUOUS_BLOCK_METRIC, (reached - 1) as f64);
+        }
+        checkpoint.highest_block = Some(highest_block);
+        checkpoint.pending_ranges = pending_ranges;
+
+        self.db.save_sync_checkpoint(checkpoint).await?;
+        Ok(reached)
+    }
+}
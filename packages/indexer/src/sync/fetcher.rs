@@ -1,21 +1,54 @@
 use std::sync::Arc;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
 use tracing::{debug, error, info, warn};
-use ethers::providers::{Provider, Ws, Middleware};
-use ethers::types::BlockNumber;
-use tokio::time::{sleep, Duration};
+use ethers::providers::{Http, JsonRpcClient, Provider, ProviderError, Ws, Middleware};
+use ethers::types::{Address, BlockId, BlockNumber, TransactionReceipt};
+use tokio::time::{sleep, timeout, Duration};
 
 use crate::models::{Block, BlockQueue, Transaction};
+use crate::utils::endpoints::EndpointPool;
+use crate::utils::rate_limit::RateLimiter;
 use crate::utils::retry::with_retry;
 use crate::sync::SyncError;
+use crate::utils::rpc;
 
 /// Maximum number of concurrent batch fetches
 const DEFAULT_MAX_CONCURRENT_BATCHES: usize = 5;
 
+/// How long an endpoint that failed has to answer `eth_blockNumber` before serving requests again
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+/// Connection a block fetcher sends its requests over
+#[derive(Clone, Debug)]
+enum FetcherTransport {
+    Ws(Ws),
+    Http(Http),
+}
+
+#[async_trait]
+impl JsonRpcClient for FetcherTransport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            FetcherTransport::Ws(ws) => ws.request(method, params).await.map_err(Into::into),
+            FetcherTransport::Http(http) => http.request(method, params).await.map_err(Into::into),
+        }
+    }
+}
 
 /// Block fetcher for concurrent block retrieval
 pub struct BlockFetcher {
-    /// Provider for network access using WebSockets
-    provider: Provider<Ws>,
+    /// Providers for network access over WebSockets or HTTP, one per endpoint
+    providers: Arc<Vec<Provider<FetcherTransport>>>,
+    /// Health of the endpoints, requests are spread across the healthy ones
+    endpoints: Arc<EndpointPool>,
     /// Block queue for passing blocks to database workers
     block_queue: Arc<BlockQueue>,
     /// The RPC batch size to use when fetching blocks
@@ -28,6 +61,12 @@ pub struct BlockFetcher {
     max_retries: u32,
     /// Worker stagger delay (ms per worker)
     worker_stagger_delay: u64,
+    /// Fetch full transaction objects instead of only hashes
+    full_transactions: bool,
+    /// Fetch transaction receipts for each block
+    fetch_receipts: bool,
+    /// Limits the request rate across all workers and fetchers sharing it
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl BlockFetcher {
@@ -40,42 +79,97 @@ impl BlockFetcher {
         max_retries: u32,
     ) -> Self {
         Self {
-            provider,
+            providers: Arc::new(vec![Provider::new(FetcherTransport::Ws(provider.as_ref().clone()))]),
+            endpoints: Arc::new(EndpointPool::new("Fetcher", vec![String::new()])),
             block_queue,
             rpc_batch_size,
             max_concurrent_batches: DEFAULT_MAX_CONCURRENT_BATCHES,
             retry_delay,
             max_retries,
             worker_stagger_delay: 100, // Default to 100ms per worker
+            full_transactions: false,
+            fetch_receipts: false,
+            rate_limiter: None,
         }
     }
     
-    /// Create a new fetcher from a WebSocket URL
-    pub async fn from_ws_url(
-        ws_url: &str,
+    /// Create a new fetcher from WebSocket URLs, spreading requests across the endpoints.
+    /// Endpoints that can't be connected to are skipped, as long as one connects.
+    pub async fn from_ws_urls(
+        ws_urls: &[String],
         block_queue: Arc<BlockQueue>,
         rpc_batch_size: usize,
         retry_delay: u64,
         max_retries: u32,
     ) -> Result<Self, SyncError> {
-        info!("Creating WebSocket provider from URL: {}", ws_url);
+        let mut providers = Vec::with_capacity(ws_urls.len());
+        let mut connected_urls = Vec::with_capacity(ws_urls.len());
+        let mut last_error = None;
         
-        // Connect to the WebSocket provider
-        let ws = Ws::connect(ws_url)
-            .await
-            .map_err(|e| SyncError::Provider(format!("Failed to connect to WebSocket: {}", e)))?;
+        for ws_url in ws_urls {
+            info!("Creating WebSocket provider from URL: {}", ws_url);
             
-        let provider = Provider::new(ws);
-        info!("Successfully connected to WebSocket provider");
+            // Connect to the WebSocket provider
+            match rpc::connect_ws(ws_url).await {
+                Ok(ws) => {
+                    providers.push(Provider::new(FetcherTransport::Ws(ws)));
+                    connected_urls.push(ws_url.clone());
+                }
+                Err(e) => {
+                    warn!("Failed to connect to WebSocket {}: {}", ws_url, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        
+        if providers.is_empty() {
+            return Err(SyncError::Provider(format!(
+                "Failed to connect to WebSocket: {}",
+                last_error.map_or_else(|| "no URL configured".to_string(), |e| e.to_string())
+            )));
+        }
+        info!("Successfully connected to {}/{} WebSocket providers", providers.len(), ws_urls.len());
         
         Ok(Self {
-            provider,
+            providers: Arc::new(providers),
+            endpoints: Arc::new(EndpointPool::new("Fetcher", connected_urls)),
             block_queue,
             rpc_batch_size,
             max_concurrent_batches: DEFAULT_MAX_CONCURRENT_BATCHES,
             retry_delay,
             max_retries,
             worker_stagger_delay: 100, // Default to 100ms per worker
+            full_transactions: false,
+            fetch_receipts: false,
+            rate_limiter: None,
+        })
+    }
+    
+    /// Create a new fetcher from an HTTP URL, for providers without a WebSocket endpoint
+    pub fn from_http_url(
+        http_url: &str,
+        block_queue: Arc<BlockQueue>,
+        rpc_batch_size: usize,
+        retry_delay: u64,
+        max_retries: u32,
+    ) -> Result<Self, SyncError> {
+        info!("Creating HTTP provider from URL: {}", http_url);
+        
+        let http = rpc::http_transport(http_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
+        
+        Ok(Self {
+            providers: Arc::new(vec![Provider::new(FetcherTransport::Http(http))]),
+            endpoints: Arc::new(EndpointPool::new("Fetcher", vec![http_url.to_string()])),
+            block_queue,
+            rpc_batch_size,
+            max_concurrent_batches: DEFAULT_MAX_CONCURRENT_BATCHES,
+            retry_delay,
+            max_retries,
+            worker_stagger_delay: 100, // Default to 100ms per worker
+            full_transactions: false,
+            fetch_receipts: false,
+            rate_limiter: None,
         })
     }
 
@@ -93,8 +187,29 @@ impl BlockFetcher {
         self
     }
 
-    /// Fetch a range of blocks concurrently using a continuous work-stealing approach
-    pub async fn fetch_blocks_range(&self, start_block: u64, end_block: u64) -> Result<(), SyncError> {
+    /// Fetch full transaction objects (`eth_getBlockByNumber(.., true)`) instead of only hashes
+    pub fn with_full_transactions(mut self, full_transactions: bool) -> Self {
+        info!("Setting full transaction fetching to {}", full_transactions);
+        self.full_transactions = full_transactions;
+        self
+    }
+
+    /// Fetch transaction receipts (`eth_getBlockReceipts`) for each block
+    pub fn with_receipts(mut self, fetch_receipts: bool) -> Self {
+        info!("Setting receipt fetching to {}", fetch_receipts);
+        self.fetch_receipts = fetch_receipts;
+        self
+    }
+
+    /// Share a rate limiter with other fetchers, counting each JSON-RPC request against it
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Fetch a range of blocks concurrently using a continuous work-stealing approach.
+    /// Returns the batches that failed after retries, as inclusive block ranges.
+    pub async fn fetch_blocks_range(&self, start_block: u64, end_block: u64) -> Result<Vec<(u64, u64)>, SyncError> {
         let total_blocks = end_block.saturating_sub(start_block) + 1;
         
         info!(
@@ -113,6 +228,7 @@ impl BlockFetcher {
         // Create a shared counter for tracking progress
         let batches_completed = Arc::new(tokio::sync::Mutex::new(0));
         let total_blocks_fetched = Arc::new(tokio::sync::Mutex::new(0));
+        let failed_batches = Arc::new(tokio::sync::Mutex::new(Vec::new()));
         
         
         // Create worker tasks that will continuously pull from the work queue
@@ -120,15 +236,20 @@ impl BlockFetcher {
         
         for worker_id in 0..self.max_concurrent_batches {
             // Clone all the resources needed for this worker
-            let provider = self.provider.clone();
+            let providers = Arc::clone(&self.providers);
+            let endpoints = Arc::clone(&self.endpoints);
+            let rate_limiter = self.rate_limiter.clone();
             let block_queue = Arc::clone(&self.block_queue);
             let retry_delay = self.retry_delay;
             let max_retries = self.max_retries;
             let rpc_batch_size = self.rpc_batch_size;
             let worker_stagger_delay = self.worker_stagger_delay;
+            let full_transactions = self.full_transactions;
+            let fetch_receipts = self.fetch_receipts;
             let work_queue = Arc::clone(&work_queue);
             let batches_completed = Arc::clone(&batches_completed);
             let total_blocks_fetched = Arc::clone(&total_blocks_fetched);
+            let failed_batches = Arc::clone(&failed_batches);
             // Create worker-local reference to total_batches
             
             // Spawn a continuous worker that keeps pulling from the queue
@@ -143,13 +264,17 @@ impl BlockFetcher {
                 
                 // Create a dedicated fetcher for this worker
                 let worker_fetcher = BlockFetcher {
-                    provider,
+                    providers,
+                    endpoints,
                     block_queue,
                     rpc_batch_size,
                     max_concurrent_batches: 1, // Not used in worker
                     retry_delay,
                     max_retries,
                     worker_stagger_delay,  // Pass through stagger delay
+                    full_transactions,
+                    fetch_receipts,
+                    rate_limiter,
                 };
                 
                 // Keep pulling and processing batches until the queue is empty
@@ -201,6 +326,7 @@ impl BlockFetcher {
                                     // But for now, we'll just count it as failed and move on
                                     let mut completed = batches_completed.lock().await;
                                     *completed += 1;
+                                    failed_batches.lock().await.push((batch_start, batch_end));
                                 }
                             }
                         },
@@ -236,7 +362,53 @@ impl BlockFetcher {
             batches_completed
         );
         
-        Ok(())
+        let mut failed_batches = std::mem::take(&mut *failed_batches.lock().await);
+        if !failed_batches.is_empty() {
+            warn!("{} batches failed: {:?}", failed_batches.len(), failed_batches);
+        }
+        failed_batches.sort_unstable();
+        Ok(failed_batches)
+    }
+    
+    /// Pick the provider for the next request, rotating through the healthy endpoints.
+    /// An endpoint that failed before must answer `eth_blockNumber` to serve requests again.
+    async fn next_provider(&self) -> (usize, &Provider<FetcherTransport>) {
+        for _ in 0..self.providers.len() {
+            let index = self.endpoints.next();
+            let provider = &self.providers[index];
+            if self.providers.len() == 1 || !self.endpoints.needs_health_check(index) {
+                return (index, provider);
+            }
+            
+            self.wait_for_rate_limit(1).await;
+            match timeout(HEALTH_CHECK_TIMEOUT, provider.get_block_number()).await {
+                Ok(Ok(_)) => {
+                    info!("Fetcher endpoint #{} passed its health check, back in rotation", index);
+                    self.endpoints.record_success(index);
+                    return (index, provider);
+                }
+                _ => self.endpoints.record_failure(index),
+            }
+        }
+        
+        // Every endpoint failed its health check, use the one available soonest anyway
+        let index = self.endpoints.next();
+        (index, &self.providers[index])
+    }
+    
+    /// Wait until the rate limiter, if any, allows sending `requests` requests
+    async fn wait_for_rate_limit(&self, requests: usize) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(requests).await;
+        }
+    }
+    
+    /// Update the health of the endpoint a request went to
+    fn record_outcome<T>(&self, endpoint: usize, result: &Result<T, SyncError>) {
+        match result {
+            Ok(_) => self.endpoints.record_success(endpoint),
+            Err(_) => self.endpoints.record_failure(endpoint),
+        }
     }
     
     /// Create a vector of batch ranges to process
@@ -272,12 +444,26 @@ impl BlockFetcher {
             );
             
             // Create a batch of requests
-            let blocks = self.fetch_blocks_batch(current_block..=batch_end).await?;
+            let mut blocks = if self.full_transactions {
+                self.fetch_full_blocks_batch(current_block..=batch_end).await?
+                    .into_iter()
+                    .map(|block| self.convert_full_block(block))
+                    .collect::<Vec<_>>()
+            } else {
+                self.fetch_blocks_batch(current_block..=batch_end).await?
+                    .into_iter()
+                    .map(|block| self.convert_block(block))
+                    .collect::<Vec<_>>()
+            };
             debug!("Fetched {} blocks from {} to {}", blocks.len(), current_block, batch_end);
             
+            if self.fetch_receipts {
+                self.attach_receipts(&mut blocks).await?;
+            }
+            
             // Queue blocks for processing
             for block in blocks {
-                match self.convert_block(block) {
+                match block {
                     Ok(model_block) => {
                         // Push to the queue with throttling if full
                         let mut retry_count = 0;
@@ -354,26 +540,25 @@ impl BlockFetcher {
     async fn fetch_blocks_batch(&self, block_range: impl Iterator<Item = u64> + Clone) -> Result<Vec<ethers::types::Block<ethers::types::H256>>, SyncError> {
         debug!("Creating batch request for multiple blocks");
         
-        let provider = self.provider.clone();
         let retry_delay = self.retry_delay;
         let max_retries = self.max_retries;
         
         // Collect block numbers into a vector to avoid lifetime issues
         let block_numbers: Vec<u64> = block_range.collect();
         
-        // Use with_retry to handle any connection issues
+        // Use with_retry to handle any connection issues, each attempt going to the next endpoint
         with_retry(
-            move || {
-                let provider = provider.clone();
-                let block_numbers = block_numbers.clone();
+            || async {
+                let (endpoint, provider) = self.next_provider().await;
+                self.wait_for_rate_limit(block_numbers.len()).await;
                 
-                async move {
+                let result = async {
                     // Create a batch request
                     let mut batch = Vec::new();
                     
                     // Add block requests to the batch - only fetch transaction hashes, not full transaction data
-                    for block_num in block_numbers {
-                        batch.push(provider.get_block(BlockNumber::Number(block_num.into())));
+                    for block_num in &block_numbers {
+                        batch.push(provider.get_block(BlockNumber::Number((*block_num).into())));
                     }
                     
                     // Execute the batch request
@@ -389,7 +574,10 @@ impl BlockFetcher {
                         .collect::<Result<Vec<_>, _>>()?;
                     
                     Ok::<_, SyncError>(blocks)
-                }
+                }.await;
+                
+                self.record_outcome(endpoint, &result);
+                result
             },
             retry_delay,
             max_retries,
@@ -397,62 +585,154 @@ impl BlockFetcher {
         ).await
     }
     
-    /// Convert ethers block to our model
-    fn convert_block(&self, eth_block: ethers::types::Block<ethers::types::H256>) -> Result<Block, SyncError> {
-        let block_number = eth_block.number
-            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))?
-            .as_u64();
+    /// Fetch a batch of blocks including full transaction objects
+    async fn fetch_full_blocks_batch(&self, block_range: impl Iterator<Item = u64> + Clone) -> Result<Vec<ethers::types::Block<ethers::types::Transaction>>, SyncError> {
+        debug!("Creating batch request for multiple blocks with full transactions");
         
-        debug!("Converting block {} to model", block_number);
+        let block_numbers: Vec<u64> = block_range.collect();
         
-        // Convert transaction hashes to our transaction model
-        let transactions = eth_block.transactions.into_iter()
-            .enumerate()
-            .filter_map(|(i, tx_hash)| {
-                // Basic validation check
-                if tx_hash.as_bytes().is_empty() {
-                    warn!("Skipping transaction with empty hash in block {}", block_number);
-                    return None;
-                }
+        with_retry(
+            || async {
+                let (endpoint, provider) = self.next_provider().await;
+                self.wait_for_rate_limit(block_numbers.len()).await;
                 
-                Some(Transaction {
-                    hash: format!("{:?}", tx_hash), // Convert H256 to string
-                    from: None,    // We don't have this info without fetching full transactions
-                    to: None,      // We don't have this info without fetching full transactions
-                    value: "0".to_string(), // Default value
-                    gas: 0,        // We don't have this info without fetching full transactions
-                    gas_price: None, // We don't have this info without fetching full transactions
-                    input: "0x".to_string(), // We don't have this info without fetching full transactions
-                    nonce: 0,      // We don't have this info without fetching full transactions
-                    transaction_index: i as u64,
-                    block_hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
-                    block_number,
-                })
-            })
-            .collect::<Vec<Transaction>>();
-            
-        // Get transaction count from actual collected transactions
-        let tx_count = transactions.len() as u64;
-        
-        // Create the block model
-        Ok(Block {
-            number: block_number,
-            hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
-            parent_hash: format!("{:?}", eth_block.parent_hash),
-            timestamp: eth_block.timestamp.as_u64(),
-            transactions_root: format!("{:?}", eth_block.transactions_root),
-            state_root: format!("{:?}", eth_block.state_root),
-            receipts_root: format!("{:?}", eth_block.receipts_root),
-            gas_used: eth_block.gas_used.as_u64(),
-            gas_limit: eth_block.gas_limit.as_u64(),
-            base_fee_per_gas: eth_block.base_fee_per_gas.map(|fee| fee.as_u64()),
-            extra_data: format!("0x{}", hex::encode(eth_block.extra_data.to_vec())),
-            miner: format!("{:?}", eth_block.author.unwrap_or_default()),
-            difficulty: eth_block.difficulty,
-            total_difficulty: eth_block.total_difficulty,
-            size: eth_block.size.unwrap_or_default().as_u64(),
-            transaction_count: tx_count,
-            transactions,
+                let result = async {
+                    let batch = block_numbers.iter()
+                        .map(|block_num| provider.get_block_with_txs(BlockNumber::Number((*block_num).into())))
+                        .collect::<Vec<_>>();
+                    
+                    let results = futures::future::try_join_all(batch).await
+                        .map_err(|e| SyncError::Provider(format!("Failed to execute batch request: {}", e)))?;
+                    
+                    results.into_iter()
+                        .enumerate()
+                        .map(|(i, block_opt)| {
+                            block_opt.ok_or_else(|| SyncError::BlockNotFound(i as u64))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                }.await;
+                
+                self.record_outcome(endpoint, &result);
+                result
+            },
+            self.retry_delay,
+            self.max_retries,
+            "fetch_full_blocks_batch",
+        ).await
+    }
+    
+    /// Fetch receipts for the converted blocks and attach them
+    async fn attach_receipts(&self, blocks: &mut [Result<Block, SyncError>]) -> Result<(), SyncError> {
+        let block_numbers = blocks.iter()
+            .filter_map(|block| block.as_ref().ok().map(|block| block.number))
+            .collect::<Vec<_>>();
+        
+        let receipts = self.fetch_receipts_batch(block_numbers).await?;
+        
+        for (block, receipts) in blocks.iter_mut().filter_map(|block| block.as_mut().ok()).zip(receipts) {
+            if !block.attach_receipts(&receipts) {
+                return Err(SyncError::Other(format!(
+                    "Receipts for block {} belong to a different block, the block was likely reorged",
+                    block.number
+                )));
+            }
+            let (_, provider) = self.next_provider().await;
+            attach_deployed_code(provider, block, self.retry_delay, self.max_retries).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Fetch the receipts of a batch of blocks
+    async fn fetch_receipts_batch(&self, block_numbers: Vec<u64>) -> Result<Vec<Vec<TransactionReceipt>>, SyncError> {
+        debug!("Creating batch request for receipts of {} blocks", block_numbers.len());
+        
+        with_retry(
+            || async {
+                let (endpoint, provider) = self.next_provider().await;
+                self.wait_for_rate_limit(block_numbers.len()).await;
+                
+                let batch = block_numbers.iter()
+                    .map(|block_num| provider.get_block_receipts(BlockNumber::Number((*block_num).into())))
+                    .collect::<Vec<_>>();
+                
+                let result = futures::future::try_join_all(batch).await
+                    .map_err(|e| SyncError::Provider(format!("Failed to execute receipts batch request: {}", e)));
+                
+                self.record_outcome(endpoint, &result);
+                result
+            },
+            self.retry_delay,
+            self.max_retries,
+            "fetch_receipts_batch",
+        ).await
+    }
+    
+    /// Convert ethers block with full transactions to our model
+    fn convert_full_block(&self, mut eth_block: ethers::types::Block<ethers::types::Transaction>) -> Result<Block, SyncError> {
+        let transactions = std::mem::take(&mut eth_block.transactions)
+            .into_iter()
+            .map(Transaction::from)
+            .collect();
+        
+        Block::from_eth_block(&eth_block, transactions)
+            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))
+    }
+    
+    /// Convert ethers block to our model
+    fn convert_block(&self, eth_block: ethers::types::Block<ethers::types::H256>) -> Result<Block, SyncError> {
+        let transactions = eth_block.transactions.iter()
+            .enumerate()
+            .map(|(i, &hash)| Transaction::from_hash(hash, i, &eth_block))
+            .collect();
+        
+        Block::from_eth_block(&eth_block, transactions)
+            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))
+    }
+}
+
+/// Fetch the runtime bytecode of each contract created in the block (`eth_getCode` at that
+/// block) and attach it to the creating receipt. Blocks without contract creations cost nothing.
+pub async fn attach_deployed_code<M: Middleware>(
+    provider: &M,
+    block: &mut Block,
+    retry_delay: u64,
+    max_retries: u32,
+) -> Result<(), SyncError>
+where
+    M::Error: std::fmt::Display
+{
+    let block_number = block.number;
+    let creations = block.receipts.iter_mut()
+        .filter_map(|receipt| {
+            let address = receipt.created_contract()?.parse::<Address>().ok()?;
+            Some((address, receipt))
         })
+        .collect::<Vec<_>>();
+
+    if creations.is_empty() {
+        return Ok(());
     }
-}
\ No newline at end of file
+
+    debug!("Fetching code of {} contracts created in block {}", creations.len(), block_number);
+
+    let addresses = creations.iter().map(|(address, _)| *address).collect::<Vec<_>>();
+    let codes = with_retry(
+        || async {
+            let requests = addresses.iter()
+                .map(|address| provider.get_code(*address, Some(BlockId::Number(BlockNumber::Number(block_number.into())))));
+
+            futures::future::try_join_all(requests).await
+                .map_err(|e| SyncError::Provider(format!("Failed to get contract code for block {}: {}", block_number, e)))
+        },
+        retry_delay,
+        max_retries,
+        &format!("fetch_deployed_code_{}", block_number),
+    ).await?;
+
+    for ((_, receipt), code) in creations.into_iter().zip(codes) {
+        receipt.deployed_code = Some(format!("0x{}", hex::encode(&code)));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+use ethers::providers::{Provider, Http, JsonRpcError, Middleware, ProviderError};
+use ethers::types::BlockNumber;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::db::Database;
+use crate::models::Finality;
+use crate::sync::SyncError;
+use crate::utils::rpc;
+
+/// Block tags tracked by the finality tracker, with the watermark names they are stored under
+const TRACKED_TAGS: &[(BlockNumber, &[&str])] = &[
+    (BlockNumber::Finalized, &["finalized"]),
+    (BlockNumber::Safe, &["safe"]),
+];
+
+/// On chains with instant finality the latest block is both finalized and safe
+const INSTANT_TAGS: &[(BlockNumber, &[&str])] = &[
+    (BlockNumber::Latest, &["finalized", "safe"]),
+];
+
+/// JSON-RPC error code for invalid params, returned by some nodes for block tags they don't know
+const INVALID_PARAMS_CODE: i64 = -32602;
+
+/// Periodically records the `finalized` and `safe` block tags as watermarks,
+/// so consumers can tell confirmed blocks apart from ones still subject to reorg
+pub struct FinalityTracker {
+    /// HTTP provider used to query block tags
+    provider: Provider<Http>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between watermark refreshes
+    poll_interval: Duration,
+    /// Tags queried, with the watermarks each one sets
+    tags: &'static [(BlockNumber, &'static [&'static str])],
+}
+
+impl FinalityTracker {
+    /// Create a new finality tracker
+    pub fn new(http_provider_url: &str, db: Arc<Database>) -> Result<Self, SyncError> {
+        let provider = rpc::http_provider(http_provider_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
+
+        Ok(Self {
+            provider,
+            db,
+            poll_interval: Duration::from_secs(12), // Default to roughly one L1 slot
+            tags: TRACKED_TAGS,
+        })
+    }
+
+    /// Configure the interval between watermark refreshes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting finality poll interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Configure how finality is determined; `Finality::None` is handled by not starting the tracker
+    pub fn with_finality(mut self, finality: Finality) -> Self {
+        info!("Setting finality to {}", finality.as_str());
+        self.tags = match finality {
+            Finality::Instant => INSTANT_TAGS,
+            Finality::Tags | Finality::None => TRACKED_TAGS,
+        };
+        self
+    }
+
+    /// Spawn the tracker as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting finality tracker");
+
+        tokio::spawn(async move {
+            // Tags the node has rejected; warn once and stop querying them
+            let mut unsupported: Vec<BlockNumber> = Vec::new();
+
+            loop {
+                for (tag, names) in self.tags {
+                    if unsupported.contains(tag) {
+                        continue;
+                    }
+
+                    match self.refresh_tag(*tag, names).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            warn!("Node doesn't support the '{}' tag, no longer tracking it", tag);
+                            unsupported.push(*tag);
+                        }
+                        Err(e) => {
+                            error!("Failed to refresh '{}' watermark: {}", tag, e);
+                        }
+                    }
+                }
+
+                if unsupported.len() == self.tags.len() {
+                    warn!("Node supports none of the finality tags, stopping finality tracker");
+                    break;
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    /// Fetch the block for a tag and store it under each of the watermark names.
+    /// Returns false if the node has no block for the tag or rejects the tag.
+    async fn refresh_tag(&self, tag: BlockNumber, names: &[&str]) -> Result<bool, SyncError> {
+        let block = match self.provider.get_block(tag).await {
+            Ok(block) => block,
+            Err(ProviderError::JsonRpcClientError(e)) if e.as_error_response().is_some_and(is_unsupported_tag) => None,
+            Err(e) => return Err(SyncError::Provider(format!("Failed to get '{}' block: {}", tag, e))),
+        };
+
+        let Some(block) = block else {
+            return Ok(false);
+        };
+
+        let block_number = block.number
+            .ok_or_else(|| SyncError::Parse(format!("'{}' block number missing", tag)))?
+            .as_u64();
+        let block_hash = format!("{:?}", block.hash.unwrap_or_default());
+
+        debug!("'{}' block is #{}", tag, block_number);
+
+        for name in names {
+            self.db.save_watermark(name, block_number, &block_hash)
+                .await
+                .map_err(|e| SyncError::Other(format!("Failed to save '{}' watermark: {}", name, e)))?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Whether the node rejected a block tag as unknown, as nodes predating the `finalized` and
+/// `safe` tags do, rather than failing to serve it
+fn is_unsupported_tag(error: &JsonRpcError) -> bool {
+    let message = error.message.to_lowercase();
+    error.code == INVALID_PARAMS_CODE || message.contains("unknown block") || message.contains("invalid block tag")
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{AuditEvent, Database, JournalEntry};
+use crate::models::{BlockQueue, BlockProcessor};
+use crate::sync::{BlockFetcher, SharedSyncState, SyncError};
+use crate::utils::rate_limit::RateLimiter;
+
+/// Number of blocks behind the latest synced block left out of each scan,
+/// so blocks still in flight through the live sync queue aren't reported as gaps
+const IN_FLIGHT_MARGIN: u64 = 10;
+
+/// Reason recorded with the gaps in `incomplete_ranges`
+const INCOMPLETE_RANGE_REASON: &str = "gap";
+
+/// Counter of blocks re-fetched by the gap repairer
+const REPAIRED_BLOCKS_METRIC: &str = "indexer_gap_blocks_repaired_total";
+
+/// Background task that scans the blocks table for missing blocks and parent hash
+/// mismatches and re-fetches them, since fetch workers drop failed batches
+pub struct GapRepairer {
+    /// WebSocket URLs used to create the block fetcher
+    ws_provider_urls: Vec<String>,
+    /// HTTP URL for the block fetcher when the WebSocket endpoints are unavailable
+    http_provider_url: Option<String>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Shared state between sync components
+    sync_state: SharedSyncState,
+    /// Interval between scans
+    scan_interval: Duration,
+    /// RPC batch size used when re-fetching blocks
+    rpc_batch_size: usize,
+    /// Retry delay for failed requests (ms)
+    retry_delay: u64,
+    /// Maximum number of retries for failed requests
+    max_retries: u32,
+    /// Fetch full transaction objects instead of only hashes
+    full_transactions: bool,
+    /// Fetch transaction receipts for each block
+    fetch_receipts: bool,
+    /// Block queue for repaired blocks
+    block_queue: Arc<BlockQueue>,
+    /// Block processor persisting repaired blocks
+    block_processor: Arc<BlockProcessor>,
+    /// Shared limit on the fetcher's request rate
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl GapRepairer {
+    /// Create a new gap repairer
+    pub fn new(ws_provider_url: String, db: Arc<Database>, sync_state: SharedSyncState) -> Self {
+        let block_queue = Arc::new(BlockQueue::with_capacity(1000));
+        let block_processor = Arc::new(BlockProcessor::new(block_queue.clone_queue()));
+
+        Self {
+            ws_provider_urls: vec![ws_provider_url],
+            http_provider_url: None,
+            db,
+            sync_state,
+            scan_interval: Duration::from_secs(300), // Default 5 minutes
+            rpc_batch_size: 10, // Default 10 blocks per RPC batch
+            retry_delay: 1000, // Default 1 second
+            max_retries: 5,    // Default 5 retries
+            full_transactions: false,
+            fetch_receipts: false,
+            block_queue,
+            block_processor,
+            rate_limiter: None,
+        }
+    }
+
+    /// Configure the interval between scans
+    pub fn with_scan_interval(mut self, seconds: u64) -> Self {
+        info!("Setting gap scan interval to {}s", seconds);
+        self.scan_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Spread block fetches across these WebSocket endpoints
+    pub fn with_ws_endpoints(mut self, urls: Vec<String>) -> Self {
+        self.ws_provider_urls = urls;
+        self
+    }
+
+    /// Fetch over HTTP from this URL when no WebSocket connection can be made
+    pub fn with_http_fallback(mut self, http_provider_url: String) -> Self {
+        self.http_provider_url = Some(http_provider_url);
+        self
+    }
+
+    /// Configure RPC batch size
+    pub fn with_rpc_batch_size(mut self, rpc_batch_size: usize) -> Self {
+        self.rpc_batch_size = rpc_batch_size;
+        self
+    }
+
+    /// Configure retry settings
+    pub fn with_retry_settings(mut self, retry_delay: u64, max_retries: u32) -> Self {
+        self.retry_delay = retry_delay;
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Fetch full transaction objects instead of only hashes
+    pub fn with_full_transactions(mut self, full_transactions: bool) -> Self {
+        self.full_transactions = full_transactions;
+        self
+    }
+
+    /// Fetch transaction receipts for each block
+    pub fn with_receipts(mut self, fetch_receipts: bool) -> Self {
+        self.fetch_receipts = fetch_receipts;
+        self
+    }
+
+    /// Limit the request rate of the block fetcher with a limiter shared with other components
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Spawn the repairer as a background task
+    pub async fn start(self) -> JoinHandle<()> {
+        info!("Starting gap repairer");
+
+        // A single worker is enough for the occasional repaired block
+        self.block_processor.start(Arc::clone(&self.db)).await;
+
+        tokio::spawn(async move {
+            loop {
+                self.db.idle_mode().sleep(self.scan_interval).await;
+
+                // Historic sync writes out of order; only scan once it has finished
+                let (historic_sync_complete, latest_synced_block) = {
+                    let state = self.sync_state.lock().await;
+                    (state.historic_sync_complete, state.latest_synced_block)
+                };
+
+                if !historic_sync_complete {
+                    debug!("Historic sync still running, skipping gap scan");
+                    continue;
+                }
+
+                if let Err(e) = self.scan_and_repair(latest_synced_block.saturating_sub(IN_FLIGHT_MARGIN)).await {
+                    error!("Gap scan failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Scan for gaps and mismatches up to `scan_end` and re-fetch affected blocks
+    async fn scan_and_repair(&self, scan_end: u64) -> Result<(), SyncError> {
+        let Some(scan_start) = self.db.get_earliest_block_number().await? else {
+            debug!("No blocks in database yet, skipping gap scan");
+            return Ok(());
+        };
+
+        if scan_start >= scan_end {
+            return Ok(());
+        }
+
+        info!("Scanning blocks {} to {} for gaps", scan_start, scan_end);
+
+        let mut repairs = self.db.find_missing_block_ranges(scan_start, scan_end).await?;
+
+        // Gaps stay flagged until a later scan no longer finds them
+        if let Err(e) = self.db.replace_incomplete_ranges(INCOMPLETE_RANGE_REASON, &repairs).await {
+            warn!("Failed to record gaps as incomplete ranges: {}", e);
+        }
+
+        // Re-fetch both sides of a parent hash mismatch, as either may be the stale one
+        for block_number in self.db.find_parent_hash_mismatches(scan_start, scan_end).await? {
+            warn!("Block {} parent hash does not match stored block {}", block_number, block_number - 1);
+            repairs.push((block_number - 1, block_number));
+        }
+
+        if repairs.is_empty() {
+            info!("No gaps found between blocks {} and {}", scan_start, scan_end);
+            return Ok(());
+        }
+
+        let missing_blocks: u64 = repairs.iter().map(|(start, end)| end - start + 1).sum();
+        warn!("Found {} ranges to repair ({} blocks)", repairs.len(), missing_blocks);
+
+        let fetcher = match BlockFetcher::from_ws_urls(
+            &self.ws_provider_urls,
+            Arc::clone(&self.block_queue),
+            self.rpc_batch_size,
+            self.retry_delay,
+            self.max_retries,
+        ).await {
+            Ok(fetcher) => fetcher,
+            Err(e) => {
+                let Some(http_provider_url) = &self.http_provider_url else {
+                    return Err(e);
+                };
+
+                // Fall back to HTTP so gaps are still repaired while the WebSocket endpoints are down
+                warn!("Failed to create WebSocket fetcher: {}. Falling back to HTTP", e);
+                self.db.record_journal(
+                    JournalEntry::new("gap_repairer", "http_fetcher_fallback")
+                        .with_details(serde_json::json!({ "error": e.to_string() }))
+                );
+
+                BlockFetcher::from_http_url(
+                    http_provider_url,
+                    Arc::clone(&self.block_queue),
+                    self.rpc_batch_size,
+                    self.retry_delay,
+                    self.max_retries,
+                )?
+            }
+        }
+        .with_max_concurrent_batches(1)
+        .with_full_transactions(self.full_transactions)
+        .with_receipts(self.fetch_receipts)
+        .with_rate_limiter(self.rate_limiter.clone());
+
+        let mut repaired_blocks = 0;
+        let mut failed_blocks = 0;
+        for &(start, end) in &repairs {
+            info!("Repairing blocks {} to {}", start, end);
+            let failed: u64 = fetcher.fetch_blocks_range(start, end).await?
+                .iter()
+                .map(|(start, end)| end - start + 1)
+                .sum();
+            metrics::counter!(REPAIRED_BLOCKS_METRIC, end - start + 1 - failed);
+            repaired_blocks += end - start + 1 - failed;
+            failed_blocks += failed;
+        }
+
+        self.db.record_audit_event(
+            AuditEvent::new("gap_repairer", "gaps_repaired")
+                .with_details(serde_json::json!({
+                    "ranges": repairs,
+                    "blocks": missing_blocks,
+                    "repaired": repaired_blocks,
+                    "failed": failed_blocks,
+                }))
+        ).await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::db::Database;
+
+/// Blocks saved up to this long before a pass started are picked up again by the next one,
+/// since `updated_at` is the start time of a save transaction that may commit later
+const OVERLAP_SECS: i64 = 60;
+
+/// Maintains hourly and daily aggregates of gas used, gas limit utilization and base fee
+/// in `gas_rollups`, so gas charts don't have to scan `blocks`.
+///
+/// Each pass re-aggregates the buckets containing blocks saved since the previous pass, so
+/// backfilled and reorged blocks are reflected as well as new ones.
+pub struct GasRollupWorker {
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between passes
+    poll_interval: Duration,
+}
+
+impl GasRollupWorker {
+    /// Create a new gas rollup worker
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            poll_interval: Duration::from_secs(60), // Default 1 minute
+        }
+    }
+
+    /// Configure the interval between passes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting gas rollup interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Spawn the worker as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting gas rollup worker");
+
+        tokio::spawn(async move {
+            // Resume from the last refresh; without one every bucket is built
+            let mut since = match self.db.get_gas_rollups_refreshed_at().await {
+                Ok(refreshed_at) => refreshed_at.map(|at| at - chrono::Duration::seconds(OVERLAP_SECS)),
+                Err(e) => {
+                    error!("Failed to read last gas rollup refresh, rebuilding all buckets: {}", e);
+                    None
+                }
+            };
+            if since.is_none() {
+                info!("Building gas rollups for all stored blocks");
+            }
+
+            loop {
+                let started = chrono::Utc::now();
+
+                match self.db.refresh_gas_rollups(since).await {
+                    Ok(buckets) => {
+                        debug!("Refreshed {} gas rollup buckets", buckets);
+                        since = Some(started - chrono::Duration::seconds(OVERLAP_SECS));
+                    }
+                    // Retried from the same point on the next pass
+                    Err(e) => error!("Gas rollup pass failed: {}", e),
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+}
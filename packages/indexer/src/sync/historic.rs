@@ -6,16 +6,23 @@ use ethers::types::BlockNumber;
 use tokio::time::{sleep, Duration, Instant};
 use tokio::task::JoinHandle;
 
-use crate::db::Database;
-use crate::models::{Block, Transaction, BlockQueue, BlockProcessor};
-use crate::utils::retry::with_retry;
+use crate::db::{AuditEvent, Database, JournalEntry, SyncRun};
+use crate::models::{Block, Transaction, BlockQueue, BlockProcessor, CommitOrder};
+use crate::utils::retry::{is_pruned_error, with_retry};
+use crate::utils::format::format_integer;
+use crate::utils::rate_limit::RateLimiter;
 use crate::utils::time::{format_duration, format_rate};
 use crate::sync::{SyncError, SharedSyncState, BlockFetcher};
+use crate::utils::rpc;
+
+/// Reason recorded with the backfill range in `incomplete_ranges`
+const INCOMPLETE_RANGE_REASON: &str = "historic_sync";
 
 /// Component responsible for historical sync
 pub struct HistoricSync {
     provider: Provider<Http>, // Keep HTTP provider for fallback purposes
-    ws_provider_url: String,  // WebSocket URL for creating WS connections
+    http_provider_url: String, // HTTP URL for the fetcher when WebSockets are unavailable
+    ws_provider_urls: Vec<String>,  // WebSocket URLs for creating WS connections
     db: Arc<Database>,
     sync_state: SharedSyncState,
     batch_size: usize,
@@ -25,7 +32,12 @@ pub struct HistoricSync {
     rpc_batch_size: usize,
     block_queue: Arc<BlockQueue>,
     block_processor: Arc<BlockProcessor>,
+    commit_order: Option<Arc<CommitOrder>>, // Reorder buffer, if blocks are persisted in order
     max_concurrent_batches: usize,
+    full_transactions: bool,
+    fetch_receipts: bool,
+    rate_limiter: Option<Arc<RateLimiter>>, // Shared limit on the fetchers' request rate
+    end_block: Option<u64>, // Last block to sync instead of the chain tip
 }
 
 impl HistoricSync {
@@ -39,7 +51,7 @@ impl HistoricSync {
         block_queue_size: usize,
     ) -> Result<Self, SyncError> {
         // Create an HTTP provider with ethers
-        let provider = Provider::<Http>::try_from(provider_url.clone())
+        let provider = rpc::http_provider(&provider_url)
             .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
             
         // Store the WebSocket URL - convert http to ws if not provided
@@ -69,7 +81,8 @@ impl HistoricSync {
             
         Ok(Self {
             provider,
-            ws_provider_url,
+            http_provider_url: provider_url,
+            ws_provider_urls: vec![ws_provider_url],
             db: db.clone(),
             sync_state,
             batch_size,
@@ -79,7 +92,12 @@ impl HistoricSync {
             rpc_batch_size: 10, // Default 10 blocks per RPC batch
             block_queue,
             block_processor,
+            commit_order: None,
             max_concurrent_batches: 5, // Default to 5 concurrent batches
+            full_transactions: false,
+            fetch_receipts: false,
+            rate_limiter: None,
+            end_block: None,
         })
     }
     
@@ -98,6 +116,13 @@ impl HistoricSync {
         self
     }
     
+    /// Spread block fetches across these WebSocket endpoints
+    pub fn with_ws_endpoints(mut self, urls: Vec<String>) -> Self {
+        info!("Fetching blocks from {} WebSocket endpoints", urls.len());
+        self.ws_provider_urls = urls;
+        self
+    }
+    
     /// Configure maximum concurrent batches
     pub fn with_max_concurrent_batches(mut self, max_concurrent_batches: usize) -> Self {
         info!("Setting maximum concurrent batches to {}", max_concurrent_batches);
@@ -105,6 +130,42 @@ impl HistoricSync {
         self
     }
     
+    /// Fetch full transaction objects instead of only hashes
+    pub fn with_full_transactions(mut self, full_transactions: bool) -> Self {
+        self.full_transactions = full_transactions;
+        self
+    }
+    
+    /// Fetch transaction receipts for each block
+    pub fn with_receipts(mut self, fetch_receipts: bool) -> Self {
+        self.fetch_receipts = fetch_receipts;
+        self
+    }
+    
+    /// Limit the request rate of the block fetcher with a limiter shared with other components
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+    
+    /// Persist blocks strictly in ascending order through a reorder buffer
+    pub fn with_commit_order(mut self, commit_order: Option<Arc<CommitOrder>>) -> Self {
+        self.block_processor = Arc::new(
+            BlockProcessor::new(self.block_queue.clone_queue()).with_commit_order(commit_order.clone())
+        );
+        self.commit_order = commit_order;
+        self
+    }
+    
+    /// Sync up to `end_block` instead of the chain tip, waiting for the chain to reach it
+    pub fn with_end_block(mut self, end_block: Option<u64>) -> Self {
+        if let Some(end_block) = end_block {
+            info!("Syncing up to END_BLOCK {}", end_block);
+        }
+        self.end_block = end_block;
+        self
+    }
+    
     /// Start the block processor
     pub async fn start_processor(&self, workers: usize) {
         info!("Starting block processor with {} workers", workers);
@@ -126,17 +187,40 @@ impl HistoricSync {
         let latest_block_number = self.get_latest_block_number().await?;
         info!("Latest block on chain: {}", latest_block_number);
         
+        // A bounded sync ends at END_BLOCK, once the chain has produced it
+        let latest_block_number = match self.end_block {
+            Some(end_block) if end_block > latest_block_number => self.wait_for_block(end_block).await?,
+            Some(end_block) => end_block,
+            None => latest_block_number,
+        };
+        
         // Get the block to start syncing from
         let start_block = {
             let state = self.sync_state.lock().await;
             state.latest_synced_block
         };
         
+        // A node that pruned old blocks can't serve the start of the range, begin at its earliest block
+        let start_block = self.clamp_to_available_blocks(start_block, latest_block_number).await;
+        {
+            let mut state = self.sync_state.lock().await;
+            state.sync_start = state.sync_start.max(start_block);
+        }
+        
         info!("Starting historical sync from block {} to {}", start_block, latest_block_number);
         
         // If we're already at the latest block, mark as complete
         if start_block >= latest_block_number {
+            // Live sync picks up after the start block
+            if let Some(commit_order) = &self.commit_order {
+                commit_order.start_at(start_block + 1).await;
+            }
+
             info!("Already at latest block, marking historic sync as complete");
+            // A previous run may have stopped before clearing its backfill range
+            if let Err(e) = self.db.replace_incomplete_ranges(INCOMPLETE_RANGE_REASON, &[]).await {
+                warn!("Failed to clear backfill incomplete range: {}", e);
+            }
             let mut state = self.sync_state.lock().await;
             state.historic_sync_complete = true;
             return Ok(());
@@ -144,25 +228,54 @@ impl HistoricSync {
         
         // Create block fetcher using WebSocket connection
         info!("Creating block fetcher with WebSocket connection");
-        let fetcher = match BlockFetcher::from_ws_url(
-            &self.ws_provider_url,
+        let fetcher = match BlockFetcher::from_ws_urls(
+            &self.ws_provider_urls,
             Arc::clone(&self.block_queue),
             self.rpc_batch_size,
             self.retry_delay,
             self.max_retries,
         ).await {
-            Ok(fetcher) => fetcher
-                .with_max_concurrent_batches(self.max_concurrent_batches)
-                .with_worker_stagger_delay(100), // Add a 100ms stagger between worker startup
+            Ok(fetcher) => fetcher,
             Err(e) => {
                 // If WebSocket connection fails, fall back to HTTP
                 warn!("Failed to create WebSocket fetcher: {}. Falling back to HTTP", e);
+                self.db.record_journal(
+                    JournalEntry::new("historic_sync", "http_fetcher_fallback")
+                        .with_details(serde_json::json!({ "error": e.to_string() }))
+                );
                 
-                // We don't currently have a way to create an HTTP fetcher directly in the new architecture
-                // So we'll need to implement that path
-                return Err(SyncError::Provider(format!("WebSocket connection failed and HTTP fallback not implemented yet: {}", e)));
+                BlockFetcher::from_http_url(
+                    &self.http_provider_url,
+                    Arc::clone(&self.block_queue),
+                    self.rpc_batch_size,
+                    self.retry_delay,
+                    self.max_retries,
+                )?
             }
-        };
+        }
+        .with_max_concurrent_batches(self.max_concurrent_batches)
+        .with_worker_stagger_delay(100) // Add a 100ms stagger between worker startup
+        .with_full_transactions(self.full_transactions)
+        .with_receipts(self.fetch_receipts)
+        .with_rate_limiter(self.rate_limiter.clone());
+        
+        if let Some(commit_order) = &self.commit_order {
+            commit_order.start_at(start_block).await;
+        }
+        
+        let started_at = chrono::Utc::now();
+        let started = Instant::now();
+        let db_size_before = self.get_database_size().await;
+        
+        self.db.record_audit_event(
+            AuditEvent::new("historic_sync", "historic_sync_started")
+                .with_details(serde_json::json!({ "fromBlock": start_block, "toBlock": latest_block_number }))
+        ).await;
+        
+        // Flag the range as incomplete until it is fully stored, so stats over it can be annotated
+        if let Err(e) = self.db.replace_incomplete_ranges(INCOMPLETE_RANGE_REASON, &[(start_block, latest_block_number)]).await {
+            warn!("Failed to record backfill range as incomplete: {}", e);
+        }
         
         // Start the ETA monitoring worker
         let eta_monitor_handle = self.start_eta_monitor(
@@ -172,7 +285,7 @@ impl HistoricSync {
         );
         
         // Process blocks in batches using concurrent fetching
-        self.process_blocks_concurrent(start_block, latest_block_number, &fetcher).await?;
+        let mut failed_ranges = self.process_blocks_concurrent(start_block, latest_block_number, &fetcher).await?;
         
         // Stop the ETA monitor
         eta_monitor_handle.abort();
@@ -180,6 +293,24 @@ impl HistoricSync {
         // Wait for the queue to be fully processed
         self.wait_for_queue_to_empty().await?;
         
+        // Fetch blocks that didn't make it into the database once more, e.g. from failed batches
+        // Nothing is stored in a dry run, so there are no gaps to look for
+        let gaps = if self.db.dry_run() {
+            Vec::new()
+        } else {
+            self.db.find_missing_block_ranges(start_block, latest_block_number).await?
+        };
+        let gaps_detected = count_blocks(&gaps);
+        let mut remaining_gaps = gaps.clone();
+        if !gaps.is_empty() {
+            warn!("{} blocks missing after historic sync, repairing {} ranges", gaps_detected, gaps.len());
+            for (start, end) in &gaps {
+                failed_ranges.extend(fetcher.fetch_blocks_range(*start, *end).await?);
+            }
+            self.wait_for_queue_to_empty().await?;
+            remaining_gaps = self.db.find_missing_block_ranges(start_block, latest_block_number).await?;
+        }
+        
         // Mark historical sync as complete
         {
             let mut state = self.sync_state.lock().await;
@@ -187,10 +318,146 @@ impl HistoricSync {
             state.latest_synced_block = latest_block_number;
         }
         
+        // Blocks still missing are left flagged until the gap repairer stores them
+        if let Err(e) = self.db.replace_incomplete_ranges(INCOMPLETE_RANGE_REASON, &remaining_gaps).await {
+            warn!("Failed to update backfill incomplete range: {}", e);
+        }
+        
+        self.db.record_audit_event(
+            AuditEvent::new("historic_sync", "historic_sync_completed")
+                .with_block_number(latest_block_number)
+        ).await;
+        
+        let duration = started.elapsed();
+        let total_blocks = latest_block_number - start_block + 1;
+        let blocks_missing = count_blocks(&remaining_gaps);
+        let run = SyncRun {
+            started_at,
+            finished_at: chrono::Utc::now(),
+            from_block: start_block,
+            to_block: latest_block_number,
+            blocks_persisted: total_blocks - blocks_missing,
+            gaps_detected,
+            gaps_repaired: gaps_detected.saturating_sub(blocks_missing),
+            failed_ranges,
+            duration_ms: duration.as_millis() as u64,
+            blocks_per_second: total_blocks as f64 / duration.as_secs_f64().max(0.001),
+            db_size_before,
+            db_size_after: self.get_database_size().await,
+        };
+        log_completion_report(&run);
+        if let Err(e) = self.db.save_sync_run(&run).await {
+            warn!("Failed to record sync run: {}", e);
+        }
+        
         info!("Historical sync completed successfully up to block {}", latest_block_number);
         Ok(())
     }
     
+    /// Wait until the chain has produced `block_number`, returning it
+    async fn wait_for_block(&self, block_number: u64) -> Result<u64, SyncError> {
+        loop {
+            let latest_block_number = self.get_latest_block_number().await?;
+            if latest_block_number >= block_number {
+                return Ok(block_number);
+            }
+            
+            info!("Waiting for the chain to reach END_BLOCK {} (at block {})", block_number, latest_block_number);
+            sleep(Duration::from_secs(5)).await;
+        }
+    }
+    
+    /// Move the start of the backfill past blocks the node pruned, recording the earliest
+    /// block it can serve. Keeps the start block if availability can't be checked.
+    async fn clamp_to_available_blocks(&self, start_block: u64, latest_block_number: u64) -> u64 {
+        // Nothing to backfill, e.g. when the database is ahead of a node that was reset
+        if start_block >= latest_block_number {
+            return start_block;
+        }
+
+        let earliest_available = match self.find_earliest_available_block(start_block, latest_block_number).await {
+            Ok(earliest_available) => earliest_available,
+            Err(e) => {
+                warn!("Failed to check which blocks the node can serve: {}", e);
+                return start_block;
+            }
+        };
+        
+        if let Err(e) = self.db.save_earliest_available_block(earliest_available).await {
+            warn!("Failed to record earliest available block: {}", e);
+        }
+        
+        let Some(earliest_available) = earliest_available else {
+            return start_block;
+        };
+        
+        warn!(
+            "Node pruned blocks before {}, skipping blocks {} to {} of the backfill",
+            earliest_available, start_block, earliest_available - 1
+        );
+        self.db.record_audit_event(
+            AuditEvent::new("historic_sync", "node_pruning_detected")
+                .with_block_number(earliest_available)
+                .with_details(serde_json::json!({
+                    "requestedStartBlock": start_block,
+                    "earliestAvailableBlock": earliest_available,
+                }))
+        ).await;
+        
+        earliest_available
+    }
+    
+    /// Earliest block from `start_block` the node can serve, found by bisecting up to the
+    /// latest block. None if the start block itself is available.
+    async fn find_earliest_available_block(&self, start_block: u64, latest_block_number: u64) -> Result<Option<u64>, SyncError> {
+        if self.is_block_available(start_block).await? {
+            return Ok(None);
+        }
+        
+        // `unavailable` is always pruned, `available` is always served
+        let (mut unavailable, mut available) = (start_block, latest_block_number);
+        while available - unavailable > 1 {
+            let middle = unavailable + (available - unavailable) / 2;
+            if self.is_block_available(middle).await? {
+                available = middle;
+            } else {
+                unavailable = middle;
+            }
+        }
+        
+        Ok(Some(available))
+    }
+    
+    /// Whether the node can serve a block, false if it answers with a pruning error or no block
+    async fn is_block_available(&self, block_number: u64) -> Result<bool, SyncError> {
+        let result = with_retry(
+            || async {
+                self.provider.get_block(block_number).await
+                    .map_err(|e| SyncError::Provider(format!("Failed to get block {}: {}", block_number, e)))
+            },
+            self.retry_delay,
+            self.max_retries,
+            "is_block_available",
+        ).await;
+        
+        match result {
+            Ok(block) => Ok(block.is_some()),
+            Err(e) if is_pruned_error(&e.to_string()) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Size of the database in bytes, None if it can't be read
+    async fn get_database_size(&self) -> Option<i64> {
+        match self.db.get_database_size().await {
+            Ok(size) => size,
+            Err(e) => {
+                warn!("Failed to read database size: {}", e);
+                None
+            }
+        }
+    }
+    
     /// Wait for the block queue to be fully processed
     async fn wait_for_queue_to_empty(&self) -> Result<(), SyncError> {
         info!("Waiting for block queue to be fully processed...");
@@ -354,9 +621,9 @@ impl HistoricSync {
                 
                 // Main progress stats
                 info!("📊 PROGRESS: {}/{} blocks ({:.2}%)", 
-                    blocks_synced_total, new_total_blocks, progress_pct);
+                    format_integer(blocks_synced_total), format_integer(new_total_blocks), progress_pct);
                 
-                info!("🔄 REMAINING: {} blocks", blocks_remaining);
+                info!("🔄 REMAINING: {} blocks", format_integer(blocks_remaining));
                 
                 // Short-term ETA (more responsive to recent performance)
                 info!("⚡ RECENT RATE: {} (last 30s)", format_rate(short_term_rate));
@@ -393,7 +660,7 @@ impl HistoricSync {
                         end_separator
                     );
                     info!("✅ Successfully synced all {} blocks! Current block: {}", 
-                        blocks_synced_total, current_synced_block);
+                        format_integer(blocks_synced_total), current_synced_block);
                     info!("🚀 Final average speed: {}", format_rate(overall_rate));
                     info!("⏱️  Total sync time: {}", format_duration(total_seconds));
                     info!("{}\n", end_separator);
@@ -407,8 +674,9 @@ impl HistoricSync {
     }
     
     
-    /// Process blocks from start to end using concurrent fetching
-    async fn process_blocks_concurrent(&self, start_block: u64, end_block: u64, fetcher: &BlockFetcher) -> Result<(), SyncError> {
+    /// Process blocks from start to end using concurrent fetching, returning the ranges
+    /// of batches that failed
+    async fn process_blocks_concurrent(&self, start_block: u64, end_block: u64, fetcher: &BlockFetcher) -> Result<Vec<(u64, u64)>, SyncError> {
         let total_blocks = end_block.saturating_sub(start_block) + 1;
         info!(
             "Processing {} blocks from {} to {} with RPC batch size {}",
@@ -417,6 +685,7 @@ impl HistoricSync {
         
         let mut current_block = start_block;
         let mut processed_blocks = 0;
+        let mut failed_ranges = Vec::new();
         
         while current_block <= end_block {
             let batch_end = std::cmp::min(current_block + self.batch_size as u64 - 1, end_block);
@@ -438,7 +707,7 @@ impl HistoricSync {
             );
             
             // Use the fetcher to concurrently fetch all blocks in this batch
-            fetcher.fetch_blocks_range(current_block, batch_end).await?;
+            failed_ranges.extend(fetcher.fetch_blocks_range(current_block, batch_end).await?);
             
             // Update processed count
             processed_blocks += batch_size;
@@ -463,7 +732,7 @@ impl HistoricSync {
         }
         
         info!("All blocks processed successfully");
-        Ok(())
+        Ok(failed_ranges)
     }
     
     /// Throttle the processing if the queue is getting full
@@ -619,54 +888,42 @@ impl HistoricSync {
     /// Convert ethers block to our model
     #[allow(dead_code)]
     fn convert_block(&self, eth_block: ethers::types::Block<ethers::types::H256>) -> Result<Block, SyncError> {
-        let block_number = eth_block.number
-            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))?
-            .as_u64();
-        
-        debug!("Converting block {} to model", block_number);
-        
-        // Get transaction count from the block
-        let tx_count = eth_block.transactions.len() as u64;
-        
-        // Convert transaction hashes to our transaction model
-        let transactions = eth_block.transactions.into_iter()
+        let transactions = eth_block.transactions.iter()
             .enumerate()
-            .map(|(i, tx_hash)| {
-                Transaction {
-                    hash: format!("{:?}", tx_hash), // Convert H256 to string
-                    from: None,    // We don't have this info without fetching full transactions
-                    to: None,      // We don't have this info without fetching full transactions
-                    value: "0".to_string(), // Default value
-                    gas: 0,        // We don't have this info without fetching full transactions
-                    gas_price: None, // We don't have this info without fetching full transactions
-                    input: "0x".to_string(), // We don't have this info without fetching full transactions
-                    nonce: 0,      // We don't have this info without fetching full transactions
-                    transaction_index: i as u64,
-                    block_hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
-                    block_number,
-                }
-            })
+            .map(|(i, &hash)| Transaction::from_hash(hash, i, &eth_block))
             .collect();
         
-        // Create the block model
-        Ok(Block {
-            number: block_number,
-            hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
-            parent_hash: format!("{:?}", eth_block.parent_hash),
-            timestamp: eth_block.timestamp.as_u64(),
-            transactions_root: format!("{:?}", eth_block.transactions_root),
-            state_root: format!("{:?}", eth_block.state_root),
-            receipts_root: format!("{:?}", eth_block.receipts_root),
-            gas_used: eth_block.gas_used.as_u64(),
-            gas_limit: eth_block.gas_limit.as_u64(),
-            base_fee_per_gas: eth_block.base_fee_per_gas.map(|fee| fee.as_u64()),
-            extra_data: format!("0x{}", hex::encode(eth_block.extra_data.to_vec())),
-            miner: format!("{:?}", eth_block.author.unwrap_or_default()),
-            difficulty: eth_block.difficulty,
-            total_difficulty: eth_block.total_difficulty,
-            size: eth_block.size.unwrap_or_default().as_u64(),
-            transaction_count: tx_count,
-            transactions,
-        })
+        Block::from_eth_block(&eth_block, transactions)
+            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))
+    }
+}
+
+/// Number of blocks in a list of inclusive ranges
+fn count_blocks(ranges: &[(u64, u64)]) -> u64 {
+    ranges.iter().map(|(start, end)| end - start + 1).sum()
+}
+
+/// Print the final summary of a historic sync run
+fn log_completion_report(run: &SyncRun) {
+    let separator = "=".repeat(80);
+    info!("\n\n{}\n{}\n{}", separator, "                     HISTORIC SYNC COMPLETION REPORT", separator);
+    info!("📦 RANGE: blocks {} to {} ({} blocks)",
+        run.from_block, run.to_block, format_integer(run.to_block - run.from_block + 1));
+    info!("✅ PERSISTED: {} blocks ({} missing, status: {})",
+        format_integer(run.blocks_persisted), format_integer(run.blocks_missing()), run.status());
+    info!("🩹 GAPS: {} blocks detected, {} repaired",
+        format_integer(run.gaps_detected), format_integer(run.gaps_repaired));
+    if run.failed_ranges.is_empty() {
+        info!("⚠️  FAILED RANGES: none");
+    } else {
+        info!("⚠️  FAILED RANGES: {:?}", run.failed_ranges);
+    }
+    info!("⏱️  DURATION: {}", format_duration(run.duration_ms as f64 / 1000.0));
+    info!("🚀 AVERAGE RATE: {}", format_rate(run.blocks_per_second));
+    match (run.db_size_before, run.db_size_after) {
+        (Some(before), Some(after)) => info!("💾 DATABASE GROWTH: {:.1} MB ({:.1} MB -> {:.1} MB)",
+            (after - before) as f64 / 1e6, before as f64 / 1e6, after as f64 / 1e6),
+        _ => info!("💾 DATABASE GROWTH: unknown"),
     }
-}
\ No newline at end of file
+    info!("{}\n", separator);
+}
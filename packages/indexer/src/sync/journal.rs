@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::db::Database;
+use crate::utils::journal::IngestJournal;
+
+/// Writes buffered ingestion events to the `ingest_journal` table and keeps only the
+/// most recent entries, like a ring buffer.
+///
+/// The journal is best-effort: entries from a failed write are dropped rather than retried.
+pub struct JournalWriter {
+    /// Database connection
+    db: Arc<Database>,
+    /// Buffer shared with the components recording events
+    journal: Arc<IngestJournal>,
+    /// Interval between flushes
+    flush_interval: Duration,
+    /// Entries kept in the table
+    size: u64,
+}
+
+impl JournalWriter {
+    /// Create a new journal writer
+    pub fn new(db: Arc<Database>, journal: Arc<IngestJournal>) -> Self {
+        Self {
+            db,
+            journal,
+            flush_interval: Duration::from_secs(1),
+            size: 100_000,
+        }
+    }
+
+    /// Configure how many entries are kept in the table
+    pub fn with_size(mut self, size: u64) -> Self {
+        info!("Keeping the latest {} journal entries", size);
+        self.size = size;
+        self
+    }
+
+    /// Spawn the writer as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting ingest journal writer");
+
+        tokio::spawn(async move {
+            loop {
+                let entries = self.journal.drain();
+                if !entries.is_empty() {
+                    match self.db.save_journal_entries(&entries, self.size).await {
+                        Ok(pruned) => debug!("Wrote {} journal entries, pruned {}", entries.len(), pruned),
+                        Err(e) => error!("Failed to write {} journal entries: {}", entries.len(), e),
+                    }
+                }
+
+                self.db.idle_mode().sleep(self.flush_interval).await;
+            }
+        })
+    }
+}
@@ -1,25 +1,30 @@
 use std::sync::Arc;
 use anyhow::Result;
 use ethers::{
-    providers::{Provider, Http, Ws, Middleware},
-    types::{BlockNumber, Block as EthBlock, TxHash},
+    providers::{Provider, Http, Middleware},
+    types::{BlockNumber, Block as EthBlock, Transaction as EthTransaction, TxHash},
 };
 use futures::StreamExt; // Add this for .next() method
-use tokio::time::{Duration, sleep};
+use tokio::time::{Duration, sleep, timeout};
 use tracing::{debug, error, info, warn, instrument};
 
-use crate::db::Database;
-use crate::models::{Block, Transaction, BlockQueue, BlockProcessor};
+use crate::db::{Database, JournalEntry};
+use crate::models::{Block, Transaction, BlockQueue, BlockProcessor, CommitOrder, HeadSource};
+use crate::utils::endpoints::EndpointPool;
 use crate::utils::retry::with_retry;
-use crate::sync::{SyncError, SharedSyncState};
+use crate::sync::{attach_deployed_code, SyncError, SharedSyncState};
+use crate::utils::rpc;
+
+/// Counter of WebSocket subscriptions abandoned because heads stopped arriving
+const WS_STALLS_METRIC: &str = "indexer_ws_stalls_total";
 
 /// Component responsible for live blockchain synchronization via WebSocket
 #[derive(Clone)]
 pub struct LiveSync {
     /// HTTP Provider URL for fetching block details
     http_provider_url: String,
-    /// WebSocket Provider URL for subscribing to new blocks
-    ws_provider_url: String,
+    /// WebSocket endpoints for subscribing to new blocks, failed over by health
+    ws_endpoints: Arc<EndpointPool>,
     /// Database connection
     db: Arc<Database>,
     /// Shared state between sync components
@@ -36,6 +41,16 @@ pub struct LiveSync {
     block_queue: Arc<BlockQueue>,
     /// Block processor for database writes
     block_processor: Arc<BlockProcessor>,
+    /// Reorder buffer shared with the historic sync, if blocks are persisted in order
+    commit_order: Option<Arc<CommitOrder>>,
+    /// Fetch full transaction objects instead of only hashes
+    full_transactions: bool,
+    /// Fetch transaction receipts for each block
+    fetch_receipts: bool,
+    /// How new blocks are discovered
+    head_source: HeadSource,
+    /// Wait for a head on the subscription after which the chain head is checked over HTTP
+    head_timeout: Option<Duration>,
 }
 
 impl LiveSync {
@@ -55,7 +70,7 @@ impl LiveSync {
         
         Self {
             http_provider_url,
-            ws_provider_url,
+            ws_endpoints: Arc::new(EndpointPool::new("WebSocket", vec![ws_provider_url])),
             db,
             sync_state,
             retry_delay: 200, // Default 200ms
@@ -64,6 +79,11 @@ impl LiveSync {
             max_parallel_blocks: 20, // Default max parallel blocks when catching up
             block_queue,
             block_processor,
+            commit_order: None,
+            full_transactions: false,
+            fetch_receipts: false,
+            head_source: HeadSource::Subscription,
+            head_timeout: None,
         }
     }
     
@@ -90,17 +110,26 @@ impl LiveSync {
         self
     }
     
+    /// Subscribe through any of these WebSocket endpoints, failing over between them
+    pub fn with_ws_endpoints(mut self, urls: Vec<String>) -> Self {
+        info!("Using {} WebSocket endpoints with failover", urls.len());
+        self.ws_endpoints = Arc::new(EndpointPool::new("WebSocket", urls));
+        self
+    }
+    
     /// Configure block queue size
     pub fn with_block_queue_size(self, queue_size: usize) -> Self {
         info!("Setting block queue size to {}", queue_size);
         
         // Create new block queue with specified size
         let block_queue = Arc::new(BlockQueue::with_capacity(queue_size));
-        let block_processor = Arc::new(BlockProcessor::new(block_queue.clone_queue()));
+        let block_processor = Arc::new(
+            BlockProcessor::new(block_queue.clone_queue()).with_commit_order(self.commit_order.clone())
+        );
         
         Self {
             http_provider_url: self.http_provider_url,
-            ws_provider_url: self.ws_provider_url,
+            ws_endpoints: self.ws_endpoints,
             db: self.db,
             sync_state: self.sync_state,
             retry_delay: self.retry_delay,
@@ -109,9 +138,52 @@ impl LiveSync {
             max_parallel_blocks: self.max_parallel_blocks,
             block_queue,
             block_processor,
+            commit_order: self.commit_order,
+            full_transactions: self.full_transactions,
+            fetch_receipts: self.fetch_receipts,
+            head_source: self.head_source,
+            head_timeout: self.head_timeout,
         }
     }
     
+    /// Persist blocks strictly in ascending order through a reorder buffer
+    pub fn with_commit_order(mut self, commit_order: Option<Arc<CommitOrder>>) -> Self {
+        self.block_processor = Arc::new(
+            BlockProcessor::new(self.block_queue.clone_queue()).with_commit_order(commit_order.clone())
+        );
+        self.commit_order = commit_order;
+        self
+    }
+    
+    /// Fetch full transaction objects (`eth_getBlockByNumber(.., true)`) instead of only hashes
+    pub fn with_full_transactions(mut self, full_transactions: bool) -> Self {
+        info!("Setting full transaction fetching to {}", full_transactions);
+        self.full_transactions = full_transactions;
+        self
+    }
+    
+    /// Fetch transaction receipts (`eth_getBlockReceipts`) for each block
+    pub fn with_receipts(mut self, fetch_receipts: bool) -> Self {
+        info!("Setting receipt fetching to {}", fetch_receipts);
+        self.fetch_receipts = fetch_receipts;
+        self
+    }
+    
+    /// Configure how new blocks are discovered; polling skips the WebSocket subscription
+    pub fn with_head_source(mut self, head_source: HeadSource) -> Self {
+        info!("Setting head source to {}", head_source.as_str());
+        self.head_source = head_source;
+        self
+    }
+    
+    /// Check the chain head over HTTP when the subscription delivers no head for this long,
+    /// abandoning the subscription if the chain moved on without it
+    pub fn with_head_timeout(mut self, head_timeout: Duration) -> Self {
+        info!("Setting WebSocket head timeout to {}ms", head_timeout.as_millis());
+        self.head_timeout = Some(head_timeout);
+        self
+    }
+    
     /// Start the block processor with the specified number of workers
     pub async fn start_processor(&self, workers: usize) {
         info!("Starting live sync block processor with {} workers", workers);
@@ -147,15 +219,41 @@ impl LiveSync {
             
             info!("Historical sync complete, starting live block monitoring");
             
-            // Try websocket subscription first, fall back to polling if it fails
-            match self.start_websocket_subscription().await {
+            // Nodes without a usable newHeads subscription are only polled
+            if self.head_source == HeadSource::Polling {
+                if let Err(e) = self.start_http_polling().await {
+                    error!("HTTP polling failed: {}, will retry", e);
+                }
+                sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            
+            // Try websocket subscription first, through the healthiest endpoint, failing over to
+            // another endpoint and only falling back to polling when none is left
+            let (endpoint, ws_url) = self.ws_endpoints.select();
+            match self.start_websocket_subscription(endpoint, &ws_url).await {
                 Ok(_) => {
                     // This should only return if the WebSocket connection was closed
-                    warn!("WebSocket connection closed, will attempt to reconnect");
-                    sleep(Duration::from_secs(5)).await;
+                    if self.ws_endpoints.has_alternative(endpoint) {
+                        warn!("WebSocket connection closed, failing over to another endpoint");
+                    } else {
+                        warn!("WebSocket connection closed, will attempt to reconnect");
+                        sleep(Duration::from_secs(5)).await;
+                    }
+                }
+                Err(e) if self.ws_endpoints.has_alternative(endpoint) => {
+                    error!("WebSocket subscription failed: {}, failing over to another endpoint", e);
+                    self.db.record_journal(
+                        JournalEntry::new("live_sync", "ws_failed")
+                            .with_details(serde_json::json!({ "endpoint": endpoint, "error": e.to_string() }))
+                    );
                 }
                 Err(e) => {
                     error!("WebSocket subscription failed: {}, falling back to HTTP polling", e);
+                    self.db.record_journal(
+                        JournalEntry::new("live_sync", "ws_failed")
+                            .with_details(serde_json::json!({ "endpoint": endpoint, "error": e.to_string() }))
+                    );
                     match self.start_http_polling().await {
                         Ok(_) => {
                             // This should only return if polling was stopped
@@ -172,15 +270,20 @@ impl LiveSync {
         }
     }
     
-    /// Start WebSocket subscription for new blocks
-    #[instrument(skip(self), name = "ws_subscription")]
-    async fn start_websocket_subscription(&self) -> Result<(), SyncError> {
-        info!("Starting WebSocket subscription to new blocks: {}", self.ws_provider_url);
+    /// Start WebSocket subscription for new blocks through endpoint `endpoint` of the pool,
+    /// recording connection failures and disconnects against its health
+    #[instrument(skip(self, ws_url), name = "ws_subscription")]
+    async fn start_websocket_subscription(&self, endpoint: usize, ws_url: &str) -> Result<(), SyncError> {
+        info!("Starting WebSocket subscription to new blocks: {} (endpoint #{} of {})",
+            ws_url, endpoint, self.ws_endpoints.len());
         
         // Connect to WebSocket
-        let ws = Ws::connect(&self.ws_provider_url)
+        let ws = rpc::connect_ws(ws_url)
             .await
-            .map_err(|e| SyncError::WebSocket(format!("Failed to connect: {}", e)))?;
+            .map_err(|e| {
+                self.ws_endpoints.record_failure(endpoint);
+                SyncError::WebSocket(format!("Failed to connect: {}", e))
+            })?;
             
         let provider = Provider::new(ws);
         
@@ -190,9 +293,20 @@ impl LiveSync {
         // Subscribe to new block headers
         let mut block_headers = provider.subscribe_blocks()
             .await
-            .map_err(|e| SyncError::WebSocket(format!("Failed to subscribe to blocks: {}", e)))?;
-        
-        info!("Successfully subscribed to new blocks via WebSocket");
+            .map_err(|e| {
+                self.ws_endpoints.record_failure(endpoint);
+                SyncError::WebSocket(format!("Failed to subscribe to blocks: {}", e))
+            })?;
+        
+        info!("Successfully subscribed to new blocks via WebSocket (subscription {:#x})", block_headers.id);
+        self.ws_endpoints.record_success(endpoint);
+        self.db.record_journal(
+            JournalEntry::new("live_sync", "ws_subscribed")
+                .with_details(serde_json::json!({
+                    "endpoint": endpoint,
+                    "subscriptionId": format!("{:#x}", block_headers.id),
+                }))
+        );
         
         // Track the last synced block number from the shared state
         let mut last_synced_block = {
@@ -220,13 +334,60 @@ impl LiveSync {
         
         info!("Listening for new blocks in real-time. Last synced block: {}", last_synced_block);
         
+        // Whether the subscription was renewed since the last head
+        let mut resubscribed = false;
+        
         // Process incoming blocks
-        while let Some(block) = block_headers.next().await {            
+        loop {
+            let next_head = match self.head_timeout {
+                Some(head_timeout) => match timeout(head_timeout, block_headers.next()).await {
+                    Ok(next_head) => next_head,
+                    Err(_) => {
+                        if let Err(e) = self.check_stale_subscription(endpoint, &http_provider, last_synced_block, head_timeout).await {
+                            if resubscribed {
+                                return Err(e);
+                            }
+                            
+                            // The node may have dropped the subscription while the socket is fine,
+                            // so subscribe again once before giving up on the connection
+                            let stale_id = block_headers.id;
+                            block_headers = provider.subscribe_blocks()
+                                .await
+                                .map_err(|e| SyncError::WebSocket(format!("Failed to resubscribe to blocks: {}", e)))?;
+                            resubscribed = true;
+                            
+                            info!("Resubscribed to new blocks: subscription {:#x} replaces {:#x}", block_headers.id, stale_id);
+                            self.db.record_journal(
+                                JournalEntry::new("live_sync", "ws_resubscribed")
+                                    .with_block_number(last_synced_block)
+                                    .with_details(serde_json::json!({
+                                        "endpoint": endpoint,
+                                        "subscriptionId": format!("{:#x}", block_headers.id),
+                                        "staleSubscriptionId": format!("{:#x}", stale_id),
+                                    }))
+                            );
+                        }
+                        continue;
+                    }
+                },
+                None => block_headers.next().await,
+            };
+            let Some(block) = next_head else {
+                break;
+            };
+            resubscribed = false;
+            
             let block_number = block.number
                 .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))?
                 .as_u64();
                 
-            info!("Received new block notification: #{}", block_number);
+            // Per-block logs drop to debug while the chain is idle
+            let quiet = self.db.idle_mode().is_idle();
+            if quiet {
+                debug!("Received new block notification: #{}", block_number);
+            } else {
+                info!("Received new block notification: #{}", block_number);
+            }
             
             // If there's a gap, process missing blocks first
             if block_number > last_synced_block + 1 {
@@ -234,42 +395,28 @@ impl LiveSync {
                 let gap_end = block_number - 1;
                 
                 warn!("Block gap detected. Processing missing blocks {} to {}", gap_start, gap_end);
+                self.db.record_journal(
+                    JournalEntry::new("live_sync", "gap_detected")
+                        .with_block_number(gap_start)
+                        .with_details(serde_json::json!({ "from": gap_start, "to": gap_end }))
+                );
                 
                 self.catch_up_blocks(&http_provider, gap_start, gap_end).await?;
             }
             
-            // WebSocket new_heads event doesn't include transaction data, so we need to fetch the block with transaction hashes
-            info!("Fetching block data with transaction hashes for block #{}", block_number);
+            // WebSocket new_heads event doesn't include transaction data, so we need to fetch the block
+            debug!("Fetching block data for block #{}", block_number);
             
             // Enforce a small delay to reduce the "block out of range" error
             sleep(Duration::from_millis(300)).await;
 
-            // Use the HTTP provider to fetch the block with transaction hashes
-            let full_block = with_retry(
-                || {
-                    let http_provider = http_provider.clone();
-                    let block_num = block_number;
-                    
-                    async move {
-                        let block = http_provider.get_block(BlockNumber::Number(block_num.into()))
-                            .await
-                            .map_err(|e| SyncError::Provider(format!("Failed to get block {}: {}", block_num, e)))?
-                            .ok_or_else(|| SyncError::BlockNotFound(block_num))?;
-                            
-                        Ok::<_, SyncError>(block)
-                    }
-                },
-                self.retry_delay,
-                self.max_retries,
-                &format!("fetch_block_{}", block_number),
-            ).await?;
-            
-            // Extract transaction count and transaction data
-            let tx_count = full_block.transactions.len() as u64;
-            info!("Block #{} contains {} transactions", block_number, tx_count);
-            
-            // Convert the block data to our model
-            let model_block = self.convert_block_with_transactions(full_block)?;
+            // Use the HTTP provider to fetch the block
+            let model_block = self.fetch_block(&http_provider, block_number).await?;
+            if quiet {
+                debug!("Block #{} contains {} transactions", block_number, model_block.transaction_count);
+            } else {
+                info!("Block #{} contains {} transactions", block_number, model_block.transaction_count);
+            }
             
             // Push to the queue using the helper method
             self.push_block_to_queue(model_block).await?;
@@ -286,9 +433,65 @@ impl LiveSync {
         }
         
         warn!("WebSocket subscription stream ended");
+        self.ws_endpoints.record_failure(endpoint);
+        self.db.record_journal(
+            JournalEntry::new("live_sync", "ws_closed")
+                .with_block_number(last_synced_block)
+                .with_details(serde_json::json!({ "endpoint": endpoint }))
+        );
         Ok(())
     }
     
+    /// Called when no head arrived on the subscription within the head timeout. Returns an
+    /// error when the chain advanced past `last_synced_block` anyway, meaning the node stopped
+    /// pushing heads, so the caller resubscribes, then fails over to another endpoint or falls
+    /// back to polling if the new subscription is stale too.
+    async fn check_stale_subscription<M: Middleware>(
+        &self,
+        endpoint: usize,
+        http_provider: &M,
+        last_synced_block: u64,
+        head_timeout: Duration,
+    ) -> Result<(), SyncError>
+    where
+        M::Error: std::fmt::Display
+    {
+        let chain_head = match self.get_latest_block_number(http_provider).await {
+            Ok(chain_head) => chain_head,
+            Err(e) => {
+                warn!("No new head for {}ms and the chain head can't be checked: {}", head_timeout.as_millis(), e);
+                return Ok(());
+            }
+        };
+        
+        if chain_head <= last_synced_block {
+            // The chain itself isn't producing blocks, the status monitor reports that
+            debug!("No new head for {}ms, chain head is still #{}", head_timeout.as_millis(), chain_head);
+            return Ok(());
+        }
+        
+        warn!(
+            "WebSocket subscription is stale: no new head for {}ms while the chain advanced from #{} to #{}",
+            head_timeout.as_millis(), last_synced_block, chain_head
+        );
+        self.ws_endpoints.record_failure(endpoint);
+        metrics::counter!(WS_STALLS_METRIC, 1);
+        self.db.record_journal(
+            JournalEntry::new("live_sync", "ws_stale")
+                .with_block_number(last_synced_block)
+                .with_details(serde_json::json!({
+                    "endpoint": endpoint,
+                    "timeoutMs": head_timeout.as_millis() as u64,
+                    "chainHead": chain_head,
+                }))
+        );
+        
+        Err(SyncError::WebSocket(format!(
+            "no new head for {}ms, chain head is #{} but last head was #{}",
+            head_timeout.as_millis(), chain_head, last_synced_block
+        )))
+    }
+    
     /// Start HTTP polling for new blocks
     #[instrument(skip(self), name = "http_polling")]
     async fn start_http_polling(&self) -> Result<(), SyncError> {
@@ -304,6 +507,9 @@ impl LiveSync {
         };
         
         info!("HTTP polling started. Last synced block: {}", last_synced_block);
+        self.db.record_journal(
+            JournalEntry::new("live_sync", "http_polling_started").with_block_number(last_synced_block)
+        );
         
         // Polling loop
         loop {
@@ -312,7 +518,7 @@ impl LiveSync {
                 Ok(num) => num,
                 Err(e) => {
                     error!("Failed to get latest block number: {}", e);
-                    sleep(Duration::from_secs(self.polling_interval)).await;
+                    self.db.idle_mode().sleep(Duration::from_secs(self.polling_interval)).await;
                     continue;
                 }
             };
@@ -336,13 +542,13 @@ impl LiveSync {
                 // If we caught up, wait for the polling interval
                 if blocks_behind <= 1 {
                     debug!("Caught up with chain head. Waiting for next polling interval.");
-                    sleep(Duration::from_secs(self.polling_interval)).await;
+                    self.db.idle_mode().sleep(Duration::from_secs(self.polling_interval)).await;
                 }
                 // If we're still behind, continue immediately
             } else {
                 // No new blocks, wait for polling interval
                 debug!("No new blocks found. Current: {}", last_synced_block);
-                sleep(Duration::from_secs(self.polling_interval)).await;
+                self.db.idle_mode().sleep(Duration::from_secs(self.polling_interval)).await;
             }
             
             // Monitor lag
@@ -469,7 +675,7 @@ impl LiveSync {
     
     /// Create an HTTP provider
     fn create_http_provider(&self) -> Result<Provider<Http>, SyncError> {
-        Provider::<Http>::try_from(self.http_provider_url.as_str())
+        rpc::http_provider(&self.http_provider_url)
             .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))
     }
     
@@ -543,33 +749,89 @@ impl LiveSync {
     {
         debug!("Fetching block {}", block_number);
         
+        let model_block = self.fetch_block(provider, block_number).await?;
+        let tx_count = model_block.transaction_count;
+        debug!("Block {} contains {} transactions", block_number, tx_count);
+        
+        // Queue block for database storage using the helper method
+        self.push_block_to_queue(model_block).await?;
+            
+        if self.db.idle_mode().is_idle() {
+            debug!("Block {} processed successfully with {} transactions", block_number, tx_count);
+        } else {
+            info!("Block {} processed successfully with {} transactions", block_number, tx_count);
+        }
+        Ok(())
+    }
+    
+    /// Fetch a block with retries and convert it to our model,
+    /// including full transaction objects and receipts when configured
+    async fn fetch_block<M: Middleware>(&self, provider: &M, block_number: u64) -> Result<Block, SyncError> 
+    where
+        M::Error: std::fmt::Display
+    {
+        let mut block = self.fetch_block_data(provider, block_number).await?;
+        
+        if self.fetch_receipts {
+            let receipts = with_retry(
+                || async {
+                    provider.get_block_receipts(BlockNumber::Number(block_number.into()))
+                        .await
+                        .map_err(|e| SyncError::Provider(format!("Failed to get receipts for block {}: {}", block_number, e)))
+                },
+                self.retry_delay,
+                self.max_retries,
+                &format!("fetch_receipts_{}", block_number),
+            ).await?;
+            
+            if !block.attach_receipts(&receipts) {
+                return Err(SyncError::Other(format!(
+                    "Receipts for block {} belong to a different block, the block was likely reorged",
+                    block_number
+                )));
+            }
+            
+            attach_deployed_code(provider, &mut block, self.retry_delay, self.max_retries).await?;
+        }
+        
+        Ok(block)
+    }
+    
+    /// Fetch a block with retries and convert it to our model
+    async fn fetch_block_data<M: Middleware>(&self, provider: &M, block_number: u64) -> Result<Block, SyncError> 
+    where
+        M::Error: std::fmt::Display
+    {
+        if self.full_transactions {
+            let eth_block = with_retry(
+                || async {
+                    provider.get_block_with_txs(BlockNumber::Number(block_number.into()))
+                        .await
+                        .map_err(|e| SyncError::Provider(format!("Failed to get block {}: {}", block_number, e)))?
+                        .ok_or(SyncError::BlockNotFound(block_number))
+                },
+                self.retry_delay,
+                self.max_retries,
+                &format!("fetch_block_{}", block_number),
+            ).await?;
+            
+            return self.convert_block_with_full_transactions(eth_block);
+        }
+        
         let eth_block = with_retry(
             || async {
                 // Fetch block with transaction hashes
-                let block = provider.get_block(BlockNumber::Number(block_number.into()))
+                provider.get_block(BlockNumber::Number(block_number.into()))
                     .await
                     .map_err(|e| SyncError::Provider(format!("Failed to get block {}: {}", block_number, e)))?
-                    .ok_or_else(|| SyncError::BlockNotFound(block_number))?;
-                
-                Ok::<_, SyncError>(block)
+                    .ok_or(SyncError::BlockNotFound(block_number))
             },
             self.retry_delay,
             self.max_retries,
             &format!("fetch_block_{}", block_number),
         ).await?;
         
-        // Count transactions
-        let tx_count = eth_block.transactions.len() as u64;
-        debug!("Block {} contains {} transactions", block_number, tx_count);
-        
-        // Convert to our model
-        let model_block = self.convert_block_with_transactions(eth_block)?;
-        
-        // Queue block for database storage using the helper method
-        self.push_block_to_queue(model_block).await?;
-            
-        info!("Block {} processed successfully with {} transactions", block_number, tx_count);
-        Ok(())
+        self.convert_block_with_transactions(eth_block)
     }
     
     /// Wait for the block queue to be fully processed
@@ -600,61 +862,25 @@ impl LiveSync {
         Ok(())
     }
     
+    /// Convert block with full transaction objects to our model
+    fn convert_block_with_full_transactions(&self, mut eth_block: EthBlock<EthTransaction>) -> Result<Block, SyncError> {
+        let transactions = std::mem::take(&mut eth_block.transactions)
+            .into_iter()
+            .map(Transaction::from)
+            .collect();
+        
+        Block::from_eth_block(&eth_block, transactions)
+            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))
+    }
+    
     /// Convert block with just transaction hashes to our model
     fn convert_block_with_transactions(&self, eth_block: EthBlock<TxHash>) -> Result<Block, SyncError> {
-        let block_number = eth_block.number
-            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))?
-            .as_u64();
-            
-        debug!("Converting block {} to model", block_number);
-        
-        // Convert transaction hashes to our transaction model
-        let transactions = eth_block.transactions.into_iter()
+        let transactions = eth_block.transactions.iter()
             .enumerate()
-            .filter_map(|(i, tx)| {
-                // Basic validation check
-                if tx.as_bytes().is_empty() {
-                    warn!("Skipping transaction with empty hash in block {}", block_number);
-                    return None;
-                }
-                
-                Some(Transaction {
-                    hash: format!("{:?}", tx),
-                    from: None,    // We don't have this info without fetching full transactions
-                    to: None,      // We don't have this info without fetching full transactions
-                    value: "0".to_string(), // Default value
-                    gas: 0,        // We don't have this info without fetching full transactions
-                    gas_price: None, // We don't have this info without fetching full transactions
-                    input: "0x".to_string(), // We don't have this info without fetching full transactions
-                    nonce: 0,      // We don't have this info without fetching full transactions
-                    transaction_index: i as u64,
-                    block_hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
-                    block_number,
-                })
-            })
-            .collect::<Vec<Transaction>>();
-        
-        let tx_count = transactions.len() as u64;  // Recount to ensure accuracy
-        
-        // Create the block model
-        Ok(Block {
-            number: block_number,
-            hash: format!("{:?}", eth_block.hash.unwrap_or_default()),
-            parent_hash: format!("{:?}", eth_block.parent_hash),
-            timestamp: eth_block.timestamp.as_u64(),
-            transactions_root: format!("{:?}", eth_block.transactions_root),
-            state_root: format!("{:?}", eth_block.state_root),
-            receipts_root: format!("{:?}", eth_block.receipts_root),
-            gas_used: eth_block.gas_used.as_u64(),
-            gas_limit: eth_block.gas_limit.as_u64(),
-            base_fee_per_gas: eth_block.base_fee_per_gas.map(|fee| fee.as_u64()),
-            extra_data: format!("0x{}", hex::encode(&eth_block.extra_data)),
-            miner: format!("{:?}", eth_block.author.unwrap_or_default()),
-            difficulty: eth_block.difficulty,
-            total_difficulty: eth_block.total_difficulty,
-            size: eth_block.size.unwrap_or_default().as_u64(),
-            transaction_count: tx_count,
-            transactions,
-        })
+            .map(|(i, &hash)| Transaction::from_hash(hash, i, &eth_block))
+            .collect();
+        
+        Block::from_eth_block(&eth_block, transactions)
+            .ok_or_else(|| SyncError::Parse("Block number missing".to_string()))
     }
 }
\ No newline at end of file
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, error, info};
+
+use crate::db::Database;
+use crate::sync::SyncError;
+
+/// Gauge of when the last maintenance pass completed, as a unix timestamp
+const LAST_PASS_METRIC: &str = "indexer_maintenance_last_pass_timestamp";
+
+/// Histogram of how long maintenance passes took, in seconds
+const PASS_DURATION_METRIC: &str = "indexer_maintenance_pass_duration_seconds";
+
+/// Runs routine database maintenance on a schedule, so operators don't need cron jobs
+/// against the explorer database: `ANALYZE` of the tables whose statistics went stale, and
+/// refreshes of the configured materialized views.
+///
+/// Autovacuum analyzes tables too, but only after a fraction of their rows changed, which
+/// takes a long time on large append-only tables such as `blocks` and `logs`.
+pub struct MaintenanceWorker {
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between passes
+    poll_interval: Duration,
+    /// Rows changed since the last `ANALYZE` for a table to be analyzed again
+    analyze_threshold: u64,
+    /// Materialized views refreshed on each pass
+    materialized_views: Vec<String>,
+}
+
+impl MaintenanceWorker {
+    /// Create a new maintenance worker
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            poll_interval: Duration::from_secs(3600), // Default 1 hour
+            analyze_threshold: 10000,
+            materialized_views: Vec::new(),
+        }
+    }
+
+    /// Configure the interval between passes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting maintenance interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Configure how many rows must change before a table is analyzed again (0 disables `ANALYZE`)
+    pub fn with_analyze_threshold(mut self, rows: u64) -> Self {
+        self.analyze_threshold = rows;
+        self
+    }
+
+    /// Configure the materialized views refreshed on each pass
+    pub fn with_materialized_views(mut self, views: Vec<String>) -> Self {
+        if !views.is_empty() {
+            info!("Refreshing materialized views {}", views.join(", "));
+        }
+        self.materialized_views = views;
+        self
+    }
+
+    /// Spawn the worker as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting database maintenance worker");
+
+        tokio::spawn(async move {
+            loop {
+                let started = Instant::now();
+                match self.run_pass().await {
+                    Ok((0, 0)) => debug!("No database maintenance needed"),
+                    Ok((tables, views)) => info!(
+                        "Database maintenance analyzed {} tables and refreshed {} materialized views in {:?}",
+                        tables, views, started.elapsed()
+                    ),
+                    Err(e) => error!("Database maintenance pass failed: {}", e),
+                }
+                metrics::histogram!(PASS_DURATION_METRIC, started.elapsed().as_secs_f64());
+                metrics::gauge!(LAST_PASS_METRIC, chrono::Utc::now().timestamp() as f64);
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    /// Analyze the stale tables and refresh the materialized views, returning how many of
+    /// each were processed. A failing table or view doesn't hold back the others.
+    async fn run_pass(&self) -> Result<(usize, usize), SyncError> {
+        let mut tables = 0;
+        if self.analyze_threshold > 0 {
+            for table in self.db.get_stale_tables(self.analyze_threshold).await? {
+                if self.db.analyze_table(&table).await.is_ok() {
+                    tables += 1;
+                }
+            }
+        }
+
+        let mut views = 0;
+        for view in &self.materialized_views {
+            if self.db.refresh_materialized_view(view).await.is_ok() {
+                views += 1;
+            }
+        }
+
+        Ok((tables, views))
+    }
+}
@@ -7,6 +7,8 @@ use super::{HistoricSync, LiveSync, SyncError};
 pub struct SyncManager {
     historic_sync: HistoricSync,
     live_sync: LiveSync,
+    /// Follow the chain with live sync once historic sync completes
+    follow_chain: bool,
 }
 
 impl SyncManager {
@@ -14,9 +16,16 @@ impl SyncManager {
         Self {
             historic_sync,
             live_sync,
+            follow_chain: true,
         }
     }
     
+    /// Stop once historic sync completes, for a sync bounded by an end block
+    pub fn without_live_sync(mut self) -> Self {
+        self.follow_chain = false;
+        self
+    }
+    
     /// Start the sync process with both components
     pub async fn start(self) -> Result<(), SyncError> {
         info!("Starting sync manager");
@@ -30,6 +39,11 @@ impl SyncManager {
             }
         }
         
+        if !self.follow_chain {
+            info!("Bounded sync complete, not starting live sync");
+            return Ok(());
+        }
+        
         // Then run live sync
         match self.live_sync.start().await {
             Ok(_) => info!("Live sync completed successfully"),
@@ -1,14 +1,48 @@
+mod alerts;
+mod checkpoint;
 mod error;
 mod historic;
 mod live;
+mod names;
 mod manager;
 mod fetcher;
+mod finality;
+mod gaps;
+mod gas_rollups;
+mod journal;
+mod maintenance;
+#[cfg(feature = "remote-sync")]
+mod remote;
+mod retention;
+mod slo;
+mod status;
+mod token_metadata;
+mod top_contracts;
+#[cfg(feature = "warehouse")]
+mod warehouse;
 
+pub use alerts::{SyncAlertMonitor, PAGERDUTY_EVENTS_URL};
+pub use checkpoint::SyncCheckpointer;
 pub use error::SyncError;
 pub use historic::HistoricSync;
 pub use live::LiveSync;
+pub use names::NameResolver;
 pub use manager::SyncManager;
-pub use fetcher::BlockFetcher;
+pub use fetcher::{attach_deployed_code, BlockFetcher};
+pub use finality::FinalityTracker;
+pub use gaps::GapRepairer;
+pub use gas_rollups::GasRollupWorker;
+pub use journal::JournalWriter;
+pub use maintenance::MaintenanceWorker;
+#[cfg(feature = "remote-sync")]
+pub use remote::RemoteSync;
+pub use retention::RetentionWorker;
+pub use slo::SloEvaluator;
+pub use status::ChainStatusMonitor;
+pub use token_metadata::TokenMetadataWorker;
+pub use top_contracts::TopContractsWorker;
+#[cfg(feature = "warehouse")]
+pub use warehouse::WarehousePublisher;
 
 use std::fmt;
 use std::sync::Arc;
@@ -20,6 +54,8 @@ pub struct SyncState {
     pub latest_synced_block: u64,
     /// Flag to indicate if historic sync is complete
     pub historic_sync_complete: bool,
+    /// First block of the synced range, moved up when the node pruned the blocks before it
+    pub sync_start: u64,
 }
 
 impl SyncState {
@@ -27,6 +63,7 @@ impl SyncState {
         Self {
             latest_synced_block: start_block,
             historic_sync_complete: false,
+            sync_start: start_block,
         }
     }
 }
@@ -36,6 +73,7 @@ impl fmt::Debug for SyncState {
         f.debug_struct("SyncState")
             .field("latest_synced_block", &self.latest_synced_block)
             .field("historic_sync_complete", &self.historic_sync_complete)
+            .field("sync_start", &self.sync_start)
             .finish()
     }
 }
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use ethers::providers::{Provider, Http, Middleware, ProviderError};
+use ethers::types::Address;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::db::Database;
+use crate::sync::SyncError;
+use crate::utils::rpc;
+
+/// Number of addresses resolved per pass
+const BATCH_SIZE: i64 = 100;
+
+/// Reverse-resolves addresses seen in transactions to names through an ENS-style registry,
+/// storing them in `address_names` and refreshing them periodically
+pub struct NameResolver {
+    /// HTTP provider, pointed at the configured registry
+    provider: Provider<Http>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between passes once every address is up to date
+    poll_interval: Duration,
+    /// Age after which a resolved name is resolved again
+    refresh_interval: Duration,
+}
+
+impl NameResolver {
+    /// Create a resolver using the registry contract at `registry_address`
+    pub fn new(http_provider_url: &str, registry_address: &str, db: Arc<Database>) -> Result<Self, SyncError> {
+        let registry = registry_address.parse::<Address>()
+            .map_err(|e| SyncError::Parse(format!("Invalid name registry address {}: {}", registry_address, e)))?;
+
+        let provider = rpc::http_provider(http_provider_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?
+            .ens(registry);
+
+        Ok(Self {
+            provider,
+            db,
+            poll_interval: Duration::from_secs(60), // Default 1 minute
+            refresh_interval: Duration::from_secs(86400), // Default 1 day
+        })
+    }
+
+    /// Configure the age after which names are resolved again
+    pub fn with_refresh_interval(mut self, seconds: u64) -> Self {
+        info!("Setting name refresh interval to {}s", seconds);
+        self.refresh_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Spawn the resolver as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting name resolver");
+
+        tokio::spawn(async move {
+            loop {
+                match self.resolve_batch().await {
+                    // A full batch means more addresses are waiting
+                    Ok(resolved) if resolved as i64 == BATCH_SIZE => continue,
+                    Ok(resolved) => debug!("Resolved names of {} addresses", resolved),
+                    Err(e) => error!("Name resolution pass failed: {}", e),
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    /// Resolve a batch of addresses that are new or due for a refresh, returning how many were stored
+    async fn resolve_batch(&self) -> Result<usize, SyncError> {
+        let addresses = self.db
+            .get_addresses_to_resolve(self.refresh_interval.as_secs() as i64, BATCH_SIZE)
+            .await?;
+
+        let mut names = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            match self.lookup(&address).await {
+                Ok(name) => names.push((address, name)),
+                // Left unresolved, so it is retried on the next pass
+                Err(e) => warn!("Failed to resolve name of {}: {}", address, e),
+            }
+        }
+
+        if !names.is_empty() {
+            self.db.save_address_names(&names).await?;
+        }
+
+        Ok(names.len())
+    }
+
+    /// Reverse-resolve an address. Returns None if it has no name, or if the name doesn't
+    /// resolve back to the address (anyone can claim any name in their reverse record).
+    async fn lookup(&self, address: &str) -> Result<Option<String>, SyncError> {
+        let parsed = address.parse::<Address>()
+            .map_err(|e| SyncError::Parse(format!("Invalid address {}: {}", address, e)))?;
+
+        match self.provider.lookup_address(parsed).await {
+            Ok(name) => Ok(Some(name)),
+            Err(ProviderError::EnsError(_)) | Err(ProviderError::EnsNotOwned(_)) => Ok(None),
+            // The node answered with an error, e.g. a resolver reverting, rather than failing
+            Err(ProviderError::JsonRpcClientError(e)) if e.as_error_response().is_some() => Ok(None),
+            Err(e) => Err(SyncError::Provider(format!("Failed to look up name of {}: {}", address, e))),
+        }
+    }
+}
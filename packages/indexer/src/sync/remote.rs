@@ -0,0 +1,290 @@
+use std::sync::Arc;
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+use crate::db::Database;
+use crate::models::{parse_difficulty, Block, BlockProcessor, BlockQueue, Transaction};
+use crate::sync::SyncError;
+use crate::utils::retry::with_retry;
+
+/// Syncs missing blocks from another explorer instance's REST API instead of the chain,
+/// so a read replica of the dataset can be stood up without RPC access
+pub struct RemoteSync {
+    /// HTTP client, carrying the remote API key
+    client: reqwest::Client,
+    /// Base URL of the remote API, e.g. `https://explorer.example.com/api`
+    api_url: String,
+    /// Database connection
+    db: Arc<Database>,
+    /// First block to mirror
+    start_block: u64,
+    /// Interval between passes once caught up
+    poll_interval: Duration,
+    /// Number of blocks requested from the remote API at once
+    concurrency: usize,
+    /// Delay between retries in milliseconds
+    retry_delay: u64,
+    /// Maximum number of retries for failed requests
+    max_retries: u32,
+    /// Block queue for fetched blocks
+    block_queue: Arc<BlockQueue>,
+    /// Block processor persisting fetched blocks
+    block_processor: Arc<BlockProcessor>,
+}
+
+/// Response envelope of the explorer API
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct LatestBlocks {
+    blocks: Vec<LatestBlock>,
+}
+
+#[derive(Deserialize)]
+struct LatestBlock {
+    number: u64,
+}
+
+#[derive(Deserialize)]
+struct BlockDetail {
+    block: RemoteBlock,
+}
+
+/// A block as served by `GET /api/blocks/:number`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteBlock {
+    number: u64,
+    hash: String,
+    parent_hash: String,
+    timestamp: u64,
+    transactions_root: String,
+    state_root: String,
+    receipts_root: String,
+    gas_used: u64,
+    gas_limit: u64,
+    base_fee_per_gas: Option<u64>,
+    extra_data: String,
+    miner: String,
+    difficulty: Option<String>,
+    total_difficulty: Option<String>,
+    size: u64,
+    transaction_count: u64,
+    /// Stored transaction objects; extra fields such as `receipt` are ignored
+    #[serde(default)]
+    transactions: Vec<Transaction>,
+}
+
+impl RemoteBlock {
+    fn into_block(self) -> Result<Block, SyncError> {
+        // Remotes on a rollup chain profile serve null; unparseable values are dropped too
+        let difficulty = self.difficulty.as_deref().and_then(parse_difficulty);
+        let total_difficulty = self.total_difficulty.as_deref().and_then(parse_difficulty);
+
+        Ok(Block {
+            number: self.number,
+            hash: self.hash,
+            parent_hash: self.parent_hash,
+            timestamp: self.timestamp,
+            transactions_root: self.transactions_root,
+            state_root: self.state_root,
+            receipts_root: self.receipts_root,
+            gas_used: self.gas_used,
+            gas_limit: self.gas_limit,
+            base_fee_per_gas: self.base_fee_per_gas,
+            extra_data: self.extra_data,
+            miner: self.miner,
+            difficulty,
+            total_difficulty,
+            size: self.size,
+            transaction_count: self.transaction_count,
+            transactions: self.transactions,
+            receipts: Vec::new(),
+        })
+    }
+}
+
+impl RemoteSync {
+    /// Create a new remote sync against the explorer API at `api_url`
+    pub fn new(api_url: &str, api_key: Option<&str>, db: Arc<Database>) -> Result<Self, SyncError> {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = api_key {
+            let value = HeaderValue::from_str(api_key)
+                .map_err(|e| SyncError::Http(format!("Invalid remote API key: {}", e)))?;
+            headers.insert("x-api-key", value);
+        }
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| SyncError::Http(format!("Failed to create HTTP client: {}", e)))?;
+
+        let block_queue = Arc::new(BlockQueue::with_capacity(1000));
+        let block_processor = Arc::new(BlockProcessor::new(block_queue.clone_queue()));
+
+        Ok(Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            db,
+            start_block: 0,
+            poll_interval: Duration::from_secs(2), // Default 2 seconds
+            concurrency: 20, // Default 20 blocks in flight
+            retry_delay: 1000, // Default 1 second
+            max_retries: 5,    // Default 5 retries
+            block_queue,
+            block_processor,
+        })
+    }
+
+    /// Configure the first block to mirror
+    pub fn with_start_block(mut self, start_block: u64) -> Self {
+        info!("Mirroring remote explorer from block {}", start_block);
+        self.start_block = start_block;
+        self
+    }
+
+    /// Configure the number of blocks requested at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        info!("Setting remote sync concurrency to {}", concurrency);
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Configure retry settings
+    pub fn with_retry_settings(mut self, retry_delay: u64, max_retries: u32) -> Self {
+        self.retry_delay = retry_delay;
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Mirror the remote explorer until the process is stopped
+    pub async fn start(&self, workers: usize) -> Result<(), SyncError> {
+        info!("Starting remote sync from {}", self.api_url);
+
+        for i in 0..workers {
+            info!("Starting remote sync database worker {}", i + 1);
+            self.block_processor.start(Arc::clone(&self.db)).await;
+        }
+
+        loop {
+            match self.sync_missing_blocks().await {
+                Ok(0) => sleep(self.poll_interval).await,
+                Ok(synced) => debug!("Synced {} blocks from remote explorer", synced),
+                Err(e) => {
+                    error!("Remote sync pass failed: {}", e);
+                    sleep(self.poll_interval).await;
+                }
+            }
+        }
+    }
+
+    /// Fetch every block the remote has and we don't, returning how many were queued
+    async fn sync_missing_blocks(&self) -> Result<u64, SyncError> {
+        let Some(remote_latest) = self.get_remote_latest_block().await? else {
+            debug!("Remote explorer has no blocks yet");
+            return Ok(0);
+        };
+
+        if remote_latest < self.start_block {
+            return Ok(0);
+        }
+
+        // Blocks still waiting in the queue would otherwise be requested again
+        while !self.block_queue.is_empty() {
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        let missing = self.db.find_missing_block_ranges(self.start_block, remote_latest).await?;
+        if missing.is_empty() {
+            return Ok(0);
+        }
+
+        let missing_blocks: u64 = missing.iter().map(|(start, end)| end - start + 1).sum();
+        info!(
+            "Syncing {} missing blocks in {} ranges from remote explorer (remote head {})",
+            missing_blocks, missing.len(), remote_latest
+        );
+
+        let mut synced = 0;
+        let mut unavailable = 0;
+
+        for (start, end) in missing {
+            let mut blocks = futures::stream::iter(start..=end)
+                .map(|number| self.fetch_block(number))
+                .buffered(self.concurrency);
+
+            while let Some(result) = blocks.next().await {
+                match result? {
+                    Some(block) => {
+                        let block_number = block.number;
+                        if let Err(e) = self.block_queue.push(block).await {
+                            return Err(SyncError::Other(format!("Failed to queue block {}: {}", block_number, e)));
+                        }
+                        synced += 1;
+                    }
+                    None => unavailable += 1,
+                }
+            }
+
+            info!("Synced blocks {} to {} from remote explorer", start, end);
+        }
+
+        if unavailable > 0 {
+            warn!("{} blocks are missing on the remote explorer too", unavailable);
+        }
+
+        Ok(synced)
+    }
+
+    /// Number of the remote's latest block
+    async fn get_remote_latest_block(&self) -> Result<Option<u64>, SyncError> {
+        let url = format!("{}/blocks/latest?limit=1", self.api_url);
+        let response: ApiResponse<LatestBlocks> = self.get_json(&url).await?
+            .ok_or_else(|| SyncError::Http(format!("{} not found", url)))?;
+
+        Ok(response.data.blocks.first().map(|block| block.number))
+    }
+
+    /// Fetch a block from the remote, or None if the remote doesn't have it
+    async fn fetch_block(&self, block_number: u64) -> Result<Option<Block>, SyncError> {
+        let url = format!("{}/blocks/{}", self.api_url, block_number);
+        let response: Option<ApiResponse<BlockDetail>> = self.get_json(&url).await?;
+
+        response.map(|response| response.data.block.into_block()).transpose()
+    }
+
+    /// GET a JSON document with retries, returning None on 404
+    async fn get_json<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<Option<T>, SyncError> {
+        with_retry(
+            || async {
+                let response = self.client.get(url)
+                    .send()
+                    .await
+                    .map_err(|e| SyncError::Http(format!("Request to {} failed: {}", url, e)))?;
+
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+
+                let response = response.error_for_status()
+                    .map_err(|e| SyncError::Http(format!("Request to {} failed: {}", url, e)))?;
+
+                response.json::<T>()
+                    .await
+                    .map(Some)
+                    .map_err(|e| SyncError::Parse(format!("Invalid response from {}: {}", url, e)))
+            },
+            self.retry_delay,
+            self.max_retries,
+            "remote_explorer_request",
+        ).await
+    }
+}
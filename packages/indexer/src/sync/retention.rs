@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::db::{AuditEvent, Database};
+use crate::sync::SyncError;
+
+/// Gauge of the block up to which transaction details were pruned
+const PRUNED_BLOCK_METRIC: &str = "indexer_retention_pruned_block";
+
+/// Blocks pruned per database transaction
+const PRUNE_BATCH_BLOCKS: u64 = 1000;
+
+/// Prunes the raw detail of blocks older than the retention period, so the database doesn't
+/// grow without bound: their transactions (JSONB and table rows), receipts and logs.
+///
+/// Block rows are kept forever, with their header fields, counts and tags, as are the
+/// aggregates built from the details (address activity, token and NFT transfers, rollups).
+pub struct RetentionWorker {
+    /// Database connection
+    db: Arc<Database>,
+    /// Age in seconds after which a block's details are pruned
+    retention: Duration,
+    /// Interval between passes
+    poll_interval: Duration,
+}
+
+impl RetentionWorker {
+    /// Create a new worker pruning the details of blocks older than `days`
+    pub fn new(db: Arc<Database>, days: u64) -> Self {
+        Self {
+            db,
+            retention: Duration::from_secs(days * 24 * 60 * 60),
+            poll_interval: Duration::from_secs(3600), // Default 1 hour
+        }
+    }
+
+    /// Configure the interval between passes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting retention interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Spawn the worker as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting retention worker, keeping transaction details for {} days", self.retention.as_secs() / 86400);
+
+        tokio::spawn(async move {
+            loop {
+                match self.prune().await {
+                    Ok(None) => debug!("No block details older than the retention period"),
+                    Ok(Some((from, to, transactions))) => {
+                        info!("Pruned {} transactions of blocks {} to {}", transactions, from, to);
+                        self.db.record_audit_event(
+                            AuditEvent::new("retention", "block_details_pruned")
+                                .with_block_number(to)
+                                .with_details(serde_json::json!({
+                                    "fromBlock": from,
+                                    "toBlock": to,
+                                    "transactions": transactions,
+                                }))
+                        ).await;
+                    }
+                    Err(e) => error!("Retention pass failed: {}", e),
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    /// Prune the details of every block older than the retention period, in batches.
+    /// Returns the range pruned and the number of transactions removed, if any.
+    async fn prune(&self) -> Result<Option<(u64, u64, u64)>, SyncError> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let cutoff = now.saturating_sub(self.retention.as_secs());
+        let Some(last_block) = self.db.get_last_block_before(cutoff).await? else {
+            return Ok(None);
+        };
+        let Some(first_block) = self.db.get_oldest_detailed_block().await?.filter(|&first| first <= last_block) else {
+            return Ok(None);
+        };
+
+        let mut transactions = 0;
+        let mut from = first_block;
+        while from <= last_block {
+            let to = last_block.min(from + PRUNE_BATCH_BLOCKS - 1);
+            transactions += self.db.prune_block_details(from, to).await?;
+            metrics::gauge!(PRUNED_BLOCK_METRIC, to as f64);
+            from = to + 1;
+        }
+
+        Ok(Some((first_block, last_block, transactions)))
+    }
+}
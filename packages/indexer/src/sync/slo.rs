@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use ethers::providers::{Provider, Http, Middleware};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::db::{AuditEvent, Database};
+use crate::sync::{SharedSyncState, SyncError};
+use crate::utils::slo::{Objective, SloTracker};
+use crate::utils::rpc;
+
+/// Fraction of good samples over the SLO window, per objective
+const COMPLIANCE_METRIC: &str = "indexer_slo_compliance";
+/// Error budget burn rate, per objective and window
+const BURN_RATE_METRIC: &str = "indexer_slo_burn_rate";
+
+/// The short window is this fraction of the SLO window, e.g. 5 minutes for 1 hour
+const SHORT_WINDOW_DIVISOR: u32 = 12;
+
+/// Samples the sync lag and evaluates the latency objectives collected by an `SloTracker`,
+/// alerting when the error budget burns faster than the threshold over both a short
+/// and a long window (the short one makes the alert resolve quickly once fixed)
+pub struct SloEvaluator {
+    /// HTTP provider used to query the chain head
+    provider: Provider<Http>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Shared state between sync components
+    sync_state: SharedSyncState,
+    /// Samples of each objective
+    tracker: Arc<SloTracker>,
+    /// Interval between evaluations
+    poll_interval: Duration,
+    /// Burn rate above which an alert fires
+    burn_rate_threshold: f64,
+}
+
+impl SloEvaluator {
+    /// Create a new SLO evaluator
+    pub fn new(
+        http_provider_url: &str,
+        db: Arc<Database>,
+        sync_state: SharedSyncState,
+        tracker: Arc<SloTracker>,
+    ) -> Result<Self, SyncError> {
+        let provider = rpc::http_provider(http_provider_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
+
+        Ok(Self {
+            provider,
+            db,
+            sync_state,
+            tracker,
+            poll_interval: Duration::from_secs(10), // Default 10 seconds
+            burn_rate_threshold: 14.4, // Default 2% of a 30 day budget spent in an hour
+        })
+    }
+
+    /// Configure how often the lag is sampled and the objectives evaluated
+    pub fn with_evaluation_interval(mut self, seconds: u64) -> Self {
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Configure the burn rate above which an alert fires
+    pub fn with_burn_rate_threshold(mut self, threshold: f64) -> Self {
+        info!("Setting SLO burn rate alert threshold to {}", threshold);
+        self.burn_rate_threshold = threshold;
+        self
+    }
+
+    /// Spawn the evaluator as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting SLO evaluator");
+
+        tokio::spawn(async move {
+            let mut firing = [false; 2];
+
+            loop {
+                self.db.idle_mode().sleep(self.poll_interval).await;
+
+                // Blocks stored during a backfill are late by design
+                if !self.tracker.is_active() {
+                    if !self.sync_state.lock().await.historic_sync_complete {
+                        continue;
+                    }
+                    info!("Historic sync complete, evaluating SLOs");
+                    self.tracker.activate();
+                }
+
+                match self.get_lag().await {
+                    Ok(lag) => self.tracker.record_lag(lag),
+                    Err(e) => error!("Failed to sample sync lag: {}", e),
+                }
+
+                for (objective, firing) in self.tracker.objectives().into_iter().zip(firing.iter_mut()) {
+                    let burning = self.evaluate(objective);
+                    if burning != *firing {
+                        self.alert_transition(objective, burning).await;
+                        *firing = burning;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Number of blocks the latest indexed block is behind the chain head
+    async fn get_lag(&self) -> Result<u64, SyncError> {
+        let head = self.provider.get_block_number()
+            .await
+            .map_err(|e| SyncError::Provider(format!("Failed to get latest block number: {}", e)))?
+            .as_u64();
+        let indexed = self.db.get_latest_block_number().await?.unwrap_or_default();
+
+        Ok(head.saturating_sub(indexed))
+    }
+
+    /// Export the objective's compliance and burn rates, returning whether it is burning too fast
+    fn evaluate(&self, objective: &Objective) -> bool {
+        let long_window = self.tracker.window();
+        let short_window = long_window / SHORT_WINDOW_DIVISOR;
+
+        if let Some(compliance) = objective.compliance(long_window) {
+            metrics::gauge!(COMPLIANCE_METRIC, compliance, "slo" => objective.name());
+        }
+
+        let short_burn = objective.burn_rate(short_window);
+        let long_burn = objective.burn_rate(long_window);
+        for (window, burn) in [("short", short_burn), ("long", long_burn)] {
+            if let Some(burn) = burn {
+                metrics::gauge!(BURN_RATE_METRIC, burn, "slo" => objective.name(), "window" => window);
+            }
+        }
+
+        debug!("SLO {}: burn rate {:?} (short), {:?} (long)", objective.name(), short_burn, long_burn);
+        short_burn.is_some_and(|burn| burn > self.burn_rate_threshold)
+            && long_burn.is_some_and(|burn| burn > self.burn_rate_threshold)
+    }
+
+    /// Log and audit an alert firing or resolving, at error level so log-based alerting picks it up
+    async fn alert_transition(&self, objective: &Objective, firing: bool) {
+        let long_window = self.tracker.window();
+        let compliance = objective.compliance(long_window).unwrap_or(1.0);
+        let burn_rate = objective.burn_rate(long_window).unwrap_or_default();
+
+        let action = if firing {
+            error!(
+                "ALERT: SLO {} burning error budget {:.1}x too fast ({:.2}% compliance over {}s, target {}%)",
+                objective.name(), burn_rate, compliance * 100.0, long_window.as_secs(), objective.target() * 100.0
+            );
+            "slo_alert_fired"
+        } else {
+            info!("SLO {} burn rate back under the threshold ({:.1}x)", objective.name(), burn_rate);
+            "slo_alert_resolved"
+        };
+
+        self.db.record_audit_event(
+            AuditEvent::new("slo_evaluator", action)
+                .with_details(serde_json::json!({
+                    "slo": objective.name(),
+                    "target": objective.target(),
+                    "compliance": compliance,
+                    "burnRate": burn_rate,
+                }))
+        ).await;
+    }
+}
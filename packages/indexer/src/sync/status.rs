@@ -0,0 +1,193 @@
+use std::sync::Arc;
+use ethers::providers::{Provider, Http, Middleware};
+use ethers::types::BlockNumber;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{AuditEvent, ChainHealth, ChainStatus, Database};
+use crate::sync::{SharedSyncState, SyncError};
+use crate::utils::rpc;
+
+/// Seconds since the chain head block was produced
+const HEAD_AGE_METRIC: &str = "indexer_chain_head_age_seconds";
+/// Seconds since the latest indexed block was produced
+const INDEXED_AGE_METRIC: &str = "indexer_indexed_block_age_seconds";
+/// 1 while the chain is stalled, 0 otherwise
+const STALLED_METRIC: &str = "indexer_chain_stalled";
+
+/// Periodically compares the chain head with the latest indexed block and records
+/// whether the chain is healthy, syncing, stalled (network halt), lagging (indexer
+/// problem), or unreachable, so ops can tell which side is broken
+pub struct ChainStatusMonitor {
+    /// HTTP provider used to query the chain head
+    provider: Provider<Http>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Shared state between sync components
+    sync_state: SharedSyncState,
+    /// Interval between checks
+    poll_interval: Duration,
+    /// Age after which a head or indexed block is considered stale (seconds)
+    stall_threshold: u64,
+}
+
+impl ChainStatusMonitor {
+    /// Create a new chain status monitor
+    pub fn new(http_provider_url: &str, db: Arc<Database>, sync_state: SharedSyncState) -> Result<Self, SyncError> {
+        let provider = rpc::http_provider(http_provider_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
+
+        Ok(Self {
+            provider,
+            db,
+            sync_state,
+            poll_interval: Duration::from_secs(5), // Default 5 seconds
+            stall_threshold: 60, // Default 1 minute without new blocks
+        })
+    }
+
+    /// Configure how long without new blocks before the chain or indexer is considered stuck
+    pub fn with_stall_threshold(mut self, seconds: u64) -> Self {
+        info!("Setting chain stall threshold to {}s", seconds);
+        self.stall_threshold = seconds;
+        self
+    }
+
+    /// Spawn the monitor as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting chain status monitor");
+
+        tokio::spawn(async move {
+            let mut previous = None;
+
+            loop {
+                let status = self.check().await;
+
+                if previous != Some(status.health) {
+                    Self::alert_transition(previous, &status);
+                    self.db.record_audit_event(
+                        AuditEvent::new("status_monitor", "chain_status_changed")
+                            .with_details(serde_json::json!({
+                                "from": previous.map(|p| p.as_str()),
+                                "to": status.health.as_str(),
+                                "detail": status.detail,
+                            }))
+                    ).await;
+                    previous = Some(status.health);
+                }
+
+                if let Err(e) = self.db.save_chain_status(&status).await {
+                    error!("Failed to save chain status: {}", e);
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    /// Determine the current chain status
+    async fn check(&self) -> ChainStatus {
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        let indexed = match self.db.get_latest_block_time().await {
+            Ok(indexed) => indexed,
+            Err(e) => {
+                error!("Failed to read latest indexed block: {}", e);
+                None
+            }
+        };
+        let (indexed_block, indexed_block_timestamp) = indexed.unzip();
+
+        let head = match self.get_head().await {
+            Ok(head) => head,
+            Err(e) => {
+                return ChainStatus {
+                    health: ChainHealth::RpcUnreachable,
+                    chain_head: None,
+                    chain_head_timestamp: None,
+                    indexed_block,
+                    indexed_block_timestamp,
+                    detail: Some(e.to_string()),
+                };
+            }
+        };
+        let (chain_head, chain_head_timestamp) = head;
+
+        let head_age = now.saturating_sub(chain_head_timestamp);
+        metrics::gauge!(HEAD_AGE_METRIC, head_age as f64);
+
+        let indexed_age = indexed_block_timestamp.map(|t| now.saturating_sub(t));
+        if let Some(age) = indexed_age {
+            metrics::gauge!(INDEXED_AGE_METRIC, age as f64);
+        }
+
+        let (health, detail) = if head_age > self.stall_threshold {
+            (
+                ChainHealth::Stalled,
+                Some(format!("No new block on chain for {}s (head #{})", head_age, chain_head)),
+            )
+        } else if !self.sync_state.lock().await.historic_sync_complete {
+            (
+                ChainHealth::Syncing,
+                Some(format!(
+                    "Historic sync in progress ({} of {} blocks)",
+                    indexed_block.unwrap_or_default(), chain_head
+                )),
+            )
+        } else if indexed_age.is_none_or(|age| age > self.stall_threshold) {
+            let behind = indexed_block.map(|n| chain_head.saturating_sub(n));
+            (
+                ChainHealth::Lagging,
+                Some(match behind {
+                    Some(behind) => format!("Indexer is {} blocks behind chain head #{}", behind, chain_head),
+                    None => format!("No blocks indexed yet (chain head #{})", chain_head),
+                }),
+            )
+        } else {
+            (ChainHealth::Healthy, None)
+        };
+
+        metrics::gauge!(STALLED_METRIC, if health == ChainHealth::Stalled { 1.0 } else { 0.0 });
+        debug!("Chain status: {} (head age {}s, indexed age {:?}s)", health, head_age, indexed_age);
+
+        ChainStatus {
+            health,
+            chain_head: Some(chain_head),
+            chain_head_timestamp: Some(chain_head_timestamp),
+            indexed_block,
+            indexed_block_timestamp,
+            detail,
+        }
+    }
+
+    /// Fetch the number and timestamp of the latest block on chain
+    async fn get_head(&self) -> Result<(u64, u64), SyncError> {
+        let block = self.provider.get_block(BlockNumber::Latest)
+            .await
+            .map_err(|e| SyncError::Provider(format!("Failed to get latest block: {}", e)))?
+            .ok_or_else(|| SyncError::Provider("Node returned no latest block".to_string()))?;
+
+        let number = block.number
+            .ok_or_else(|| SyncError::Parse("Latest block number missing".to_string()))?
+            .as_u64();
+
+        Ok((number, self.db.chain_profile().timestamp_secs(block.timestamp.as_u64())))
+    }
+
+    /// Log a status change, at error level for unhealthy states so log-based alerting picks it up
+    fn alert_transition(previous: Option<ChainHealth>, status: &ChainStatus) {
+        let detail = status.detail.as_deref().unwrap_or_default();
+
+        match status.health {
+            ChainHealth::Healthy if previous.is_some_and(|p| p != ChainHealth::Syncing) => {
+                info!("Chain status recovered: {} -> healthy", previous.map(|p| p.as_str()).unwrap_or_default());
+            }
+            ChainHealth::Healthy => info!("Chain status: healthy"),
+            ChainHealth::Syncing => info!("Chain status: syncing ({})", detail),
+            ChainHealth::Stalled => error!("ALERT: chain stalled, RPC is reachable but no new blocks: {}", detail),
+            ChainHealth::Lagging => error!("ALERT: indexer lagging behind a live chain: {}", detail),
+            ChainHealth::RpcUnreachable => warn!("ALERT: RPC node unreachable: {}", detail),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use ethers::abi::{self, ParamType, Token};
+use ethers::providers::{Provider, Http, Middleware, ProviderError};
+use ethers::types::{Address, Bytes, TransactionRequest};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{Database, TokenMetadata};
+use crate::sync::SyncError;
+use crate::utils::rpc;
+
+/// `name()`, `symbol()` and `decimals()` selectors, shared by ERC-20 and ERC-721
+const NAME_SELECTOR: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// Number of tokens read per pass
+const BATCH_SIZE: i64 = 100;
+
+/// Reads `name()`, `symbol()` and `decimals()` of token contracts newly seen in transfers
+/// and stores them in `tokens` for display
+pub struct TokenMetadataWorker {
+    /// HTTP provider used for the contract calls
+    provider: Provider<Http>,
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between passes once every known token has been read
+    poll_interval: Duration,
+}
+
+impl TokenMetadataWorker {
+    /// Create a new token metadata worker
+    pub fn new(http_provider_url: &str, db: Arc<Database>) -> Result<Self, SyncError> {
+        let provider = rpc::http_provider(http_provider_url)
+            .map_err(|e| SyncError::Provider(format!("Failed to create HTTP provider: {}", e)))?;
+
+        Ok(Self {
+            provider,
+            db,
+            poll_interval: Duration::from_secs(30), // Default 30 seconds
+        })
+    }
+
+    /// Configure the interval between passes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting token metadata poll interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Spawn the worker as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting token metadata worker");
+
+        tokio::spawn(async move {
+            loop {
+                match self.read_pending_tokens().await {
+                    // A full batch means more tokens are waiting
+                    Ok(read) if read as i64 == BATCH_SIZE => continue,
+                    Ok(read) => debug!("Read metadata of {} tokens", read),
+                    Err(e) => error!("Token metadata pass failed: {}", e),
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    /// Read the metadata of a batch of tokens without it, returning how many were stored
+    async fn read_pending_tokens(&self) -> Result<usize, SyncError> {
+        let tokens = self.db.get_tokens_without_metadata(BATCH_SIZE).await?;
+        let mut read = 0;
+
+        for (address, standard) in tokens {
+            match self.read_metadata(&address, &standard).await {
+                Ok(metadata) => {
+                    self.db.save_token_metadata(&metadata).await?;
+                    read += 1;
+                }
+                // Left without metadata, so it is retried on the next pass
+                Err(e) => warn!("Failed to read metadata of token {}: {}", address, e),
+            }
+        }
+
+        Ok(read)
+    }
+
+    /// Call the metadata getters of a token. Getters the contract doesn't implement
+    /// (ERC-1155 has none, ERC-721 has no `decimals()`) are left empty.
+    async fn read_metadata(&self, address: &str, standard: &str) -> Result<TokenMetadata, SyncError> {
+        let contract = address.parse::<Address>()
+            .map_err(|e| SyncError::Parse(format!("Invalid token address {}: {}", address, e)))?;
+
+        let name = self.call(contract, NAME_SELECTOR).await?.and_then(|data| decode_string(&data));
+        let symbol = self.call(contract, SYMBOL_SELECTOR).await?.and_then(|data| decode_string(&data));
+        let decimals = match standard {
+            "erc20" => self.call(contract, DECIMALS_SELECTOR).await?.and_then(|data| decode_decimals(&data)),
+            _ => None,
+        };
+
+        Ok(TokenMetadata {
+            address: address.to_string(),
+            name,
+            symbol,
+            decimals,
+        })
+    }
+
+    /// Call a getter on the contract, returning None if it reverts
+    async fn call(&self, contract: Address, selector: [u8; 4]) -> Result<Option<Bytes>, SyncError> {
+        let request = TransactionRequest::new().to(contract).data(selector.to_vec());
+
+        match self.provider.call(&request.into(), None).await {
+            Ok(data) => Ok(Some(data)),
+            // The node answered with an error, e.g. `execution reverted`, rather than failing
+            Err(ProviderError::JsonRpcClientError(e)) if e.as_error_response().is_some() => Ok(None),
+            Err(e) => Err(SyncError::Provider(format!("Failed to call token {:?}: {}", contract, e))),
+        }
+    }
+}
+
+/// Decode a string return value. Some early tokens (e.g. MKR) return `bytes32` instead.
+fn decode_string(data: &[u8]) -> Option<String> {
+    let value = if data.len() == 32 {
+        String::from_utf8(data.iter().copied().take_while(|b| *b != 0).collect()).ok()?
+    } else {
+        match abi::decode(&[ParamType::String], data).ok()?.pop()? {
+            Token::String(value) => value,
+            _ => return None,
+        }
+    };
+
+    // Postgres rejects NUL in text
+    let value = value.replace('\0', "").trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Decode a `uint8` return value
+fn decode_decimals(data: &[u8]) -> Option<u8> {
+    match abi::decode(&[ParamType::Uint(8)], data).ok()?.pop()? {
+        Token::Uint(value) if value <= 255.into() => Some(value.as_u32() as u8),
+        _ => None,
+    }
+}
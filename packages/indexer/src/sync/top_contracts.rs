@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info};
+
+use crate::db::Database;
+
+/// Keeps the `top_contracts` leaderboard of the most called contracts over the last hour,
+/// day and week up to date.
+///
+/// Calls are counted into 5 minute buckets as blocks are saved; each pass ranks the buckets
+/// in each window and prunes those older than the longest one.
+pub struct TopContractsWorker {
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between passes
+    poll_interval: Duration,
+    /// Contracts kept per window and ranking
+    size: u32,
+}
+
+impl TopContractsWorker {
+    /// Create a new top contracts worker
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            poll_interval: Duration::from_secs(60), // Default 1 minute
+            size: 100,
+        }
+    }
+
+    /// Configure the interval between passes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting top contracts interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Configure how many contracts are kept per window and ranking
+    pub fn with_size(mut self, size: u32) -> Self {
+        info!("Keeping the top {} contracts", size);
+        self.size = size;
+        self
+    }
+
+    /// Spawn the worker as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting top contracts worker");
+
+        tokio::spawn(async move {
+            loop {
+                match self.db.refresh_top_contracts(self.size).await {
+                    Ok(rows) => debug!("Refreshed top contracts ({} rows)", rows),
+                    Err(e) => error!("Top contracts pass failed: {}", e),
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use std::sync::Arc;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::json;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::db::Database;
+use crate::models::Block;
+use crate::sync::SyncError;
+
+/// Name of the ClickHouse sink in `warehouse_exports`
+const SINK: &str = "clickhouse";
+
+/// Gauge of the last block shipped to the warehouse
+const EXPORTED_BLOCK_METRIC: &str = "indexer_warehouse_exported_block";
+
+/// Tables created in the warehouse if missing. ReplacingMergeTree keeps one row per key, so
+/// a batch shipped again after a failed high-water mark update doesn't duplicate rows.
+const CREATE_TABLES: &[&str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS blocks (
+        number UInt64,
+        hash String,
+        parent_hash String,
+        timestamp DateTime,
+        miner String,
+        gas_used UInt64,
+        gas_limit UInt64,
+        base_fee_per_gas Nullable(UInt64),
+        size UInt64,
+        transaction_count UInt64,
+        extra_data String
+    ) ENGINE = ReplacingMergeTree ORDER BY number
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS transactions (
+        block_number UInt64,
+        transaction_index UInt64,
+        hash String,
+        block_hash String,
+        block_timestamp DateTime,
+        from_address Nullable(String),
+        to_address Nullable(String),
+        value String,
+        gas UInt64,
+        gas_price Nullable(UInt64),
+        nonce UInt64,
+        input String
+    ) ENGINE = ReplacingMergeTree ORDER BY (block_number, transaction_index)
+    "#,
+];
+
+/// Ships finalized blocks and their transactions to a ClickHouse warehouse, so analytics
+/// queries run there instead of against the explorer's database.
+///
+/// Blocks are shipped in ascending order from the sink's high-water mark in `warehouse_exports`,
+/// up to the `finalized` watermark (the latest stored block on chains without finality).
+/// A pass stops at the first block missing from the database, so gaps are shipped once repaired.
+pub struct WarehousePublisher {
+    /// HTTP client, carrying the warehouse credentials
+    client: reqwest::Client,
+    /// ClickHouse HTTP interface, e.g. `http://clickhouse:8123`
+    url: String,
+    /// Database the warehouse tables live in
+    database: String,
+    /// Database connection
+    db: Arc<Database>,
+    /// Interval between passes
+    poll_interval: Duration,
+    /// Blocks shipped per insert
+    batch_size: u64,
+    /// Ship blocks up to the latest stored one when there is no `finalized` watermark
+    without_finality: bool,
+}
+
+impl WarehousePublisher {
+    /// Create a new publisher to the ClickHouse HTTP interface at `url`
+    pub fn new(url: &str, user: Option<&str>, password: Option<&str>, db: Arc<Database>) -> Result<Self, SyncError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [("x-clickhouse-user", user), ("x-clickhouse-key", password)] {
+            if let Some(value) = value {
+                let value = HeaderValue::from_str(value)
+                    .map_err(|e| SyncError::Http(format!("Invalid warehouse credentials: {}", e)))?;
+                headers.insert(name, value);
+            }
+        }
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(Duration::from_secs(60))
+            .build()
+            .map_err(|e| SyncError::Http(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            database: "default".to_string(),
+            db,
+            poll_interval: Duration::from_secs(60), // Default 1 minute
+            batch_size: 1000,
+            without_finality: false,
+        })
+    }
+
+    /// Configure the warehouse database the tables are created in
+    pub fn with_database(mut self, database: &str) -> Self {
+        info!("Publishing to warehouse database {}", database);
+        self.database = database.to_string();
+        self
+    }
+
+    /// Configure the interval between passes
+    pub fn with_poll_interval(mut self, seconds: u64) -> Self {
+        info!("Setting warehouse publishing interval to {}s", seconds);
+        self.poll_interval = Duration::from_secs(seconds);
+        self
+    }
+
+    /// Configure the number of blocks shipped per insert
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Ship blocks up to the latest stored one, for chains without a `finalized` tag
+    pub fn without_finality(mut self) -> Self {
+        self.without_finality = true;
+        self
+    }
+
+    /// Spawn the publisher as a background task
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting warehouse publisher to {}", self.url);
+
+        tokio::spawn(async move {
+            let mut tables_created = false;
+
+            loop {
+                if !tables_created {
+                    match self.create_tables().await {
+                        Ok(()) => tables_created = true,
+                        Err(e) => error!("Failed to create warehouse tables: {}", e),
+                    }
+                }
+
+                if tables_created {
+                    match self.publish().await {
+                        Ok(0) => debug!("Warehouse is up to date"),
+                        Ok(blocks) => info!("Shipped {} blocks to the warehouse", blocks),
+                        // Retried from the high-water mark on the next pass
+                        Err(e) => error!("Warehouse publishing pass failed: {}", e),
+                    }
+                }
+
+                self.db.idle_mode().sleep(self.poll_interval).await;
+            }
+        })
+    }
+
+    async fn create_tables(&self) -> Result<(), SyncError> {
+        for statement in CREATE_TABLES {
+            self.execute(statement, String::new()).await?;
+        }
+        Ok(())
+    }
+
+    /// Ship the blocks between the high-water mark and the publishable head in batches,
+    /// returning how many were shipped
+    async fn publish(&self) -> Result<u64, SyncError> {
+        let Some(head) = self.publishable_head().await? else {
+            return Ok(0);
+        };
+
+        let mut from = match self.db.get_export_high_water_mark(SINK).await? {
+            Some(last_block) => last_block + 1,
+            None => match self.db.get_earliest_block_number().await? {
+                Some(earliest) => earliest,
+                None => return Ok(0),
+            },
+        };
+
+        let mut shipped = 0;
+        while from <= head {
+            let to = head.min(from + self.batch_size - 1);
+            let blocks = self.db.get_blocks_range(from, to).await?;
+
+            // Only ship the blocks before the first gap, so the high-water mark never skips one
+            let contiguous = blocks.iter()
+                .zip(from..)
+                .take_while(|(block, number)| block.number == *number)
+                .count();
+            if contiguous == 0 {
+                warn!("Block {} is missing from the database, waiting for it before shipping more", from);
+                break;
+            }
+            let blocks = &blocks[..contiguous];
+
+            let transactions = self.insert_blocks(blocks).await?;
+            let last_block = blocks[contiguous - 1].number;
+            self.db.save_export_high_water_mark(SINK, last_block, contiguous as u64, transactions).await?;
+            metrics::gauge!(EXPORTED_BLOCK_METRIC, last_block as f64);
+
+            shipped += contiguous as u64;
+            if last_block < to {
+                warn!("Block {} is missing from the database, waiting for it before shipping more", last_block + 1);
+                break;
+            }
+            from = last_block + 1;
+        }
+
+        Ok(shipped)
+    }
+
+    /// Latest block that may be shipped: the `finalized` watermark, or the latest stored
+    /// block when configured without finality
+    async fn publishable_head(&self) -> Result<Option<u64>, SyncError> {
+        if self.without_finality {
+            return Ok(self.db.get_latest_block_number().await?);
+        }
+
+        Ok(self.db.get_watermark("finalized").await?.map(|watermark| watermark.block_number))
+    }
+
+    /// Insert the blocks and their transactions, returning the number of transactions
+    async fn insert_blocks(&self, blocks: &[Block]) -> Result<u64, SyncError> {
+        let mut block_rows = String::new();
+        let mut transaction_rows = String::new();
+        let mut transactions = 0;
+
+        for block in blocks {
+            block_rows.push_str(&json!({
+                "number": block.number,
+                "hash": block.hash,
+                "parent_hash": block.parent_hash,
+                "timestamp": block.timestamp,
+                "miner": block.miner,
+                "gas_used": block.gas_used,
+                "gas_limit": block.gas_limit,
+                "base_fee_per_gas": block.base_fee_per_gas,
+                "size": block.size,
+                "transaction_count": block.transaction_count,
+                "extra_data": block.extra_data,
+            }).to_string());
+            block_rows.push('\n');
+
+            for tx in &block.transactions {
+                transaction_rows.push_str(&json!({
+                    "block_number": block.number,
+                    "transaction_index": tx.transaction_index,
+                    "hash": tx.hash,
+                    "block_hash": block.hash,
+                    "block_timestamp": block.timestamp,
+                    "from_address": tx.from,
+                    "to_address": tx.to,
+                    "value": tx.value,
+                    "gas": tx.gas,
+                    "gas_price": tx.gas_price,
+                    "nonce": tx.nonce,
+                    "input": tx.input,
+                }).to_string());
+                transaction_rows.push('\n');
+                transactions += 1;
+            }
+        }
+
+        self.execute("INSERT INTO blocks FORMAT JSONEachRow", block_rows).await?;
+        if transactions > 0 {
+            self.execute("INSERT INTO transactions FORMAT JSONEachRow", transaction_rows).await?;
+        }
+
+        Ok(transactions)
+    }
+
+    /// Run a statement over the HTTP interface, with `data` as the rows of an insert
+    async fn execute(&self, query: &str, data: String) -> Result<(), SyncError> {
+        // Statements without data are sent as the body, inserts carry their rows instead
+        let request = if data.is_empty() {
+            self.client.post(&self.url).body(query.to_string())
+        } else {
+            self.client.post(&self.url).query(&[("query", query)]).body(data)
+        };
+
+        let response = request
+            .query(&[("database", self.database.as_str())])
+            .send()
+            .await
+            .map_err(|e| SyncError::Http(format!("Warehouse request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(SyncError::Http(format!("Warehouse returned {}: {}", status, body.trim())));
+        }
+
+        Ok(())
+    }
+}
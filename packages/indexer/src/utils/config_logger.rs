@@ -7,6 +7,28 @@ pub fn log_config(config: &crate::config::Config) {
         config.start_block, config.batch_size, config.max_concurrent_requests, config.rpc_batch_size
     );
     
+    info!("Slow query threshold: {}ms", config.slow_query_threshold_ms);
+    
+    if config.rpc_authorization.is_some() {
+        info!("Sending an Authorization header to RPC endpoints");
+    }
+    
+    if config.fetch_full_transactions {
+        info!("Fetching full transaction objects for each block");
+    }
+    
+    if config.fetch_receipts {
+        info!("Fetching transaction receipts for each block");
+    }
+    
+    if config.ordered_commits {
+        info!("Persisting blocks in ascending order (waiting up to {}s for missing blocks)", config.ordered_commit_max_wait);
+    }
+    
+    if let Some(remote_explorer_url) = &config.remote_explorer_url {
+        info!("Mirroring blocks from remote explorer at {}", remote_explorer_url);
+    }
+    
     // Log blocks_from_tip if set
     if let Some(blocks_from_tip) = config.blocks_from_tip {
         info!("Indexing {} blocks from chain tip", blocks_from_tip);
@@ -0,0 +1,239 @@
+//! Health tracking for a set of interchangeable RPC endpoints, so a connection can fail over
+//! to another endpoint when the one in use dies, or requests can be spread across them
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Gauge with each endpoint's health score, from 0 (failing) to 1 (healthy)
+const ENDPOINT_HEALTH_METRIC: &str = "indexer_endpoint_health";
+
+/// Weight of the latest outcome in an endpoint's health score
+const SCORE_SMOOTHING: f64 = 0.3;
+
+/// Cooldown after a first failure, doubled for each further consecutive failure
+const BASE_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Upper bound for the cooldown
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct EndpointHealth {
+    url: String,
+    /// Moving average of outcomes, 1 for success and 0 for failure
+    score: f64,
+    consecutive_failures: u32,
+    /// Not selected before this time, unless every endpoint is cooling down
+    cooldown_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    fn is_available(&self, now: Instant) -> bool {
+        self.cooldown_until.is_none_or(|until| until <= now)
+    }
+}
+
+/// Endpoints ranked by health: the healthiest endpoint that isn't cooling down after a
+/// failure is selected, so a single endpoint outage doesn't stall ingestion
+#[derive(Debug)]
+pub struct EndpointPool {
+    /// What the endpoints serve, for logs, e.g. `WebSocket`
+    kind: &'static str,
+    endpoints: Mutex<Vec<EndpointHealth>>,
+    /// Index the round-robin selection resumes from
+    cursor: AtomicUsize,
+}
+
+impl EndpointPool {
+    pub fn new(kind: &'static str, urls: Vec<String>) -> Self {
+        let endpoints = urls.into_iter()
+            .map(|url| EndpointHealth {
+                url,
+                score: 1.0,
+                consecutive_failures: 0,
+                cooldown_until: None,
+            })
+            .collect();
+
+        Self {
+            kind,
+            endpoints: Mutex::new(endpoints),
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.lock().unwrap().len()
+    }
+
+    /// Pick the endpoint to connect to, returning its index and URL. Ties go to the endpoint
+    /// listed first; when all are cooling down, the one available soonest is picked.
+    pub fn select(&self) -> (usize, String) {
+        let now = Instant::now();
+        let endpoints = self.endpoints.lock().unwrap();
+
+        let available = endpoints.iter()
+            .enumerate()
+            .filter(|(_, endpoint)| endpoint.is_available(now))
+            .max_by(|(a_index, a), (b_index, b)| a.score.total_cmp(&b.score).then(b_index.cmp(a_index)));
+
+        let (index, endpoint) = available.unwrap_or_else(|| {
+            endpoints.iter()
+                .enumerate()
+                .min_by_key(|(_, endpoint)| endpoint.cooldown_until)
+                .expect("endpoint pool is empty")
+        });
+        (index, endpoint.url.clone())
+    }
+
+    /// Pick the endpoint for the next request, rotating through the endpoints that aren't
+    /// cooling down. When all are cooling down, the one available soonest is picked.
+    pub fn next(&self) -> usize {
+        let now = Instant::now();
+        let endpoints = self.endpoints.lock().unwrap();
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed);
+
+        (0..endpoints.len())
+            .map(|offset| (start + offset) % endpoints.len())
+            .find(|&index| endpoints[index].is_available(now))
+            .unwrap_or_else(|| {
+                endpoints.iter()
+                    .enumerate()
+                    .min_by_key(|(_, endpoint)| endpoint.cooldown_until)
+                    .map(|(index, _)| index)
+                    .expect("endpoint pool is empty")
+            })
+    }
+
+    /// Whether the endpoint failed last time it was used, so it should pass a health check
+    /// before serving requests again
+    pub fn needs_health_check(&self, index: usize) -> bool {
+        self.endpoints.lock().unwrap().get(index).is_some_and(|endpoint| endpoint.consecutive_failures > 0)
+    }
+
+    /// Whether an endpoint other than `index` is not cooling down, i.e. worth failing over to
+    pub fn has_alternative(&self, index: usize) -> bool {
+        let now = Instant::now();
+        self.endpoints.lock().unwrap().iter()
+            .enumerate()
+            .any(|(i, endpoint)| i != index && endpoint.is_available(now))
+    }
+
+    pub fn record_success(&self, index: usize) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let Some(endpoint) = endpoints.get_mut(index) else {
+            return;
+        };
+
+        endpoint.score += SCORE_SMOOTHING * (1.0 - endpoint.score);
+        endpoint.consecutive_failures = 0;
+        endpoint.cooldown_until = None;
+        metrics::gauge!(ENDPOINT_HEALTH_METRIC, endpoint.score, "kind" => self.kind, "endpoint" => index.to_string());
+    }
+
+    /// Lower the endpoint's score and keep it out of rotation for a backoff period
+    pub fn record_failure(&self, index: usize) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let Some(endpoint) = endpoints.get_mut(index) else {
+            return;
+        };
+
+        endpoint.score -= SCORE_SMOOTHING * endpoint.score;
+        endpoint.consecutive_failures += 1;
+        let cooldown = BASE_COOLDOWN
+            .saturating_mul(1 << endpoint.consecutive_failures.min(16).saturating_sub(1))
+            .min(MAX_COOLDOWN);
+        endpoint.cooldown_until = Some(Instant::now() + cooldown);
+        metrics::gauge!(ENDPOINT_HEALTH_METRIC, endpoint.score, "kind" => self.kind, "endpoint" => index.to_string());
+
+        if endpoints.len() > 1 {
+            warn!(
+                "{} endpoint #{} failed ({} in a row, health {:.2}), out of rotation for {}s",
+                self.kind, index, endpoints[index].consecutive_failures, endpoints[index].score, cooldown.as_secs()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(endpoints: usize) -> EndpointPool {
+        EndpointPool::new("Test", (0..endpoints).map(|i| format!("ws://endpoint{}", i)).collect())
+    }
+
+    /// End the endpoint's cooldown without waiting for it
+    fn end_cooldown(pool: &EndpointPool, index: usize) {
+        pool.endpoints.lock().unwrap()[index].cooldown_until = Some(Instant::now());
+    }
+
+    #[test]
+    fn selects_the_healthiest_endpoint() {
+        let pool = pool(3);
+        assert_eq!(pool.select(), (0, "ws://endpoint0".to_string()));
+
+        // Back from its cooldown and successful again, but still less healthy than the others
+        pool.record_failure(0);
+        end_cooldown(&pool, 0);
+        pool.record_success(0);
+        assert_eq!(pool.select().0, 1);
+
+        pool.record_failure(1);
+        end_cooldown(&pool, 1);
+        pool.record_failure(2);
+        end_cooldown(&pool, 2);
+        assert_eq!(pool.select().0, 0);
+    }
+
+    #[test]
+    fn skips_a_failed_endpoint_until_its_cooldown_ends() {
+        let pool = pool(2);
+        pool.record_failure(0);
+        assert!(pool.needs_health_check(0));
+        assert_eq!(pool.select().0, 1);
+        assert_eq!(pool.next(), 1);
+        assert_eq!(pool.next(), 1);
+
+        end_cooldown(&pool, 0);
+        // The failure still counts against its health, but it is back in rotation
+        assert_eq!(pool.select().0, 1);
+        assert_eq!(pool.next(), 0);
+    }
+
+    #[test]
+    fn rotates_through_available_endpoints() {
+        let pool = pool(3);
+        assert_eq!((0..4).map(|_| pool.next()).collect::<Vec<_>>(), vec![0, 1, 2, 0]);
+
+        pool.record_failure(2);
+        assert_eq!((0..3).map(|_| pool.next()).collect::<Vec<_>>(), vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn falls_back_to_the_endpoint_available_soonest() {
+        let pool = pool(2);
+
+        // The second consecutive failure doubles the cooldown
+        pool.record_failure(0);
+        pool.record_failure(0);
+        pool.record_failure(1);
+
+        assert_eq!(pool.next(), 1);
+        assert_eq!(pool.next(), 1);
+        assert_eq!(pool.select().0, 1);
+    }
+
+    #[test]
+    fn has_alternative_only_counts_other_available_endpoints() {
+        let pool = pool(2);
+        assert!(pool.has_alternative(0));
+
+        pool.record_failure(1);
+        assert!(!pool.has_alternative(0));
+        assert!(pool.has_alternative(1));
+
+        assert!(!self::pool(1).has_alternative(0));
+    }
+}
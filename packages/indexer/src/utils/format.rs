@@ -0,0 +1,218 @@
+//! Number formatting shared by the indexer logs and the CLI binaries, so every
+//! surface renders counts and ether amounts the same way
+
+use ethers::types::U256;
+
+/// Denominations of ether
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Wei,
+    Gwei,
+    Ether,
+}
+
+impl Unit {
+    /// Number of decimals between wei and this unit
+    pub fn decimals(self) -> usize {
+        match self {
+            Unit::Wei => 0,
+            Unit::Gwei => 9,
+            Unit::Ether => 18,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Wei => "wei",
+            Unit::Gwei => "gwei",
+            Unit::Ether => "ETH",
+        }
+    }
+}
+
+/// Separators and precision used to render numbers.
+/// The default renders `1234567.891` as `1,234,567.891`, with at most 4 decimals.
+#[derive(Clone, Copy, Debug)]
+pub struct NumberFormat {
+    /// Inserted between groups of three integer digits, if any
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+    /// Fractional digits beyond this are truncated, never rounded up
+    pub max_decimals: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+            max_decimals: 4,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Configure the thousands separator, or None for plain digits
+    pub fn with_thousands_separator(mut self, separator: Option<char>) -> Self {
+        self.thousands_separator = separator;
+        self
+    }
+
+    /// Configure the decimal separator
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Configure the number of fractional digits shown
+    pub fn with_max_decimals(mut self, max_decimals: usize) -> Self {
+        self.max_decimals = max_decimals;
+        self
+    }
+
+    /// Render an integer, e.g. a block number or transaction count
+    pub fn integer(&self, value: impl Into<U256>) -> String {
+        self.group(&value.into().to_string())
+    }
+
+    /// Render a fixed-point amount with `decimals` fractional digits, such as a token balance.
+    /// Trailing zeros are dropped, so whole amounts have no decimal part.
+    pub fn fixed(&self, value: U256, decimals: usize) -> String {
+        // 10^decimals overflows U256 beyond 77 decimals, at which point every value is a fraction
+        let (integer, fraction) = match U256::from(10).checked_pow(U256::from(decimals)) {
+            Some(scale) => value.div_mod(scale),
+            None => (U256::zero(), value),
+        };
+
+        let mut formatted = self.group(&integer.to_string());
+
+        let digits = format!("{:0>width$}", fraction.to_string(), width = decimals);
+        let shown = digits[..self.max_decimals.min(decimals)].trim_end_matches('0');
+        if !shown.is_empty() {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(shown);
+        } else if integer.is_zero() && !fraction.is_zero() {
+            // Don't render a non-zero amount as 0, e.g. 7 wei in gwei is `<0.0001`
+            return match self.max_decimals {
+                0 => "<1".to_string(),
+                n => format!("<0{}{}1", self.decimal_separator, "0".repeat(n - 1)),
+            };
+        }
+
+        formatted
+    }
+
+    /// Render a wei amount in `unit`, followed by the unit symbol
+    pub fn units(&self, wei: U256, unit: Unit) -> String {
+        format!("{} {}", self.fixed(wei, unit.decimals()), unit.symbol())
+    }
+
+    fn group(&self, digits: &str) -> String {
+        let Some(separator) = self.thousands_separator else {
+            return digits.to_string();
+        };
+
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+/// Render an integer with the default format, e.g. `1,234,567`
+pub fn format_integer(value: impl Into<U256>) -> String {
+    NumberFormat::default().integer(value)
+}
+
+/// Render a wei amount in `unit` with the default format, e.g. `1.5 gwei`
+pub fn format_units(wei: U256, unit: Unit) -> String {
+    NumberFormat::default().units(wei, unit)
+}
+
+/// Convert a decimal amount in `unit` (e.g. `"1.5"` gwei) to wei.
+/// Returns None if the amount is malformed, has more decimals than the unit, or overflows.
+pub fn parse_units(amount: &str, unit: Unit) -> Option<U256> {
+    let amount = amount.trim();
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty())
+        || !is_digits(integer)
+        || !is_digits(fraction)
+        || fraction.len() > unit.decimals()
+    {
+        return None;
+    }
+
+    U256::from_dec_str(&format!("{}{:0<width$}", integer, fraction, width = unit.decimals())).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_thousands() {
+        assert_eq!(format_integer(0u64), "0");
+        assert_eq!(format_integer(999u64), "999");
+        assert_eq!(format_integer(1000u64), "1,000");
+        assert_eq!(format_integer(100_000u64), "100,000");
+        assert_eq!(format_integer(1_234_567u64), "1,234,567");
+        assert_eq!(format_integer(U256::MAX).split(',').next(), Some("115"));
+    }
+
+    #[test]
+    fn uses_the_configured_separators() {
+        let format = NumberFormat::default().with_thousands_separator(Some('.')).with_decimal_separator(',');
+        assert_eq!(format.fixed(U256::from(1_234_567_891u64), 3), "1.234.567,891");
+
+        let plain = NumberFormat::default().with_thousands_separator(None);
+        assert_eq!(plain.integer(1_234_567u64), "1234567");
+    }
+
+    #[test]
+    fn truncates_and_trims_decimals() {
+        let format = NumberFormat::default();
+        assert_eq!(format.fixed(U256::from(199_999u64), 5), "1.9999");
+        assert_eq!(format.fixed(U256::from(150_000u64), 5), "1.5");
+        assert_eq!(format.fixed(U256::from(200_000u64), 5), "2");
+        assert_eq!(format.fixed(U256::from(1_234u64), 0), "1,234");
+        assert_eq!(format.with_max_decimals(2).fixed(U256::from(123_456u64), 4), "12.34");
+    }
+
+    #[test]
+    fn never_renders_a_non_zero_amount_as_zero() {
+        assert_eq!(format_units(U256::from(7u64), Unit::Gwei), "<0.0001 gwei");
+        assert_eq!(NumberFormat::default().with_max_decimals(0).fixed(U256::from(7u64), 9), "<1");
+        // 10^80 doesn't fit in U256, so every value is a fraction
+        assert_eq!(NumberFormat::default().fixed(U256::from(5u64), 80), "<0.0001");
+        assert_eq!(format_units(U256::zero(), Unit::Ether), "0 ETH");
+    }
+
+    #[test]
+    fn formats_units() {
+        assert_eq!(format_units(U256::from(1_500_000_000u64), Unit::Gwei), "1.5 gwei");
+        assert_eq!(format_units(U256::from(1_234u64) * U256::exp10(18), Unit::Ether), "1,234 ETH");
+        assert_eq!(format_units(U256::from(21_000u64), Unit::Wei), "21,000 wei");
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_units("1.5", Unit::Gwei), Some(U256::from(1_500_000_000u64)));
+        assert_eq!(parse_units(" 2 ", Unit::Gwei), Some(U256::from(2_000_000_000u64)));
+        assert_eq!(parse_units(".5", Unit::Ether), Some(U256::exp10(17) * 5));
+        assert_eq!(parse_units("1.", Unit::Wei), Some(U256::one()));
+
+        assert_eq!(parse_units("", Unit::Gwei), None);
+        assert_eq!(parse_units(".", Unit::Gwei), None);
+        assert_eq!(parse_units("-1", Unit::Gwei), None);
+        assert_eq!(parse_units("1.2.3", Unit::Gwei), None);
+        assert_eq!(parse_units("0.0000000001", Unit::Gwei), None);
+        assert_eq!(parse_units("1.5", Unit::Wei), None);
+        assert_eq!(parse_units(&"9".repeat(70), Unit::Ether), None);
+    }
+}
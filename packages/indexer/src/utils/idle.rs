@@ -0,0 +1,97 @@
+//! Idle mode for quiet chains such as dev deployments: once no transactions have been
+//! indexed for a while, background loops poll less often and per-block logging quiets
+//! down, until the next block with transactions wakes everything up again
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::time::sleep;
+use tracing::info;
+
+/// 1 while the indexer is in idle mode
+const IDLE_METRIC: &str = "indexer_idle";
+
+#[derive(Debug)]
+pub struct IdleMode {
+    /// Time without activity before going idle, None if idle mode is disabled
+    idle_after: Option<Duration>,
+    /// Factor intervals are stretched by while idle
+    multiplier: u32,
+    last_activity: Mutex<Instant>,
+    idle: AtomicBool,
+    /// Wakes loops sleeping through an idle interval
+    wake: Notify,
+}
+
+impl Default for IdleMode {
+    fn default() -> Self {
+        Self::new(0, 1)
+    }
+}
+
+impl IdleMode {
+    /// Go idle after `idle_after_secs` without activity (0 disables idle mode)
+    pub fn new(idle_after_secs: u64, multiplier: u32) -> Self {
+        Self {
+            idle_after: (idle_after_secs > 0).then(|| Duration::from_secs(idle_after_secs)),
+            multiplier: multiplier.max(1),
+            last_activity: Mutex::new(Instant::now()),
+            idle: AtomicBool::new(false),
+            wake: Notify::new(),
+        }
+    }
+
+    /// Record activity, leaving idle mode and waking sleeping loops
+    pub fn record_activity(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+
+        if self.idle.swap(false, Ordering::Relaxed) {
+            info!("Activity resumed, leaving idle mode");
+            metrics::gauge!(IDLE_METRIC, 0.0);
+            self.wake.notify_waiters();
+        }
+    }
+
+    /// Whether nothing has happened for longer than the idle threshold
+    pub fn is_idle(&self) -> bool {
+        let Some(idle_after) = self.idle_after else {
+            return false;
+        };
+
+        if self.idle.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let quiet_for = self.last_activity.lock().unwrap().elapsed();
+        if quiet_for < idle_after {
+            return false;
+        }
+
+        if !self.idle.swap(true, Ordering::Relaxed) {
+            info!(
+                "No activity for {}s, entering idle mode (intervals stretched {}x)",
+                quiet_for.as_secs(), self.multiplier
+            );
+            metrics::gauge!(IDLE_METRIC, 1.0);
+        }
+        true
+    }
+
+    /// Sleep for `interval` between passes of a background loop, or `multiplier` times
+    /// longer while idle, returning as soon as activity resumes
+    pub async fn sleep(&self, interval: Duration) {
+        // Registered before checking, so activity in between still wakes us
+        let woken = self.wake.notified();
+
+        if !self.is_idle() {
+            sleep(interval).await;
+            return;
+        }
+
+        tokio::select! {
+            _ = sleep(interval * self.multiplier) => {}
+            _ = woken => {}
+        }
+    }
+}
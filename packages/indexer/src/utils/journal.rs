@@ -0,0 +1,44 @@
+//! In-memory buffer of ingestion journal entries, written to `ingest_journal` in batches
+//! by the journal writer so recording an event never waits on the database
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::db::JournalEntry;
+
+/// Entries held between flushes before the oldest are dropped
+const BUFFER_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Default)]
+pub struct IngestJournal {
+    entries: Mutex<VecDeque<JournalEntry>>,
+    /// Entries dropped since the last flush because the buffer was full
+    dropped: Mutex<u64>,
+}
+
+impl IngestJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffer an entry, dropping the oldest one if the writer has fallen behind
+    pub fn record(&self, entry: JournalEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= BUFFER_CAPACITY {
+            entries.pop_front();
+            *self.dropped.lock().unwrap() += 1;
+        }
+        entries.push_back(entry);
+    }
+
+    /// Take the buffered entries, oldest first
+    pub fn drain(&self) -> Vec<JournalEntry> {
+        let dropped = std::mem::take(&mut *self.dropped.lock().unwrap());
+        if dropped > 0 {
+            warn!("Journal buffer full, dropped {} entries", dropped);
+        }
+
+        self.entries.lock().unwrap().drain(..).collect()
+    }
+}
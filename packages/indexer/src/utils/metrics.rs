@@ -0,0 +1,31 @@
+use anyhow::{Context, Result};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+use std::net::SocketAddr;
+use tracing::info;
+
+use crate::db::QUERY_DURATION_METRIC;
+
+/// Histogram buckets (seconds) used for query duration metrics
+const QUERY_DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Install the Prometheus exporter, serving metrics over HTTP on the given address
+pub fn init_metrics(listen_addr: &str) -> Result<()> {
+    let addr: SocketAddr = listen_addr
+        .parse()
+        .context("METRICS_ADDR must be a valid socket address")?;
+
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .set_buckets_for_metric(
+            Matcher::Full(QUERY_DURATION_METRIC.to_string()),
+            QUERY_DURATION_BUCKETS,
+        )
+        .context("Failed to configure query duration buckets")?
+        .install()
+        .context("Failed to install Prometheus metrics exporter")?;
+
+    info!("Prometheus metrics available at http://{}/metrics", addr);
+    Ok(())
+}
@@ -1,4 +1,15 @@
 pub mod logger;
+#[cfg(feature = "prometheus")]
+pub mod metrics;
 pub mod retry;
+pub mod slo;
 pub mod config_logger;
 pub mod time;
+pub mod endpoints;
+pub mod idle;
+pub mod journal;
+pub mod rate_limit;
+pub mod rpc;
+// Also compiled into the CLI binaries, which use more of it than the indexer
+#[allow(dead_code)]
+pub mod format;
@@ -0,0 +1,106 @@
+//! Token bucket limiting the rate of RPC requests, shared by every block fetcher worker so
+//! the total request rate stays under a provider's limit however many batches run at once
+
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+/// Histogram of how long requests waited for the rate limiter, in seconds
+const RATE_LIMIT_WAIT_METRIC: &str = "indexer_rpc_rate_limit_wait_seconds";
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Requests allowed per second, also the burst size
+    requests_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = f64::from(requests_per_second.max(1));
+
+        Self {
+            requests_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: requests_per_second,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until `requests` more requests may be sent. Waiters are served in order, and
+    /// the bucket stays locked while one waits so later callers queue behind it.
+    pub async fn acquire(&self, requests: usize) {
+        let requests = requests as f64;
+        let mut bucket = self.bucket.lock().await;
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.requests_per_second);
+        bucket.refilled_at = now;
+
+        let missing = requests - bucket.tokens;
+        if missing <= 0.0 {
+            bucket.tokens -= requests;
+            return;
+        }
+
+        // Wait for the missing tokens to refill, then spend them all
+        let wait = Duration::from_secs_f64(missing / self.requests_per_second);
+        metrics::histogram!(RATE_LIMIT_WAIT_METRIC, wait.as_secs_f64());
+        sleep(wait).await;
+        bucket.tokens = 0.0;
+        bucket.refilled_at = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// How long acquiring `requests` takes
+    async fn time_acquire(limiter: &RateLimiter, requests: usize) -> Duration {
+        let started = Instant::now();
+        limiter.acquire(requests).await;
+        started.elapsed()
+    }
+
+    #[tokio::test]
+    async fn allows_a_full_bucket_at_once() {
+        let limiter = RateLimiter::new(50);
+        assert!(time_acquire(&limiter, 20).await < Duration::from_millis(50));
+        assert!(time_acquire(&limiter, 30).await < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_bucket_to_refill() {
+        let limiter = RateLimiter::new(100);
+        limiter.acquire(100).await;
+
+        // 10 tokens refill in 100ms
+        assert!(time_acquire(&limiter, 10).await >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_difference_on_requests_larger_than_the_bucket() {
+        let limiter = RateLimiter::new(100);
+
+        // The full bucket covers 100 requests, the other 50 take 500ms to refill
+        assert!(time_acquire(&limiter, 150).await >= Duration::from_millis(490));
+
+        // and leave the bucket empty
+        assert!(time_acquire(&limiter, 10).await >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn allows_at_least_one_request_per_second() {
+        let limiter = RateLimiter::new(0);
+        assert!(time_acquire(&limiter, 1).await < Duration::from_millis(50));
+    }
+}
@@ -1,6 +1,23 @@
 use std::time::Duration;
 use tracing::{error, warn};
 
+/// Error messages of nodes asked for blocks or state they pruned, lowercase
+const PRUNED_ERROR_PATTERNS: &[&str] = &[
+    "missing trie node",
+    "pruned",
+    "history unavailable",
+    "historical state",
+    "state is not available",
+    "not available on this node",
+];
+
+/// Whether an RPC error means the node no longer has the requested data, which
+/// retrying won't change
+pub fn is_pruned_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    PRUNED_ERROR_PATTERNS.iter().any(|pattern| message.contains(pattern))
+}
+
 pub async fn with_retry<F, Fut, T, E>(
     operation: F,
     retry_delay: u64,
@@ -19,6 +36,11 @@ where
         match operation().await {
             Ok(result) => return Ok(result),
             Err(err) => {
+                if is_pruned_error(&err.to_string()) {
+                    warn!("Operation '{}' asked for data the node pruned, not retrying: {}", operation_name, err);
+                    return Err(err);
+                }
+
                 if attempt > max_retries {
                     error!(
                         "Operation '{}' failed after {} attempts: {}",
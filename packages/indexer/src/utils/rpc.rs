@@ -0,0 +1,81 @@
+//! Connections to the node's RPC endpoints. Authenticated endpoints get the configured
+//! `Authorization` header on every HTTP request and on the WebSocket handshake, and HTTP
+//! requests go through the configured proxy and trust the configured root certificates.
+
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use ethers::providers::{Authorization, ConnectionDetails, Http, Provider, Ws, WsClientError};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION as AUTHORIZATION_HEADER};
+use tracing::warn;
+use url::Url;
+
+use crate::config::Config;
+
+static AUTHORIZATION: OnceLock<Authorization> = OnceLock::new();
+
+/// Client for HTTP endpoints, when the defaults are not enough
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Configure how RPC endpoints are reached, once at startup before any connection is made.
+///
+/// `RPC_AUTHORIZATION` is sent as the `Authorization` header (e.g. `Bearer <token>`). The
+/// other settings only apply to HTTP, as the WebSocket transport can't be given a connector:
+/// root certificates from `RPC_CA_CERT` are trusted on top of the built-in ones,
+/// `RPC_TLS_INSECURE` disables certificate verification and requests go through `RPC_PROXY`.
+pub fn configure(config: &Config) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    if let Some(authorization) = &config.rpc_authorization {
+        let mut value = HeaderValue::from_str(authorization).context("Invalid RPC authorization")?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION_HEADER, value);
+        let _ = AUTHORIZATION.set(Authorization::raw(authorization));
+    }
+
+    let mut builder = reqwest::Client::builder().default_headers(headers);
+    if let Some(ca_cert_file) = &config.rpc_ca_cert {
+        let pem = std::fs::read(ca_cert_file)
+            .with_context(|| format!("Failed to read RPC CA certificates from {}", ca_cert_file))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid RPC CA certificates in {}", ca_cert_file))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if config.rpc_tls_insecure {
+        warn!("TLS certificate verification is disabled for RPC endpoints over HTTP");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(proxy) = &config.rpc_proxy {
+        // socks5h:// resolves hostnames through the proxy, socks5:// resolves them locally
+        if !["http://", "https://", "socks5://", "socks5h://"].iter().any(|scheme| proxy.starts_with(scheme)) {
+            anyhow::bail!("RPC_PROXY must be an http://, https://, socks5:// or socks5h:// proxy URL");
+        }
+        builder = builder.proxy(reqwest::Proxy::all(proxy.as_str()).context("Invalid RPC_PROXY")?);
+    }
+
+    let client = builder.build().context("Failed to create RPC HTTP client")?;
+    if HTTP_CLIENT.set(client).is_err() {
+        warn!("RPC connections already configured, ignoring the new settings");
+    }
+    Ok(())
+}
+
+/// HTTP transport to `url`
+pub fn http_transport(url: &str) -> Result<Http> {
+    let url = Url::parse(url)?;
+    match HTTP_CLIENT.get() {
+        Some(client) => Ok(Http::new_with_client(url, client.clone())),
+        None => Ok(Http::new(url)),
+    }
+}
+
+/// HTTP provider for `url`
+pub fn http_provider(url: &str) -> Result<Provider<Http>> {
+    Ok(Provider::new(http_transport(url)?))
+}
+
+/// Open a WebSocket connection to `url`
+pub async fn connect_ws(url: &str) -> Result<Ws, WsClientError> {
+    Ws::connect(ConnectionDetails::new(url, AUTHORIZATION.get().cloned())).await
+}
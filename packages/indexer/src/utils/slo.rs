@@ -0,0 +1,146 @@
+//! Service level objectives, evaluated from good/bad samples collected in-process
+//! as blocks are persisted and the sync lag is checked
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// One objective, e.g. "99% of blocks persisted within 2s", with its recent samples
+#[derive(Debug)]
+pub struct Objective {
+    name: &'static str,
+    /// Fraction of samples that must be good, e.g. 0.99
+    target: f64,
+    /// Time of each sample and whether it met the objective, oldest first
+    samples: Mutex<VecDeque<(Instant, bool)>>,
+}
+
+impl Objective {
+    fn new(name: &'static str, target: f64) -> Self {
+        Self {
+            name,
+            target,
+            samples: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn target(&self) -> f64 {
+        self.target
+    }
+
+    /// Record a sample, dropping those older than `retention`
+    fn record(&self, good: bool, retention: Duration) {
+        let now = Instant::now();
+        let mut samples = self.samples.lock().unwrap();
+        while samples.front().is_some_and(|(at, _)| now.duration_since(*at) > retention) {
+            samples.pop_front();
+        }
+        samples.push_back((now, good));
+    }
+
+    /// Fraction of good samples over the last `window`, None without samples
+    pub fn compliance(&self, window: Duration) -> Option<f64> {
+        let now = Instant::now();
+        let samples = self.samples.lock().unwrap();
+        let (total, good) = samples.iter()
+            .rev()
+            .take_while(|(at, _)| now.duration_since(*at) <= window)
+            .fold((0u64, 0u64), |(total, good), (_, ok)| (total + 1, good + *ok as u64));
+
+        (total > 0).then(|| good as f64 / total as f64)
+    }
+
+    /// How many times faster than sustainable the error budget was spent over the last
+    /// `window`: 1 exhausts the budget exactly at the end of the SLO period
+    pub fn burn_rate(&self, window: Duration) -> Option<f64> {
+        self.compliance(window).map(|compliance| (1.0 - compliance) / (1.0 - self.target))
+    }
+}
+
+/// Collects samples for the indexer's latency objectives:
+/// - persist latency: new head blocks stored within a bound of their timestamp
+/// - sync lag: the latest indexed block within a number of blocks of the chain head
+///
+/// Samples are only taken once activated, so a backfill isn't counted against the objectives
+#[derive(Debug)]
+pub struct SloTracker {
+    /// Longest window objectives are evaluated over
+    window: Duration,
+    persist_latency: Objective,
+    max_persist_latency: Duration,
+    sync_lag: Objective,
+    max_lag_blocks: u64,
+    active: AtomicBool,
+    /// Highest block recorded, so gap repairs and reorgs below it are not counted
+    highest_block: AtomicU64,
+}
+
+impl SloTracker {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window: Duration::from_secs(window_secs),
+            persist_latency: Objective::new("persist_latency", 0.99),
+            max_persist_latency: Duration::from_secs(2),
+            sync_lag: Objective::new("sync_lag", 0.99),
+            max_lag_blocks: 5,
+            active: AtomicBool::new(false),
+            highest_block: AtomicU64::new(0),
+        }
+    }
+
+    /// Configure the persist latency objective
+    pub fn with_persist_latency(mut self, max_latency_ms: u64, target: f64) -> Self {
+        self.max_persist_latency = Duration::from_millis(max_latency_ms);
+        self.persist_latency = Objective::new("persist_latency", target);
+        self
+    }
+
+    /// Configure the sync lag objective
+    pub fn with_sync_lag(mut self, max_lag_blocks: u64, target: f64) -> Self {
+        self.max_lag_blocks = max_lag_blocks;
+        self.sync_lag = Objective::new("sync_lag", target);
+        self
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn objectives(&self) -> [&Objective; 2] {
+        [&self.persist_latency, &self.sync_lag]
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Start taking samples
+    pub fn activate(&self) {
+        self.active.store(true, Ordering::Relaxed);
+    }
+
+    /// Record that a block was persisted. Unless the chain reports millisecond timestamps,
+    /// `timestamp_ms` is the start of the block's second, so the measured latency can exceed
+    /// the real one by up to a second.
+    pub fn record_persisted(&self, number: u64, timestamp_ms: u64) {
+        if !self.is_active() || self.highest_block.fetch_max(number, Ordering::Relaxed) >= number {
+            return;
+        }
+
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let latency = Duration::from_millis(now_ms.saturating_sub(timestamp_ms));
+        self.persist_latency.record(latency <= self.max_persist_latency, self.window);
+    }
+
+    /// Record how many blocks the indexer is behind the chain head
+    pub fn record_lag(&self, lag: u64) {
+        if self.is_active() {
+            self.sync_lag.record(lag <= self.max_lag_blocks, self.window);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-/// Utility functions for time-related operations
+//! Utility functions for time-related operations
 
 /// Format a duration in seconds to a human-readable string
 /// For example, 65 seconds becomes "1m 5s"
@@ -35,13 +35,13 @@ pub fn format_rate(blocks_per_second: f64) -> String {
     if blocks_per_second < 0.01 {
         // Less than 0.01 blocks per second, show as blocks per hour
         let blocks_per_hour = blocks_per_second * 3600.0;
-        return format!("{:.2} blocks/hour", blocks_per_hour);
+        format!("{:.2} blocks/hour", blocks_per_hour)
     } else if blocks_per_second < 1.0 {
         // Less than 1 block per second, show as blocks per minute
         let blocks_per_minute = blocks_per_second * 60.0;
-        return format!("{:.2} blocks/min", blocks_per_minute);
+        format!("{:.2} blocks/min", blocks_per_minute)
     } else {
         // 1 or more blocks per second
-        return format!("{:.2} blocks/sec", blocks_per_second);
+        format!("{:.2} blocks/sec", blocks_per_second)
     }
 }
\ No newline at end of file